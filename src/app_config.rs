@@ -1,4 +1,4 @@
-use std::{fs, path::Path};
+use std::{env, fs, path::Path};
 
 use bevy::{
     log::{Level, LogPlugin},
@@ -6,11 +6,15 @@ use bevy::{
     window::{PresentMode, WindowPlugin, WindowResolution},
 };
 use bevy_winit::{UpdateMode, WinitSettings};
+use schemars::JsonSchema;
 use serde::Deserialize;
 
 const CONFIG_PATH: &str = "assets/config.toml";
+const ENV_PREFIX: &str = "ATMOS_";
+const CONFIG_PATH_ENV: &str = "ATMOS_CONFIG";
+const PROD_QUIET_TARGETS: &str = "atmos::scenes::spawn=warn,atmos::scenes::loaders=warn";
 
-#[derive(Resource, Debug, Clone, Deserialize)]
+#[derive(Resource, Debug, Clone, Deserialize, JsonSchema)]
 #[serde(default)]
 pub struct AppConfig {
     pub mode: AppMode,
@@ -19,6 +23,8 @@ pub struct AppConfig {
     pub window: WindowConfig,
     pub msaa_samples: Option<u32>,
     pub mouse: MouseConfig,
+    pub scene: String,
+    pub dev_tools: DevToolsConfig,
 }
 
 impl Default for AppConfig {
@@ -30,11 +36,13 @@ impl Default for AppConfig {
             window: WindowConfig::default(),
             msaa_samples: Some(4),
             mouse: MouseConfig::default(),
+            scene: "main".to_string(),
+            dev_tools: DevToolsConfig::default(),
         }
     }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum AppMode {
     Dev,
@@ -47,7 +55,7 @@ impl Default for AppMode {
     }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 #[serde(default)]
 pub struct WindowConfig {
     pub title: String,
@@ -67,7 +75,7 @@ impl Default for WindowConfig {
     }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 #[serde(default)]
 pub struct MouseConfig {
     pub sensitivity: f32,
@@ -85,7 +93,33 @@ impl Default for MouseConfig {
     }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+/// Dev-only tooling. Ignored in prod mode, where none of it is added to the app.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct DevToolsConfig {
+    /// Whether the world inspector is visible at startup.
+    pub inspector: bool,
+    /// Whether collider debug rendering is on at startup.
+    pub physics_debug: bool,
+    pub toggle_inspector: String,
+    pub toggle_physics_debug: String,
+    /// Lists config diagnostics for the active scene on screen.
+    pub diagnostics_overlay: bool,
+}
+
+impl Default for DevToolsConfig {
+    fn default() -> Self {
+        Self {
+            inspector: true,
+            physics_debug: true,
+            toggle_inspector: "F1".to_string(),
+            toggle_physics_debug: "F2".to_string(),
+            diagnostics_overlay: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum PresentModeConfig {
     AutoVsync,
@@ -113,39 +147,259 @@ impl PresentModeConfig {
     }
 }
 
+/// Builds the app config from defaults, then `assets/config.toml` (or `--config` /
+/// `ATMOS_CONFIG`), then `ATMOS_*` environment variables, then command-line flags.
 pub fn load_app_config() -> AppConfig {
-    let default_config = AppConfig::default();
-    if !Path::new(CONFIG_PATH).exists() {
-        warn!(
-            "Config file '{}' not found; using defaults.",
-            CONFIG_PATH
-        );
-        return default_config;
+    let args: Vec<String> = env::args().skip(1).collect();
+    let cli = match parse_cli_args(&args) {
+        Ok(cli) => cli,
+        Err(err) => {
+            eprintln!("{err}\n\n{}", usage());
+            std::process::exit(2);
+        }
+    };
+    if cli.help {
+        println!("{}", usage());
+        std::process::exit(0);
     }
 
-    let contents = match fs::read_to_string(CONFIG_PATH) {
-        Ok(text) => text,
+    let config_path = cli
+        .config_path
+        .clone()
+        .or_else(|| env::var(CONFIG_PATH_ENV).ok())
+        .unwrap_or_else(|| CONFIG_PATH.to_string());
+
+    let env_overrides = match env_overrides() {
+        Ok(overrides) => overrides,
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(2);
+        }
+    };
+
+    let (mut table, missing) = match load_config_table(&config_path) {
+        Ok(Some(table)) => (table, false),
+        Ok(None) => (toml::Table::new(), true),
         Err(err) => {
-            warn!("Failed to read {}: {err}; using defaults.", CONFIG_PATH);
-            return default_config;
+            eprintln!("{err}");
+            std::process::exit(1);
         }
     };
+    for (path, value) in env_overrides.iter().chain(cli.overrides.iter()) {
+        set_config_value(&mut table, path, value.clone());
+    }
 
-    match toml::from_str::<AppConfig>(&contents) {
-        Ok(cfg) => cfg,
+    // Logging is not set up yet, so failures go straight to stderr. A config file that
+    // exists but is broken stops the app in every mode: falling back to defaults would
+    // quietly run a prod config as dev.
+    let config = match config_from_table(table) {
+        Ok(config) => config,
         Err(err) => {
-            warn!("Failed to parse {}: {err}; using defaults.", CONFIG_PATH);
-            default_config
+            eprintln!("Failed to load {config_path}: {err}");
+            std::process::exit(1);
+        }
+    };
+
+    // Without a config file dev runs on defaults, but prod refuses to start.
+    if missing {
+        if config.is_prod() {
+            eprintln!("Config file '{config_path}' not found.");
+            std::process::exit(1);
+        }
+        eprintln!("Config file '{config_path}' not found; using defaults.");
+    }
+    config
+}
+
+fn config_from_table(table: toml::Table) -> Result<AppConfig, toml::de::Error> {
+    toml::Value::Table(table).try_into::<AppConfig>()
+}
+
+/// The config file as a table, or `None` when there is no file at `config_path`.
+fn load_config_table(config_path: &str) -> Result<Option<toml::Table>, String> {
+    if !Path::new(config_path).exists() {
+        return Ok(None);
+    }
+
+    let contents = fs::read_to_string(config_path)
+        .map_err(|err| format!("Failed to read {config_path}: {err}"))?;
+
+    toml::from_str::<toml::Table>(&contents)
+        .map(Some)
+        .map_err(|err| format!("Failed to parse {config_path}: {err}"))
+}
+
+#[derive(Clone, Copy)]
+enum OverrideKind {
+    Str,
+    Int,
+    Float,
+    Bool,
+}
+
+/// `AppConfig` fields that can be overridden, with their command-line flags. The
+/// environment variable is the field path upper-cased with `.` replaced by `_`, prefixed
+/// with `ATMOS_` (e.g. `window.width` -> `ATMOS_WINDOW_WIDTH`).
+const OVERRIDE_FIELDS: &[(&str, OverrideKind, &[&str])] = &[
+    ("mode", OverrideKind::Str, &["--mode"]),
+    ("scene", OverrideKind::Str, &["--scene"]),
+    ("fps_limit", OverrideKind::Float, &["--fps-limit", "--fps"]),
+    ("log_level", OverrideKind::Str, &["--log-level"]),
+    ("msaa_samples", OverrideKind::Int, &["--msaa-samples", "--msaa"]),
+    ("window.title", OverrideKind::Str, &["--window-title", "--title"]),
+    ("window.width", OverrideKind::Int, &["--window-width", "--width"]),
+    ("window.height", OverrideKind::Int, &["--window-height", "--height"]),
+    ("window.present_mode", OverrideKind::Str, &["--present-mode"]),
+    ("mouse.sensitivity", OverrideKind::Float, &["--mouse-sensitivity", "--sensitivity"]),
+    ("mouse.invert_x", OverrideKind::Bool, &["--mouse-invert-x", "--invert-x"]),
+    ("mouse.invert_y", OverrideKind::Bool, &["--mouse-invert-y", "--invert-y"]),
+    ("dev_tools.inspector", OverrideKind::Bool, &["--inspector"]),
+    ("dev_tools.physics_debug", OverrideKind::Bool, &["--physics-debug"]),
+    ("dev_tools.toggle_inspector", OverrideKind::Str, &["--toggle-inspector"]),
+    ("dev_tools.toggle_physics_debug", OverrideKind::Str, &["--toggle-physics-debug"]),
+    ("dev_tools.diagnostics_overlay", OverrideKind::Bool, &["--diagnostics-overlay"]),
+];
+
+#[derive(Default)]
+struct CliArgs {
+    help: bool,
+    config_path: Option<String>,
+    overrides: Vec<(&'static str, toml::Value)>,
+}
+
+fn parse_cli_args(args: &[String]) -> Result<CliArgs, String> {
+    let mut cli = CliArgs::default();
+    let mut iter = args.iter().peekable();
+    while let Some(arg) = iter.next() {
+        if arg == "-h" || arg == "--help" {
+            cli.help = true;
+            continue;
+        }
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) => (flag, Some(value.to_string())),
+            None => (arg.as_str(), None),
+        };
+
+        if flag == "--config" {
+            let value = match inline_value {
+                Some(value) => value,
+                None => iter
+                    .next()
+                    .cloned()
+                    .ok_or_else(|| format!("Missing value for {flag}."))?,
+            };
+            cli.config_path = Some(value);
+            continue;
+        }
+
+        let Some((path, kind, _)) = OVERRIDE_FIELDS
+            .iter()
+            .find(|(_, _, flags)| flags.contains(&flag))
+        else {
+            return Err(format!("Unknown argument '{arg}'."));
+        };
+
+        let raw = match (inline_value, kind) {
+            (Some(value), _) => value,
+            (None, OverrideKind::Bool)
+                if iter.peek().is_none_or(|next| next.starts_with("--")) =>
+            {
+                "true".to_string()
+            }
+            (None, _) => iter
+                .next()
+                .cloned()
+                .ok_or_else(|| format!("Missing value for {flag}."))?,
+        };
+        let value = parse_override(*kind, &raw)
+            .ok_or_else(|| format!("Invalid value '{raw}' for {flag}."))?;
+        cli.overrides.push((*path, value));
+    }
+    Ok(cli)
+}
+
+fn env_overrides() -> Result<Vec<(&'static str, toml::Value)>, String> {
+    let mut overrides = Vec::new();
+    for (path, kind, _) in OVERRIDE_FIELDS {
+        let name = format!("{ENV_PREFIX}{}", path.replace('.', "_").to_ascii_uppercase());
+        let Ok(raw) = env::var(&name) else {
+            continue;
+        };
+        let value = parse_override(*kind, &raw)
+            .ok_or_else(|| format!("Invalid value '{raw}' for {name}."))?;
+        overrides.push((*path, value));
+    }
+    Ok(overrides)
+}
+
+fn parse_override(kind: OverrideKind, raw: &str) -> Option<toml::Value> {
+    let raw = raw.trim();
+    match kind {
+        OverrideKind::Str => Some(toml::Value::String(raw.to_string())),
+        OverrideKind::Int => raw.parse::<i64>().ok().map(toml::Value::Integer),
+        OverrideKind::Float => raw.parse::<f64>().ok().map(toml::Value::Float),
+        OverrideKind::Bool => match raw.to_ascii_lowercase().as_str() {
+            "true" | "1" | "yes" | "on" => Some(toml::Value::Boolean(true)),
+            "false" | "0" | "no" | "off" => Some(toml::Value::Boolean(false)),
+            _ => None,
+        },
+    }
+}
+
+fn set_config_value(table: &mut toml::Table, path: &str, value: toml::Value) {
+    let mut current = table;
+    let mut segments = path.split('.').peekable();
+    while let Some(segment) = segments.next() {
+        if segments.peek().is_none() {
+            current.insert(segment.to_string(), value);
+            return;
+        }
+        let entry = current
+            .entry(segment.to_string())
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+        if !entry.is_table() {
+            *entry = toml::Value::Table(toml::Table::new());
         }
+        let toml::Value::Table(next) = entry else {
+            unreachable!();
+        };
+        current = next;
+    }
+}
+
+fn usage() -> String {
+    let mut text = String::from(
+        "Usage: atmos [OPTIONS]\n\nOptions:\n  --config <PATH>  Config file (env: ATMOS_CONFIG, default: assets/config.toml)\n",
+    );
+    for (path, _, flags) in OVERRIDE_FIELDS {
+        let env_name = format!("{ENV_PREFIX}{}", path.replace('.', "_").to_ascii_uppercase());
+        text.push_str(&format!(
+            "  {} <VALUE>  Overrides `{path}` (env: {env_name})\n",
+            flags.join(", ")
+        ));
     }
+    text.push_str("  -h, --help  Print this help\n\nPrecedence: defaults < config file < ATMOS_* env < flags.");
+    text
 }
 
 impl AppConfig {
+    pub fn is_dev(&self) -> bool {
+        self.mode == AppMode::Dev
+    }
+
+    pub fn is_prod(&self) -> bool {
+        self.mode == AppMode::Prod
+    }
+
     pub fn to_log_plugin(&self) -> LogPlugin {
         let mut log = LogPlugin::default();
         if let Some(level) = self.log_level() {
             log.level = level;
         }
+        if self.is_prod() {
+            // Per-entity spawn and config load chatter is only useful while authoring.
+            log.filter = format!("{},{PROD_QUIET_TARGETS}", log.filter);
+        }
         log
     }
 
@@ -200,3 +454,79 @@ impl AppConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(text: &str) -> toml::Table {
+        toml::from_str(text).expect("test config is valid TOML")
+    }
+
+    #[test]
+    fn a_config_that_does_not_deserialize_is_an_error() {
+        let config = table("mode = \"prod\"\n[window]\nwidth = \"wide\"\n");
+        assert!(config_from_table(config).is_err());
+    }
+
+    #[test]
+    fn an_unknown_mode_is_an_error_rather_than_dev() {
+        assert!(config_from_table(table("mode = \"production\"")).is_err());
+    }
+
+    #[test]
+    fn missing_fields_take_their_defaults() {
+        let config = config_from_table(table("mode = \"prod\"")).unwrap();
+        assert!(config.is_prod());
+        assert_eq!(config.scene, "main");
+        assert_eq!(config.window.width, 1280);
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn flags_take_inline_or_following_values() {
+        let cli = parse_cli_args(&args(&[
+            "--scene=arena",
+            "--width",
+            "800",
+            "--config",
+            "other.toml",
+            "--invert-y",
+            "--fps",
+            "30",
+        ]))
+        .unwrap();
+        assert_eq!(cli.config_path.as_deref(), Some("other.toml"));
+        assert_eq!(
+            cli.overrides,
+            [
+                ("scene", toml::Value::String("arena".to_string())),
+                ("window.width", toml::Value::Integer(800)),
+                ("mouse.invert_y", toml::Value::Boolean(true)),
+                ("fps_limit", toml::Value::Float(30.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn bad_arguments_are_errors() {
+        assert!(parse_cli_args(&args(&["--colour", "red"])).is_err());
+        assert!(parse_cli_args(&args(&["--width", "wide"])).is_err());
+        assert!(parse_cli_args(&args(&["--scene"])).is_err());
+        assert!(parse_cli_args(&args(&["--inspector=maybe"])).is_err());
+    }
+
+    #[test]
+    fn overrides_replace_nested_fields() {
+        let mut config = table("mode = \"dev\"\n[window]\ntitle = \"Atmos\"\nwidth = 1280\n");
+        set_config_value(&mut config, "window.width", toml::Value::Integer(640));
+        set_config_value(&mut config, "mouse.invert_x", toml::Value::Boolean(true));
+        let config = config_from_table(config).unwrap();
+        assert_eq!(config.window.width, 640);
+        assert_eq!(config.window.title, "Atmos");
+        assert!(config.mouse.invert_x);
+    }
+}
@@ -0,0 +1,95 @@
+use std::{env, fs, path::Path, process::ExitCode};
+
+use atmos::scenes::{
+    migrate_text, overlay_files, scene_files, SceneFormat, SceneManager, CURRENT_FORMAT_VERSION,
+};
+
+const USAGE: &str = "Usage: atmos-migrate [--check] [SCENE ...]\n\n\
+Upgrades the TOML, JSON and RON files of scene directories under assets/scenes to the\n\
+current format_version, rewriting them in place and listing what changed. SCENE is a\n\
+scene name or its directory path; with no arguments every scene and the shared overlays\n\
+are migrated. With --check nothing is written; the command exits with status 1 if any\n\
+file needs migrating.";
+
+fn main() -> ExitCode {
+    let mut check = false;
+    let mut scenes = Vec::new();
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "--check" => check = true,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return ExitCode::SUCCESS;
+            }
+            flag if flag.starts_with('-') => {
+                eprintln!("Unknown argument '{flag}'.\n\n{USAGE}");
+                return ExitCode::from(2);
+            }
+            scene => scenes.push(scene_name(scene)),
+        }
+    }
+
+    let mut files = Vec::new();
+    if scenes.is_empty() {
+        scenes = SceneManager::available_scenes();
+        files.extend(overlay_files());
+    }
+    if scenes.is_empty() {
+        eprintln!("No scenes found.");
+        return ExitCode::from(2);
+    }
+    for scene in &scenes {
+        files.extend(scene_files(scene));
+    }
+
+    let mut pending = 0;
+    let mut failed = false;
+    for file in &files {
+        let path = file.display();
+        let migrated = fs::read_to_string(file)
+            .map_err(|err| format!("Failed to read: {err}."))
+            .and_then(|text| migrate_text(SceneFormat::of(file), &text));
+        let migrated = match migrated {
+            Ok(migrated) if migrated.changes.is_empty() => continue,
+            Ok(migrated) => migrated,
+            Err(err) => {
+                eprintln!("{path}: {err}");
+                failed = true;
+                continue;
+            }
+        };
+
+        pending += 1;
+        println!("{path}: format_version {} -> {CURRENT_FORMAT_VERSION}", migrated.from);
+        for change in &migrated.changes {
+            println!("  {change}");
+        }
+        if !check {
+            if let Err(err) = fs::write(file, &migrated.text) {
+                eprintln!("{path}: Failed to write: {err}.");
+                failed = true;
+            }
+        }
+    }
+
+    if pending == 0 {
+        println!("All {} file(s) are at format_version {CURRENT_FORMAT_VERSION}.", files.len());
+    } else if check {
+        println!("{pending} file(s) need migrating; run without --check to rewrite them.");
+    } else {
+        println!("Migrated {pending} file(s).");
+    }
+    if failed || (check && pending > 0) {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Accepts `main` as well as `assets/scenes/main/`.
+fn scene_name(arg: &str) -> String {
+    Path::new(arg.trim_end_matches('/'))
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| arg.to_string())
+}
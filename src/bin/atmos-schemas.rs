@@ -0,0 +1,49 @@
+use std::{env, fs, path::Path, process::ExitCode};
+
+use atmos::schemas::{generate_schemas, SCHEMA_DIR};
+
+const USAGE: &str = "Usage: atmos-schemas [--check]\n\n\
+Writes the JSON schemas in schemas/ from the Rust config types. With --check nothing is\n\
+written; the command exits with status 1 if any checked-in schema is out of date.";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let check = match args.as_slice() {
+        [] => false,
+        [flag] if flag == "--check" => true,
+        [flag] if flag == "-h" || flag == "--help" => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        _ => {
+            eprintln!("{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut stale = Vec::new();
+    for (file, contents) in generate_schemas() {
+        let path = Path::new(SCHEMA_DIR).join(file);
+        if fs::read_to_string(&path).is_ok_and(|current| current == contents) {
+            continue;
+        }
+        if check {
+            stale.push(path.display().to_string());
+            continue;
+        }
+        if let Err(err) = fs::write(&path, contents) {
+            eprintln!("Failed to write {}: {err}", path.display());
+            return ExitCode::FAILURE;
+        }
+        println!("Wrote {}", path.display());
+    }
+
+    if stale.is_empty() {
+        return ExitCode::SUCCESS;
+    }
+    eprintln!("Stale schemas (run `cargo run --bin atmos-schemas` to regenerate):");
+    for path in stale {
+        eprintln!("  {path}");
+    }
+    ExitCode::FAILURE
+}
@@ -0,0 +1,79 @@
+use std::{
+    env,
+    path::{Component, Path},
+    process::ExitCode,
+};
+
+use atmos::scenes::{format_report, validate_scene, SceneManager, SCENE_ROOT};
+
+const USAGE: &str = "Usage: atmos-validate [SCENE ...]\n\n\
+Validates scene directories under assets/scenes without starting the game. SCENE is a\n\
+scene name or the path of a directory directly inside assets/scenes; with no arguments\n\
+every scene is checked. Exits with status 1 when any error is found.";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+    if let Some(flag) = args.iter().find(|arg| arg.starts_with('-')) {
+        eprintln!("Unknown argument '{flag}'.\n\n{USAGE}");
+        return ExitCode::from(2);
+    }
+
+    let scenes: Vec<String> = if args.is_empty() {
+        SceneManager::available_scenes()
+    } else {
+        match args.iter().map(|arg| scene_name(arg)).collect() {
+            Ok(scenes) => scenes,
+            Err(err) => {
+                eprintln!("{err}\n\n{USAGE}");
+                return ExitCode::from(2);
+            }
+        }
+    };
+    if scenes.is_empty() {
+        eprintln!("No scenes found.");
+        return ExitCode::from(2);
+    }
+
+    let mut failed = false;
+    for scene in &scenes {
+        let diagnostics = validate_scene(scene);
+        if diagnostics.is_empty() {
+            println!("Scene '{scene}': ok");
+            continue;
+        }
+        failed |= diagnostics.iter().any(|diagnostic| diagnostic.is_error());
+        println!("Scene '{scene}': {}", format_report(&diagnostics));
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Accepts `main` as well as `assets/scenes/main/`. Scenes are only ever loaded from
+/// `SCENE_ROOT`, so a path to a directory anywhere else is rejected rather than quietly
+/// validating the scene of the same name.
+fn scene_name(arg: &str) -> Result<String, String> {
+    let path = Path::new(arg.trim_end_matches('/'));
+    let mut components = path.components();
+    if let (Some(Component::Normal(_)), None) = (components.next(), components.next()) {
+        return Ok(arg.to_string());
+    }
+    let outside = || format!("'{arg}' is not a scene directory inside {SCENE_ROOT}.");
+    let (Ok(dir), Ok(root)) = (path.canonicalize(), Path::new(SCENE_ROOT).canonicalize())
+    else {
+        return Err(outside());
+    };
+    if !dir.is_dir() || dir.parent() != Some(root.as_path()) {
+        return Err(outside());
+    }
+    dir.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(outside)
+}
@@ -0,0 +1,72 @@
+use bevy::prelude::*;
+use bevy_inspector_egui::{bevy_egui::EguiPlugin, quick::WorldInspectorPlugin};
+use bevy_rapier3d::prelude::{DebugRenderContext, RapierDebugRenderPlugin};
+
+use crate::app_config::{AppConfig, DevToolsConfig};
+use crate::scenes::resolve_key_or_warn;
+
+/// World inspector and collider debug rendering. Only added in dev mode; both can be
+/// toggled at runtime with the keys from `[dev_tools]` in `assets/config.toml`.
+pub struct DevToolsPlugin;
+
+#[derive(Resource, Debug)]
+pub struct DevTools {
+    pub inspector: bool,
+    toggle_inspector: Option<KeyCode>,
+    toggle_physics_debug: Option<KeyCode>,
+}
+
+impl Plugin for DevToolsPlugin {
+    fn build(&self, app: &mut App) {
+        let config = app
+            .world()
+            .get_resource::<AppConfig>()
+            .map(|config| config.dev_tools.clone())
+            .unwrap_or_else(DevToolsConfig::default);
+
+        app.insert_resource(DevTools {
+            inspector: config.inspector,
+            toggle_inspector: resolve_key_or_warn(&config.toggle_inspector, "toggle inspector"),
+            toggle_physics_debug: resolve_key_or_warn(
+                &config.toggle_physics_debug,
+                "toggle physics debug",
+            ),
+        });
+        app.add_plugins(RapierDebugRenderPlugin {
+            enabled: config.physics_debug,
+            ..default()
+        })
+        .add_plugins(EguiPlugin::default())
+        .add_plugins(WorldInspectorPlugin::new().run_if(inspector_visible))
+        .add_systems(Update, toggle_dev_tools);
+    }
+}
+
+fn inspector_visible(dev_tools: Res<DevTools>) -> bool {
+    dev_tools.inspector
+}
+
+fn toggle_dev_tools(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut dev_tools: ResMut<DevTools>,
+    mut debug_render: ResMut<DebugRenderContext>,
+) {
+    if dev_tools
+        .toggle_inspector
+        .is_some_and(|key| keys.just_pressed(key))
+    {
+        dev_tools.inspector = !dev_tools.inspector;
+        info!("World inspector {}.", on_off(dev_tools.inspector));
+    }
+    if dev_tools
+        .toggle_physics_debug
+        .is_some_and(|key| keys.just_pressed(key))
+    {
+        debug_render.enabled = !debug_render.enabled;
+        info!("Physics debug render {}.", on_off(debug_render.enabled));
+    }
+}
+
+fn on_off(enabled: bool) -> &'static str {
+    if enabled { "on" } else { "off" }
+}
@@ -0,0 +1,4 @@
+pub mod app_config;
+pub mod dev_tools;
+pub mod scenes;
+pub mod schemas;
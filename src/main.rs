@@ -1,14 +1,12 @@
-mod app_config;
-mod scenes;
-
-use app_config::load_app_config;
+use atmos::app_config::load_app_config;
+use atmos::dev_tools::DevToolsPlugin;
+use atmos::scenes;
 use bevy::prelude::*;
 use bevy::window::{CursorGrabMode, CursorOptions, PrimaryWindow};
 use bevy_rapier3d::prelude::*;
 use bevy::winit::WinitSettings;
-use bevy_inspector_egui::{bevy_egui::EguiPlugin, quick::WorldInspectorPlugin};
 
-fn main() {
+fn main() -> AppExit {
     let app_config = load_app_config();
     let log_plugin = app_config.to_log_plugin();
     let window_plugin = app_config.to_window_plugin();
@@ -25,11 +23,13 @@ fn main() {
     )
     .add_systems(Startup, configure_cursor_options)
     .add_plugins(RapierPhysicsPlugin::<NoUserData>::default())
-    .add_plugins(RapierDebugRenderPlugin::default())
-    .add_plugins(EguiPlugin::default())
-    .add_plugins(WorldInspectorPlugin::new())
-    .add_plugins(scenes::ScenePlugin::new("main"))
-    .run();
+    .add_plugins(scenes::ScenePlugin::new(app_config.scene.clone()));
+
+    if app_config.is_dev() {
+        app.add_plugins(DevToolsPlugin);
+    }
+
+    app.run()
 }
 
 fn configure_cursor_options(mut windows: Query<&mut CursorOptions, With<PrimaryWindow>>) {
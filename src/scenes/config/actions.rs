@@ -1,8 +1,12 @@
+use schemars::JsonSchema;
 use serde::Deserialize;
 
 use super::transforms::Vec3Config;
 
-#[derive(Debug, Deserialize, Clone)]
+/// Template the shoot action fires, relative to the scene directory.
+pub const PROJECTILE_TEMPLATE: &str = "entities/sphere.3D.toml";
+
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
 #[serde(default)]
 pub struct ShootActionConfig {
     pub name: String,
@@ -33,7 +37,7 @@ impl Default for ShootActionConfig {
     }
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
 #[serde(default)]
 pub struct SprintActionConfig {
     pub name: String,
@@ -51,7 +55,7 @@ impl Default for SprintActionConfig {
     }
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
 #[serde(default)]
 pub struct ZoomActionConfig {
     pub name: String,
@@ -1,5 +1,6 @@
 use bevy::prelude::*;
 
+pub const ASSET_ROOT: &str = "assets";
 pub const SCENE_ROOT: &str = "assets/scenes";
 #[allow(dead_code)]
 pub const OVERLAY_ROOT: &str = "assets/overlay";
@@ -1,6 +1,7 @@
+use schemars::JsonSchema;
 use serde::Deserialize;
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
 #[serde(default)]
 pub struct BoundingBoxConfig {
     pub shape: String,
@@ -20,7 +21,7 @@ impl Default for BoundingBoxConfig {
     }
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
 #[serde(default)]
 pub struct BoundsAxisConfig {
     pub min: f32,
@@ -1,8 +1,9 @@
+use schemars::JsonSchema;
 use serde::Deserialize;
 
 use super::transforms::Vec3Config;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct CameraConfig {
     pub name: String,
     #[serde(default)]
@@ -18,7 +19,7 @@ impl Default for CameraConfig {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct TransformConfig {
     #[serde(default = "default_camera_position")]
     pub position: Vec3Config,
@@ -9,11 +9,8 @@ pub fn default_color_rgb() -> [u8; 3] {
 }
 
 pub fn parse_color(color_name: &str) -> Option<[u8; 3]> {
-    match csscolorparser::parse(color_name) {
-        Ok(parsed) => {
-            let [r, g, b, _a] = parsed.to_rgba8();
-            Some([r, g, b])
-        }
+    match try_parse_color(color_name) {
+        Ok(rgb) => Some(rgb),
         Err(err) => {
             warn!("Failed to parse color '{color_name}': {err}");
             None
@@ -21,6 +18,11 @@ pub fn parse_color(color_name: &str) -> Option<[u8; 3]> {
     }
 }
 
+pub fn try_parse_color(color_name: &str) -> Result<[u8; 3], csscolorparser::ParseColorError> {
+    let [r, g, b, _a] = csscolorparser::parse(color_name)?.to_rgba8();
+    Ok([r, g, b])
+}
+
 pub fn default_circle_color_name() -> String {
     "white".to_string()
 }
@@ -1,8 +1,11 @@
+use bevy::asset::Asset;
+use bevy::reflect::TypePath;
+use schemars::JsonSchema;
 use serde::Deserialize;
 
 use super::{EntityOverrides, TransformOverrides, Vec3Config};
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Asset, TypePath, Debug, Deserialize, JsonSchema, Clone)]
 pub struct ComboTemplate {
     pub name: String,
     #[serde(default)]
@@ -15,7 +18,7 @@ pub struct ComboTemplate {
     pub stack: Option<ComboStackConfig>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
 pub struct ComboPart {
     pub template: String,
     #[serde(default)]
@@ -30,7 +33,7 @@ pub struct ComboPart {
     pub physics_root: bool,
 }
 
-#[derive(Debug, Deserialize, Clone, Default)]
+#[derive(Debug, Deserialize, JsonSchema, Clone, Default)]
 pub struct ComboPhysics {
     #[serde(default)]
     pub shared: bool,
@@ -38,14 +41,14 @@ pub struct ComboPhysics {
     pub root: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
 pub struct AttachConfig {
     pub target: String,
     #[serde(default)]
     pub offset: Vec3Config,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
 pub struct ComboStackConfig {
     pub count: u32,
     #[serde(default)]
@@ -1,12 +1,21 @@
-use serde::Deserialize;
+use bevy::asset::Asset;
+use bevy::math::Vec3;
+use bevy::reflect::TypePath;
+use bevy::transform::components::Transform;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
 use super::light::LightKind;
 use super::material::MaterialConfig;
 use super::physics::PhysicsConfig;
-use super::transforms::{CubeRotationConfig, DimensionsConfig, PositionConfig};
+use super::transforms::{CubeRotationConfig, DimensionsConfig, PositionConfig, ScaleConfig};
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Asset, TypePath, Debug, Deserialize, JsonSchema, Clone)]
 pub struct EntityTemplate {
+    /// Parent template path, relative to the scene. Its tables are merged under this
+    /// file's before deserializing, so only the differences need to be listed here.
+    #[serde(default)]
+    pub extends: Option<String>,
     pub name: String,
     #[serde(default)]
     pub transform: TransformConfig,
@@ -20,7 +29,7 @@ pub struct EntityTemplate {
     pub light: Option<LightComponent>,
 }
 
-#[derive(Debug, Deserialize, Clone, Default)]
+#[derive(Debug, Deserialize, JsonSchema, Clone, Default, PartialEq)]
 pub struct EntityOverrides {
     #[serde(default)]
     pub shape: Option<ShapeOverrides>,
@@ -30,14 +39,23 @@ pub struct EntityOverrides {
     pub light: Option<LightOverridesConfig>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
 pub struct TransformConfig {
     #[serde(default)]
     pub position: PositionConfig,
     #[serde(default)]
     pub rotation: CubeRotationConfig,
-    #[serde(default = "default_unit_scale")]
-    pub scale: f32,
+    #[serde(default)]
+    pub scale: ScaleConfig,
+}
+
+impl TransformConfig {
+    pub fn to_transform(&self) -> Transform {
+        let position = Vec3::new(self.position.x, self.position.y, self.position.z);
+        Transform::from_translation(position)
+            .with_rotation(self.rotation.to_quat(position))
+            .with_scale(self.scale.to_vec3())
+    }
 }
 
 impl Default for TransformConfig {
@@ -45,41 +63,166 @@ impl Default for TransformConfig {
         Self {
             position: PositionConfig::default(),
             rotation: CubeRotationConfig::default(),
-            scale: default_unit_scale(),
+            scale: ScaleConfig::default(),
         }
     }
 }
 
-#[derive(Debug, Deserialize, Clone, Default)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default, PartialEq)]
 pub struct TransformOverrides {
     #[serde(default)]
     pub position: Option<PositionConfig>,
     #[serde(default)]
     pub rotation: Option<CubeRotationConfig>,
     #[serde(default)]
-    pub scale: Option<f32>,
+    pub scale: Option<ScaleConfig>,
 }
 
-#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Deserialize, JsonSchema, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum ShapeKind {
     Box,
     Sphere,
     Circle,
+    /// Upright along Y, sized by `radius` and `height`.
+    Cylinder,
+    /// Upright along Y; `height` runs from the tip of one cap to the other.
+    Capsule,
+    /// Tip up, sized by the base `radius` and `height`.
+    Cone,
+    /// Ring lying flat in XZ; `radius` reaches the middle of the tube, which is
+    /// `tube_radius` thick.
+    Torus,
+    /// Flat in XZ, facing up, sized by `dimensions.width` and `dimensions.depth`.
+    Plane,
+    /// Ramp filling `dimensions`, rising from nothing at the front (+Z) to full height at
+    /// the back (-Z).
+    Wedge,
+    /// Meshes of a glTF file, named by `model`.
+    Model,
+    /// Ground shaped by the grayscale `heightmap`, `dimensions.width` by
+    /// `dimensions.depth` across, rising from the entity's position where the heightmap is
+    /// black to `dimensions.height` above it where it is white.
+    Terrain,
+    /// Flat rectangle in XY facing +Z, sized by `dimensions.width` and
+    /// `dimensions.height`; usually textured and turned to the camera with `billboard`.
+    Sprite,
+    /// Line of `text` drawn in the world at the entity's position, facing +Z.
+    Text,
+}
+
+impl ShapeKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ShapeKind::Box => "box",
+            ShapeKind::Sphere => "sphere",
+            ShapeKind::Circle => "circle",
+            ShapeKind::Cylinder => "cylinder",
+            ShapeKind::Capsule => "capsule",
+            ShapeKind::Cone => "cone",
+            ShapeKind::Torus => "torus",
+            ShapeKind::Plane => "plane",
+            ShapeKind::Wedge => "wedge",
+            ShapeKind::Model => "model",
+            ShapeKind::Terrain => "terrain",
+            ShapeKind::Sprite => "sprite",
+            ShapeKind::Text => "text",
+        }
+    }
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
 pub struct ShapeConfig {
     pub kind: ShapeKind,
     #[serde(default)]
     pub color: Option<String>,
+    /// Box, plane, wedge, terrain and sprite size.
     #[serde(default)]
     pub dimensions: Option<DimensionsConfig>,
+    /// Sphere, circle, cylinder, capsule, cone and torus radius.
     #[serde(default)]
     pub radius: Option<f32>,
+    /// Cylinder, capsule and cone height.
+    #[serde(default)]
+    pub height: Option<f32>,
+    /// Thickness of a torus tube, from its middle to its surface.
+    #[serde(default)]
+    pub tube_radius: Option<f32>,
+    /// `.glb` or `.gltf` file relative to `ASSET_ROOT`, for models.
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Named scene of the model to use instead of its default one.
+    #[serde(default)]
+    pub scene: Option<String>,
+    /// Named node of the model to use, with its children, instead of a whole scene.
+    #[serde(default)]
+    pub node: Option<String>,
+    /// How a model's collider is built from its meshes.
+    #[serde(default)]
+    pub collider: ModelCollider,
+    /// Grayscale image relative to `ASSET_ROOT` giving a terrain's heights.
+    #[serde(default)]
+    pub heightmap: Option<String>,
+    /// Vertices along each side of a terrain's mesh and collider, sampled from the
+    /// heightmap. Defaults to one per heightmap pixel.
+    #[serde(default)]
+    pub resolution: Option<u32>,
+    /// Keeps the shape's +Z face turned to the scene camera, replacing its rotation.
+    #[serde(default)]
+    pub billboard: Option<BillboardMode>,
+    /// What a text shape reads; `\n` starts a new line.
+    #[serde(default)]
+    pub text: Option<String>,
+    /// Height of a text shape's letters in world units, or in pixels with
+    /// `fixed_screen_size`.
+    #[serde(default)]
+    pub font_size: Option<f32>,
+    /// Which side of a text shape sits on the entity's position, and how its lines line up.
+    #[serde(default)]
+    pub align: Option<TextAlign>,
+    /// Keeps a text shape the same size on screen however far the camera is.
+    #[serde(default)]
+    pub fixed_screen_size: Option<bool>,
+}
+
+/// Horizontal alignment of a text shape.
+#[derive(Debug, Deserialize, JsonSchema, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TextAlign {
+    /// Starts at the entity's position.
+    Left,
+    #[default]
+    Center,
+    /// Ends at the entity's position.
+    Right,
+}
+
+/// How a billboard turns to follow the camera.
+#[derive(Debug, Deserialize, JsonSchema, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BillboardMode {
+    /// Lies in the screen plane, tilting as the camera looks up or down.
+    All,
+    /// Stays upright and only turns about Y, for foliage and markers standing on the ground.
+    YAxis,
 }
 
-#[derive(Debug, Deserialize, Clone, Default)]
+/// Collider generated for a model once its meshes have loaded.
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelCollider {
+    /// Smallest convex shape around every vertex; fills holes and hollows.
+    #[default]
+    ConvexHull,
+    /// Several convex pieces following the surface, at a higher cost to build.
+    ConvexDecomposition,
+    /// The triangles themselves. Exact, but only reliable on fixed bodies.
+    Trimesh,
+    /// Axis-aligned box around every vertex.
+    BoundingBox,
+}
+
+#[derive(Debug, Deserialize, JsonSchema, Clone, Default, PartialEq)]
 pub struct ShapeOverrides {
     #[serde(default)]
     pub color: Option<String>,
@@ -87,9 +230,17 @@ pub struct ShapeOverrides {
     pub dimensions: Option<DimensionsConfig>,
     #[serde(default)]
     pub radius: Option<f32>,
+    #[serde(default)]
+    pub height: Option<f32>,
+    #[serde(default)]
+    pub tube_radius: Option<f32>,
+    #[serde(default)]
+    pub collider: Option<ModelCollider>,
+    #[serde(default)]
+    pub text: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Clone, Default)]
+#[derive(Debug, Deserialize, JsonSchema, Clone, Default, PartialEq)]
 pub struct PhysicsOverrides {
     #[serde(default)]
     pub enabled: Option<bool>,
@@ -103,7 +254,7 @@ pub struct PhysicsOverrides {
     pub friction: Option<f32>,
 }
 
-#[derive(Debug, Deserialize, Clone, Default)]
+#[derive(Debug, Deserialize, JsonSchema, Clone, Default)]
 pub struct LightComponent {
     #[serde(default)]
     pub kind: Option<LightKind>,
@@ -121,7 +272,7 @@ pub struct LightComponent {
     pub offset: Option<PositionConfig>,
 }
 
-#[derive(Debug, Deserialize, Clone, Default)]
+#[derive(Debug, Deserialize, JsonSchema, Clone, Default, PartialEq)]
 pub struct LightOverridesConfig {
     #[serde(default)]
     pub kind: Option<LightKind>,
@@ -140,7 +291,3 @@ pub struct LightOverridesConfig {
 }
 
 // Vec3Config is used in combo templates for stacking and offsets.
-
-fn default_unit_scale() -> f32 {
-    1.0
-}
@@ -0,0 +1,47 @@
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use super::transforms::PositionConfig;
+
+/// One `include` entry: either a path or glob relative to the scene directory, or a table
+/// that also places everything the matched files contain.
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
+#[serde(untagged)]
+pub enum IncludeConfig {
+    Path(String),
+    Placed(PlacedInclude),
+}
+
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
+pub struct PlacedInclude {
+    pub path: String,
+    /// Added to the position of everything included, nested includes accumulating.
+    #[serde(default)]
+    pub offset: PositionConfig,
+    /// Prepended to the names of included placements.
+    #[serde(default)]
+    pub prefix: String,
+}
+
+impl IncludeConfig {
+    pub fn path(&self) -> &str {
+        match self {
+            IncludeConfig::Path(path) => path,
+            IncludeConfig::Placed(include) => &include.path,
+        }
+    }
+
+    pub fn offset(&self) -> PositionConfig {
+        match self {
+            IncludeConfig::Path(_) => PositionConfig::default(),
+            IncludeConfig::Placed(include) => include.offset.clone(),
+        }
+    }
+
+    pub fn prefix(&self) -> &str {
+        match self {
+            IncludeConfig::Path(_) => "",
+            IncludeConfig::Placed(include) => &include.prefix,
+        }
+    }
+}
@@ -1,6 +1,9 @@
+use bevy::asset::Asset;
+use bevy::reflect::TypePath;
+use schemars::JsonSchema;
 use serde::Deserialize;
 
-#[derive(Debug, Deserialize)]
+#[derive(Asset, TypePath, Debug, Deserialize, JsonSchema)]
 pub struct InputConfig {
     #[serde(default)]
     pub camera: CameraInputConfig,
@@ -8,6 +11,8 @@ pub struct InputConfig {
     pub overlays: Vec<OverlayInputConfig>,
     #[serde(default)]
     pub actions: Vec<ActionBindingConfig>,
+    #[serde(default)]
+    pub system: SystemInputConfig,
 }
 
 impl Default for InputConfig {
@@ -16,11 +21,22 @@ impl Default for InputConfig {
             camera: CameraInputConfig::default(),
             overlays: vec![],
             actions: vec![],
+            system: SystemInputConfig::default(),
         }
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema, Default)]
+pub struct SystemInputConfig {
+    /// Switches to the next scene directory under `SCENE_ROOT`.
+    #[serde(default)]
+    pub next_scene: String,
+    /// Writes the live placements back to the scene's `entities.toml`. Dev mode only.
+    #[serde(default)]
+    pub save_scene: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct CameraInputConfig {
     #[serde(default)]
     pub movement: MovementConfig,
@@ -37,13 +53,16 @@ impl Default for CameraInputConfig {
     }
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
 pub struct OverlayInputConfig {
     pub name: String,
     pub toggle: String,
+    /// Only spawned when the app runs in dev mode.
+    #[serde(default)]
+    pub dev_only: bool,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
 pub struct ActionBindingConfig {
     pub name: String,
     pub action: String,
@@ -55,7 +74,7 @@ pub struct ActionBindingConfig {
     pub value: Option<f32>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct MovementConfig {
     #[serde(default = "default_camera_control")]
     pub control: String,
@@ -84,7 +103,7 @@ impl Default for MovementConfig {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct CameraRotationConfig {
     #[serde(default = "default_rotation_speed")]
     pub degrees_per_second: f32,
@@ -0,0 +1,109 @@
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use super::{EntityOverrides, TransformOverrides, Vec3Config};
+
+/// Copies of one template laid out on a grid or around a circle. `transform.position` is
+/// where the first copy goes, or the circle's center; left out, it is the template's own.
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
+pub struct ArrayConfig {
+    pub template: String,
+    /// Copies are named `<name_override>_<n>`, counting from 1; without it they keep the
+    /// template's name.
+    #[serde(default)]
+    pub name_override: Option<String>,
+    pub layout: ArrayLayout,
+    #[serde(default)]
+    pub transform: TransformOverrides,
+    #[serde(default)]
+    pub overrides: EntityOverrides,
+}
+
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ArrayLayout {
+    /// `count` copies along each axis, `spacing` apart. Axes left at 1 give 1D or 2D grids.
+    Grid {
+        #[serde(default)]
+        count: GridCount,
+        #[serde(default)]
+        spacing: Vec3Config,
+    },
+    /// `count` copies evenly spaced around a horizontal circle, starting on +X.
+    Circle {
+        count: u32,
+        radius: f32,
+        /// Turns each copy about the vertical axis to face the center.
+        #[serde(default)]
+        face_center: bool,
+    },
+}
+
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
+pub struct GridCount {
+    #[serde(default = "default_count")]
+    pub x: u32,
+    #[serde(default = "default_count")]
+    pub y: u32,
+    #[serde(default = "default_count")]
+    pub z: u32,
+}
+
+impl Default for GridCount {
+    fn default() -> Self {
+        Self {
+            x: default_count(),
+            y: default_count(),
+            z: default_count(),
+        }
+    }
+}
+
+/// Copies of one template spread at random over an area centered on `transform.position`,
+/// or on the template's own position when that is left out. The same `seed` always gives
+/// the same layout.
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
+pub struct ScatterConfig {
+    pub template: String,
+    /// Copies are named `<name_override>_<n>`, counting from 1; without it they keep the
+    /// template's name.
+    #[serde(default)]
+    pub name_override: Option<String>,
+    pub count: u32,
+    #[serde(default)]
+    pub seed: u64,
+    pub area: ScatterArea,
+    /// Least horizontal distance between two copies. Copies that find no free spot are
+    /// left out with a warning.
+    #[serde(default)]
+    pub min_spacing: f32,
+    /// Degrees each copy is turned about the vertical axis, drawn from this range.
+    #[serde(default)]
+    pub yaw: Option<RangeConfig>,
+    /// Scale of each copy, drawn from this range and multiplied by `transform.scale`.
+    #[serde(default)]
+    pub scale: Option<RangeConfig>,
+    #[serde(default)]
+    pub transform: TransformOverrides,
+    #[serde(default)]
+    pub overrides: EntityOverrides,
+}
+
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ScatterArea {
+    /// Axis-aligned box of `size`; leave `size.y` at 0 to keep every copy at one height.
+    Box { size: Vec3Config },
+    /// Horizontal disc of `radius`.
+    Disc { radius: f32 },
+}
+
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
+pub struct RangeConfig {
+    pub min: f32,
+    pub max: f32,
+}
+
+fn default_count() -> u32 {
+    1
+}
@@ -1,6 +1,7 @@
+use schemars::JsonSchema;
 use serde::Deserialize;
 
-#[derive(Debug, Deserialize, Clone, Default)]
+#[derive(Debug, Deserialize, JsonSchema, Clone, Default)]
 pub struct MaterialConfig {
     #[serde(default)]
     pub preset: Option<String>,
@@ -56,4 +57,89 @@ pub struct MaterialConfig {
     pub attenuation_color: Option<String>,
     #[serde(default)]
     pub attenuation_distance: Option<f32>,
+    /// Layers painted over a terrain by height and slope, each over the ones before it.
+    /// They are baked into the base color texture, replacing `base_color_texture`.
+    #[serde(default)]
+    pub splat: Vec<SplatLayerConfig>,
+    /// Texels along each side of the baked splat texture. Defaults to 128.
+    #[serde(default)]
+    pub splat_resolution: Option<u32>,
+    /// Plays `base_color_texture` as a grid of animation frames.
+    #[serde(default)]
+    pub sprite_sheet: Option<SpriteSheetConfig>,
+}
+
+/// Animation frames laid out on a texture in `columns` by `rows` equal cells, played left
+/// to right and then top to bottom, looping.
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
+pub struct SpriteSheetConfig {
+    pub columns: u32,
+    #[serde(default = "default_sheet_rows")]
+    pub rows: u32,
+    /// Frames to play from the first cell. Defaults to every cell.
+    #[serde(default)]
+    pub frames: Option<u32>,
+    /// Frames per second.
+    #[serde(default = "default_sheet_fps")]
+    pub fps: f32,
+}
+
+fn default_sheet_rows() -> u32 {
+    1
+}
+
+fn default_sheet_fps() -> f32 {
+    12.0
+}
+
+/// One layer of a terrain's splat texture. It covers the terrain where both its height
+/// and slope ranges hold; an unset bound leaves that side open.
+#[derive(Debug, Deserialize, JsonSchema, Clone, Default)]
+pub struct SplatLayerConfig {
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Image relative to `ASSET_ROOT`, tinted by `color`.
+    #[serde(default)]
+    pub texture: Option<String>,
+    /// Times the texture repeats across the terrain.
+    #[serde(default)]
+    pub tiling: Option<f32>,
+    /// Height above the terrain's base, in world units.
+    #[serde(default)]
+    pub min_height: Option<f32>,
+    #[serde(default)]
+    pub max_height: Option<f32>,
+    /// Steepness in degrees, 0 being level.
+    #[serde(default)]
+    pub min_slope: Option<f32>,
+    #[serde(default)]
+    pub max_slope: Option<f32>,
+    /// Width, in world units, over which the layer fades in across its height bounds.
+    #[serde(default)]
+    pub height_blend: Option<f32>,
+    /// Width, in degrees, over which the layer fades in across its slope bounds.
+    #[serde(default)]
+    pub slope_blend: Option<f32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaterialPreset {
+    Wood,
+    Metal,
+    Marble,
+    Stone,
+    Glass,
+}
+
+impl MaterialPreset {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+            "wood" | "wooden" => Some(MaterialPreset::Wood),
+            "metal" | "metallic" => Some(MaterialPreset::Metal),
+            "marble" => Some(MaterialPreset::Marble),
+            "stone" => Some(MaterialPreset::Stone),
+            "glass" => Some(MaterialPreset::Glass),
+            _ => None,
+        }
+    }
 }
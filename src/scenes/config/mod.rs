@@ -5,7 +5,9 @@ mod camera;
 mod colors;
 mod combo_entity;
 mod entity;
+mod include;
 mod input;
+mod layout;
 mod light;
 mod material;
 mod overlay;
@@ -16,31 +18,35 @@ mod sun;
 mod skybox;
 mod transforms;
 
-pub use active::{ActiveScene, OVERLAY_ROOT, SCENE_ROOT};
-pub use actions::{ShootActionConfig, SprintActionConfig, ZoomActionConfig};
+pub use active::{ActiveScene, ASSET_ROOT, OVERLAY_ROOT, SCENE_ROOT};
+pub use actions::{
+    ShootActionConfig, SprintActionConfig, ZoomActionConfig, PROJECTILE_TEMPLATE,
+};
 pub use bounds::BoundingBoxConfig;
 pub use camera::CameraConfig;
 pub use colors::{
     default_circle_color_name, default_circle_rgb, default_color_name, default_color_rgb,
-    parse_color,
+    parse_color, try_parse_color,
 };
 pub use combo_entity::{ComboPart, ComboPhysics, ComboStackConfig, ComboTemplate};
 pub use entity::{
-    EntityOverrides, EntityTemplate, LightComponent, LightOverridesConfig, PhysicsOverrides,
-    ShapeConfig, ShapeKind, ShapeOverrides,
+    BillboardMode, EntityOverrides, EntityTemplate, LightComponent, LightOverridesConfig,
+    PhysicsOverrides, ModelCollider, ShapeConfig, ShapeKind, ShapeOverrides, TextAlign,
     TransformConfig as EntityTransformConfig, TransformOverrides,
 };
+pub use include::IncludeConfig;
 pub use input::{CameraRotationConfig, InputConfig, MovementConfig, OverlayInputConfig};
+pub use layout::{ArrayConfig, ArrayLayout, RangeConfig, ScatterArea, ScatterConfig};
 pub use light::{LightEntry, LightKind};
-pub use material::MaterialConfig;
+pub use material::{MaterialConfig, MaterialPreset, SplatLayerConfig, SpriteSheetConfig};
 pub use overlay::{
     OverlayAnchor, OverlayConfig, OverlayElement, TextOverlay,
 };
-pub use render::{BloomConfig, FogConfig, FogFalloffConfig, RenderConfig};
+pub use render::{parse_tonemapping, BloomConfig, FogConfig, FogFalloffConfig, RenderConfig};
 pub use paths::{
-    action_config_path, input_config_path, overlay_config_path,
+    action_config_path, input_config_path, overlay_config_path, scene_asset_path,
 };
-pub use physics::PhysicsConfig;
+pub use physics::{parse_rigid_body, PhysicsConfig};
 pub use skybox::SkyboxConfig;
 pub use sun::SunConfig;
-pub use transforms::Vec3Config;
+pub use transforms::{CubeRotationConfig, EulerOrder, PositionConfig, ScaleConfig, Vec3Config};
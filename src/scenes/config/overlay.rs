@@ -1,14 +1,15 @@
 #![allow(dead_code)]
 
+use schemars::JsonSchema;
 use serde::Deserialize;
 
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Deserialize, JsonSchema, Default)]
 pub struct OverlayConfig {
     #[serde(default)]
     pub elements: Vec<OverlayElement>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 #[serde(tag = "kind", rename_all = "snake_case")]
 pub enum OverlayElement {
     Text(TextOverlay),
@@ -21,7 +22,7 @@ impl Default for OverlayElement {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct TextOverlay {
     #[serde(flatten)]
     pub common: OverlayCommon,
@@ -49,7 +50,7 @@ impl Default for TextOverlay {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct ImageOverlay {
     #[serde(flatten)]
     pub common: OverlayCommon,
@@ -74,7 +75,7 @@ impl Default for ImageOverlay {
     }
 }
 
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Deserialize, JsonSchema, Default)]
 pub struct OverlayCommon {
     #[serde(default = "default_visible")]
     pub visible: bool,
@@ -90,7 +91,7 @@ pub struct OverlayCommon {
     pub scale: f32,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct OverlayOffset {
     #[serde(default)]
     pub x: f32,
@@ -104,7 +105,7 @@ impl Default for OverlayOffset {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum OverlayAnchor {
     TopLeft,
@@ -124,7 +125,7 @@ impl Default for OverlayAnchor {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ImageFit {
     Contain,
@@ -1,7 +1,10 @@
+use crate::scenes::format::{resolve_format, scene_file};
+use crate::scenes::loaders::INPUT_FILE;
+
 use super::SCENE_ROOT;
 
 pub fn input_config_path(scene: &str) -> String {
-    format!("{SCENE_ROOT}/{scene}/input.toml")
+    format!("{SCENE_ROOT}/{scene}/{}", scene_file(scene, INPUT_FILE))
 }
 
 pub fn action_config_path(scene: &str, action_path: &str) -> String {
@@ -9,8 +12,17 @@ pub fn action_config_path(scene: &str, action_path: &str) -> String {
 }
 
 pub fn overlay_config_path(name: &str) -> String {
-    format!(
+    resolve_format(&format!(
         "{overlay_root}/{name}.toml",
         overlay_root = crate::scenes::config::OVERLAY_ROOT
-    )
+    ))
+}
+
+/// Path of a scene file as the `AssetServer` sees it, relative to `ASSET_ROOT`.
+pub fn scene_asset_path(scene: &str, relative_path: &str) -> String {
+    let scenes = SCENE_ROOT
+        .strip_prefix(crate::scenes::config::ASSET_ROOT)
+        .unwrap_or(SCENE_ROOT)
+        .trim_start_matches('/');
+    format!("{scenes}/{scene}/{relative_path}")
 }
@@ -1,6 +1,8 @@
+use bevy_rapier3d::prelude::RigidBody;
+use schemars::JsonSchema;
 use serde::Deserialize;
 
-#[derive(Debug, Deserialize, Default, Clone)]
+#[derive(Debug, Deserialize, JsonSchema, Default, Clone)]
 pub struct PhysicsConfig {
     #[serde(default)]
     pub enabled: bool,
@@ -14,6 +16,20 @@ pub struct PhysicsConfig {
     pub friction: f32,
 }
 
+pub fn parse_rigid_body(body_type: &str) -> Option<RigidBody> {
+    match body_type.trim().to_ascii_lowercase().as_str() {
+        "dynamic" => Some(RigidBody::Dynamic),
+        "fixed" | "static" => Some(RigidBody::Fixed),
+        "kinematic_position" | "kinematic_position_based" => {
+            Some(RigidBody::KinematicPositionBased)
+        }
+        "kinematic_velocity" | "kinematic_velocity_based" => {
+            Some(RigidBody::KinematicVelocityBased)
+        }
+        _ => None,
+    }
+}
+
 fn default_body_type() -> String {
     "dynamic".to_string()
 }
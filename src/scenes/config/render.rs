@@ -1,8 +1,10 @@
+use bevy::core_pipeline::tonemapping::Tonemapping;
+use schemars::JsonSchema;
 use serde::Deserialize;
 
 use super::transforms::{Vec2Config, Vec3Config};
 
-#[derive(Debug, Deserialize, Clone, Default)]
+#[derive(Debug, Deserialize, JsonSchema, Clone, Default)]
 pub struct RenderConfig {
     #[serde(default)]
     pub tonemapping: Option<String>,
@@ -18,7 +20,7 @@ pub struct RenderConfig {
     pub fog: Option<FogConfig>,
 }
 
-#[derive(Debug, Deserialize, Clone, Default)]
+#[derive(Debug, Deserialize, JsonSchema, Clone, Default)]
 pub struct BloomConfig {
     #[serde(default)]
     pub enabled: bool,
@@ -42,7 +44,7 @@ pub struct BloomConfig {
     pub scale: Option<Vec2Config>,
 }
 
-#[derive(Debug, Deserialize, Clone, Default)]
+#[derive(Debug, Deserialize, JsonSchema, Clone, Default)]
 pub struct BloomPrefilterConfig {
     #[serde(default)]
     pub threshold: f32,
@@ -50,7 +52,7 @@ pub struct BloomPrefilterConfig {
     pub threshold_softness: f32,
 }
 
-#[derive(Debug, Deserialize, Clone, Default)]
+#[derive(Debug, Deserialize, JsonSchema, Clone, Default)]
 pub struct FogConfig {
     #[serde(default)]
     pub enabled: bool,
@@ -68,7 +70,7 @@ pub struct FogConfig {
     pub falloff: Option<FogFalloffConfig>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
 #[serde(tag = "kind", rename_all = "snake_case")]
 pub enum FogFalloffConfig {
     Linear { start: f32, end: f32 },
@@ -76,3 +78,20 @@ pub enum FogFalloffConfig {
     ExponentialSquared { density: f32 },
     Atmospheric { extinction: Vec3Config, inscattering: Vec3Config },
 }
+
+pub fn parse_tonemapping(value: &str) -> Option<Tonemapping> {
+    let normalized = value.trim().to_ascii_lowercase().replace('-', "_");
+    match normalized.as_str() {
+        "none" => Some(Tonemapping::None),
+        "reinhard" => Some(Tonemapping::Reinhard),
+        "reinhard_luminance" => Some(Tonemapping::ReinhardLuminance),
+        "aces_fitted" => Some(Tonemapping::AcesFitted),
+        "agx" => Some(Tonemapping::AgX),
+        "somewhat_boring_display_transform" => {
+            Some(Tonemapping::SomewhatBoringDisplayTransform)
+        }
+        "tony_mc_mapface" => Some(Tonemapping::TonyMcMapface),
+        "blender_filmic" => Some(Tonemapping::BlenderFilmic),
+        _ => None,
+    }
+}
@@ -1,8 +1,9 @@
+use schemars::JsonSchema;
 use serde::Deserialize;
 
 use super::colors::default_color_name;
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
 pub struct SunConfig {
     #[serde(default = "default_sun_time")]
     pub time: f32, // hours 0-24
@@ -1,6 +1,11 @@
-use serde::Deserialize;
+use std::ops::Mul;
 
-#[derive(Debug, Deserialize, Clone)]
+use bevy::math::{EulerRot, Quat, Vec3, Vec4};
+use bevy::transform::components::Transform;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
 pub struct Vec3Config {
     #[serde(default)]
     pub x: f32,
@@ -10,7 +15,7 @@ pub struct Vec3Config {
     pub z: f32,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
 pub struct Vec2Config {
     #[serde(default)]
     pub x: f32,
@@ -34,27 +39,156 @@ impl Default for Vec3Config {
     }
 }
 
-#[derive(Debug, Deserialize, Clone)]
-pub struct CubeRotationConfig {
+impl Vec3Config {
+    pub fn to_vec3(&self) -> Vec3 {
+        Vec3::new(self.x, self.y, self.z)
+    }
+}
+
+/// Orientation of an entity, given in whichever form is easiest to write. Angles are in
+/// degrees.
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
+#[serde(untagged, deny_unknown_fields)]
+pub enum CubeRotationConfig {
+    /// Turns the entity's forward direction, -Z, towards `look_at`, a point in the same
+    /// space as its position, keeping its +Y as close to `up` as it can.
+    LookAt {
+        look_at: Vec3Config,
+        #[serde(default = "default_up")]
+        up: Vec3Config,
+    },
+    /// Normalized before use.
+    Quaternion { quat: QuatConfig },
+    /// `angle` about `axis`, counter-clockwise looking back along the axis.
+    AxisAngle { axis: Vec3Config, angle: f32 },
+    /// Turns of `x`, `y` and `z` about those axes, made in `order`.
+    Euler {
+        #[serde(default)]
+        x: f32,
+        #[serde(default)]
+        y: f32,
+        #[serde(default)]
+        z: f32,
+        #[serde(default)]
+        order: EulerOrder,
+    },
+}
+
+impl CubeRotationConfig {
+    /// The rotation of an entity at `position`, which only `look_at` depends on.
+    pub fn to_quat(&self, position: Vec3) -> Quat {
+        match self {
+            Self::LookAt { look_at, up } => {
+                Transform::from_translation(position)
+                    .looking_at(look_at.to_vec3(), up.to_vec3())
+                    .rotation
+            }
+            Self::Quaternion { quat } => Vec4::new(quat.x, quat.y, quat.z, quat.w)
+                .try_normalize()
+                .map_or(Quat::IDENTITY, Quat::from_vec4),
+            Self::AxisAngle { axis, angle } => axis
+                .to_vec3()
+                .try_normalize()
+                .map_or(Quat::IDENTITY, |axis| {
+                    Quat::from_axis_angle(axis, angle.to_radians())
+                }),
+            Self::Euler { x, y, z, order } => order.to_quat(Vec3::new(*x, *y, *z)),
+        }
+    }
+
+    /// Euler angles in the default order.
+    pub fn from_quat(rotation: Quat) -> Self {
+        let order = EulerOrder::default();
+        let angles = order.angles(rotation);
+        Self::Euler {
+            x: angles.x,
+            y: angles.y,
+            z: angles.z,
+            order,
+        }
+    }
+}
+
+impl Default for CubeRotationConfig {
+    fn default() -> Self {
+        Self::Euler {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+            order: EulerOrder::default(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
+pub struct QuatConfig {
     #[serde(default)]
-    pub roll: f32,
+    pub x: f32,
     #[serde(default)]
-    pub pitch: f32,
+    pub y: f32,
     #[serde(default)]
-    pub yaw: f32,
+    pub z: f32,
+    #[serde(default = "default_unit")]
+    pub w: f32,
 }
 
-impl Default for CubeRotationConfig {
-    fn default() -> Self {
-        Self {
-            roll: 0.0,
-            pitch: 0.0,
-            yaw: 0.0,
+/// Axes of Euler turns in the order they are made, each about the entity's axes as the
+/// turns before left them. The default `yxz` turns about the vertical axis first, then
+/// tilts, then banks.
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EulerOrder {
+    Xyz,
+    Xzy,
+    #[default]
+    Yxz,
+    Yzx,
+    Zxy,
+    Zyx,
+}
+
+impl EulerOrder {
+    /// `degrees` holds the turn about each of X, Y and Z.
+    pub fn to_quat(self, degrees: Vec3) -> Quat {
+        let [first, second, third] = self.axes().map(|axis| degrees[axis].to_radians());
+        Quat::from_euler(self.euler_rot(), first, second, third)
+    }
+
+    /// The turn about each of X, Y and Z, in degrees, that makes up `rotation`.
+    pub fn angles(self, rotation: Quat) -> Vec3 {
+        let (first, second, third) = rotation.to_euler(self.euler_rot());
+        let mut degrees = Vec3::ZERO;
+        for (axis, angle) in self.axes().into_iter().zip([first, second, third]) {
+            degrees[axis] = angle.to_degrees();
+        }
+        degrees
+    }
+
+    /// Indices of the axes, X being 0, in turning order.
+    fn axes(self) -> [usize; 3] {
+        match self {
+            Self::Xyz => [0, 1, 2],
+            Self::Xzy => [0, 2, 1],
+            Self::Yxz => [1, 0, 2],
+            Self::Yzx => [1, 2, 0],
+            Self::Zxy => [2, 0, 1],
+            Self::Zyx => [2, 1, 0],
+        }
+    }
+
+    fn euler_rot(self) -> EulerRot {
+        match self {
+            Self::Xyz => EulerRot::XYZ,
+            Self::Xzy => EulerRot::XZY,
+            Self::Yxz => EulerRot::YXZ,
+            Self::Yzx => EulerRot::YZX,
+            Self::Zxy => EulerRot::ZXY,
+            Self::Zyx => EulerRot::ZYX,
         }
     }
 }
 
-#[derive(Debug, Deserialize, Default, Clone)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Default, Clone, PartialEq)]
 pub struct PositionConfig {
     #[serde(default)]
     pub x: f32,
@@ -64,7 +198,67 @@ pub struct PositionConfig {
     pub z: f32,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+/// Scale written as one number for every axis or as an `{ x, y, z }` table, where
+/// missing axes stay at 1.
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq)]
+#[serde(untagged)]
+pub enum ScaleConfig {
+    Uniform(f32),
+    PerAxis {
+        #[serde(default = "default_unit")]
+        x: f32,
+        #[serde(default = "default_unit")]
+        y: f32,
+        #[serde(default = "default_unit")]
+        z: f32,
+    },
+}
+
+impl ScaleConfig {
+    /// Writes equal axes back as a single number.
+    pub fn from_vec3(scale: Vec3) -> Self {
+        if scale.x == scale.y && scale.y == scale.z {
+            Self::Uniform(scale.x)
+        } else {
+            Self::PerAxis {
+                x: scale.x,
+                y: scale.y,
+                z: scale.z,
+            }
+        }
+    }
+
+    pub fn to_vec3(self) -> Vec3 {
+        match self {
+            Self::Uniform(scale) => Vec3::splat(scale),
+            Self::PerAxis { x, y, z } => Vec3::new(x, y, z),
+        }
+    }
+}
+
+impl Default for ScaleConfig {
+    fn default() -> Self {
+        Self::Uniform(1.0)
+    }
+}
+
+impl Mul for ScaleConfig {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self::from_vec3(self.to_vec3() * other.to_vec3())
+    }
+}
+
+impl Mul<f32> for ScaleConfig {
+    type Output = Self;
+
+    fn mul(self, factor: f32) -> Self {
+        Self::from_vec3(self.to_vec3() * factor)
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
 pub struct DimensionsConfig {
     #[serde(default = "default_unit")]
     pub width: f32,
@@ -88,3 +282,11 @@ impl Default for DimensionsConfig {
 fn default_unit() -> f32 {
     1.0
 }
+
+fn default_up() -> Vec3Config {
+    Vec3Config {
+        x: 0.0,
+        y: 1.0,
+        z: 0.0,
+    }
+}
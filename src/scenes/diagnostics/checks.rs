@@ -0,0 +1,203 @@
+use std::path::Path;
+
+use crate::scenes::config::{
+    action_config_path, parse_rigid_body, parse_tonemapping, try_parse_color, MaterialPreset,
+    ASSET_ROOT,
+};
+use crate::scenes::expand::{include_paths, include_pattern};
+use crate::scenes::input::{resolve_key, resolve_mouse_button};
+
+use super::source::SourceFile;
+
+/// Keys holding CSS color strings, wherever they appear in a config file.
+const COLOR_KEYS: &[&str] = &[
+    "color",
+    "base_color",
+    "specular_tint",
+    "emissive_color",
+    "attenuation_color",
+    "directional_light_color",
+];
+
+/// Material keys holding image paths relative to `ASSET_ROOT`.
+const TEXTURE_KEYS: &[&str] = &[
+    "base_color_texture",
+    "emissive_texture",
+    "normal_map",
+    "metallic_roughness_texture",
+    "occlusion_texture",
+];
+
+const KEY_FIELDS: &[&str] = &[
+    "camera.movement.forward",
+    "camera.movement.backward",
+    "camera.movement.left",
+    "camera.movement.right",
+    "camera.rotation.yaw_left",
+    "camera.rotation.yaw_right",
+    "camera.rotation.pitch_up",
+    "camera.rotation.pitch_down",
+    "system.next_scene",
+    "system.save_scene",
+];
+
+/// Flags named values anywhere in the file that spawning would silently replace with a
+/// default: colors, material presets, rigid body types, tonemapping names, and texture,
+/// model and heightmap paths.
+pub fn check_values(source: &SourceFile, table: &toml::Table) {
+    check_table_values(source, table, "", "");
+}
+
+fn check_table_values(source: &SourceFile, table: &toml::Table, field: &str, parent: &str) {
+    for (key, value) in table {
+        let child = if field.is_empty() {
+            key.clone()
+        } else {
+            format!("{field}.{key}")
+        };
+        let toml::Value::String(text) = value else {
+            check_nested_values(source, value, &child, key);
+            continue;
+        };
+        let key = key.as_str();
+        if COLOR_KEYS.contains(&key) {
+            if let Err(err) = try_parse_color(text) {
+                source.error(&child, format!("Unrecognized color '{text}': {err}."));
+            }
+        } else if TEXTURE_KEYS.contains(&key) {
+            if !Path::new(ASSET_ROOT).join(text).is_file() {
+                source.error(&child, format!("Texture '{text}' not found under {ASSET_ROOT}."));
+            }
+        } else if key == "model" && parent == "shape" {
+            if !Path::new(ASSET_ROOT).join(text).is_file() {
+                source.error(&child, format!("Model '{text}' not found under {ASSET_ROOT}."));
+            }
+        } else if key == "heightmap" && parent == "shape" {
+            if !Path::new(ASSET_ROOT).join(text).is_file() {
+                source.error(&child, format!("Heightmap '{text}' not found under {ASSET_ROOT}."));
+            }
+        } else if key == "texture" && parent == "splat" {
+            if !Path::new(ASSET_ROOT).join(text).is_file() {
+                source.error(&child, format!("Texture '{text}' not found under {ASSET_ROOT}."));
+            }
+        } else if key == "preset" && parent == "material" {
+            if MaterialPreset::parse(text).is_none() {
+                source.error(&child, format!("Unknown material preset '{text}'."));
+            }
+        } else if key == "body_type" {
+            if parse_rigid_body(text).is_none() {
+                source.error(&child, format!("Unknown rigid body type '{text}'."));
+            }
+        } else if key == "tonemapping" && parse_tonemapping(text).is_none() {
+            source.error(&child, format!("Unknown tonemapping '{text}'."));
+        }
+    }
+}
+
+fn check_nested_values(source: &SourceFile, value: &toml::Value, field: &str, parent: &str) {
+    match value {
+        toml::Value::Table(table) => check_table_values(source, table, field, parent),
+        toml::Value::Array(values) => {
+            for (index, value) in values.iter().enumerate() {
+                check_nested_values(source, value, &format!("{field}[{index}]"), parent);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Flags key and mouse button names in `input.toml` that would leave a binding disabled.
+pub fn check_input_bindings(source: &SourceFile, table: &toml::Table) {
+    for field in KEY_FIELDS {
+        if let Some(key) = string_at(table, field) {
+            check_key(source, field, key);
+        }
+    }
+    for (index, overlay) in array_of_tables(table, "overlays") {
+        if let Some(toml::Value::String(key)) = overlay.get("toggle") {
+            check_key(source, &format!("overlays[{index}].toggle"), key);
+        }
+    }
+    for (index, action) in array_of_tables(table, "actions") {
+        if let Some(toml::Value::String(key)) = action.get("key") {
+            check_key(source, &format!("actions[{index}].key"), key);
+        }
+        if let Some(toml::Value::String(button)) = action.get("mouse") {
+            if !button.trim().is_empty() && resolve_mouse_button(button).is_none() {
+                source.error(
+                    &format!("actions[{index}].mouse"),
+                    format!("Unrecognized mouse button '{button}'; binding disabled."),
+                );
+            }
+        }
+    }
+}
+
+/// Flags `template` references in `entities.toml` placements or combo `parts` that point
+/// at files missing from the scene.
+pub fn check_template_refs(source: &SourceFile, table: &toml::Table, scene: &str, list: &str) {
+    for (index, entry) in array_of_tables(table, list) {
+        let Some(toml::Value::String(template)) = entry.get("template") else {
+            continue;
+        };
+        if !Path::new(&action_config_path(scene, template)).is_file() {
+            source.error(
+                &format!("{list}[{index}].template"),
+                format!("Template '{template}' not found in scene '{scene}'."),
+            );
+        }
+    }
+}
+
+/// Flags `include` patterns that are not valid globs or match no file in the scene.
+pub fn check_includes(source: &SourceFile, table: &toml::Table, scene: &str) {
+    let Some(toml::Value::Array(includes)) = table.get("include") else {
+        return;
+    };
+    for (index, entry) in includes.iter().enumerate() {
+        let Some(pattern) = include_pattern(entry) else {
+            continue;
+        };
+        let field = match entry {
+            toml::Value::Table(_) => format!("include[{index}].path"),
+            _ => format!("include[{index}]"),
+        };
+        match include_paths(scene, pattern) {
+            Ok(paths) if paths.is_empty() => {
+                source.warning(&field, format!("Include '{pattern}' matches no files."));
+            }
+            Ok(_) => {}
+            Err(err) => {
+                source.error(&field, format!("Invalid include pattern '{pattern}': {err}."));
+            }
+        }
+    }
+}
+
+fn check_key(source: &SourceFile, field: &str, key: &str) {
+    if !key.trim().is_empty() && resolve_key(key).is_none() {
+        source.error(field, format!("Unrecognized key '{key}'; binding disabled."));
+    }
+}
+
+fn string_at<'a>(table: &'a toml::Table, field: &str) -> Option<&'a str> {
+    let mut segments = field.split('.');
+    let mut current = table.get(segments.next()?)?;
+    for segment in segments {
+        current = current.as_table()?.get(segment)?;
+    }
+    current.as_str()
+}
+
+fn array_of_tables<'a>(
+    table: &'a toml::Table,
+    key: &str,
+) -> impl Iterator<Item = (usize, &'a toml::Table)> {
+    table
+        .get(key)
+        .and_then(toml::Value::as_array)
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .filter_map(|(index, value)| value.as_table().map(|table| (index, table)))
+}
@@ -0,0 +1,121 @@
+use std::{
+    fmt,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use bevy::prelude::Resource;
+
+/// Diagnostics reported by the loaders since the last `drain`. Each app keeps one as a
+/// resource and hands it to every loader it calls; clones share their entries, so the
+/// template asset loaders report into the collector of the app that registered them.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct DiagnosticCollector {
+    pending: Arc<Mutex<Vec<ConfigDiagnostic>>>,
+}
+
+impl DiagnosticCollector {
+    /// Queues a diagnostic. Identical entries (e.g. a broken template loaded once per
+    /// combo stack instance) are only kept once.
+    pub fn report(&self, diagnostic: ConfigDiagnostic) {
+        let mut pending = self.pending.lock().unwrap_or_else(PoisonError::into_inner);
+        if !pending.contains(&diagnostic) {
+            pending.push(diagnostic);
+        }
+    }
+
+    /// Takes every diagnostic reported since the last call.
+    pub fn drain(&self) -> Vec<ConfigDiagnostic> {
+        let mut pending = self.pending.lock().unwrap_or_else(PoisonError::into_inner);
+        std::mem::take(&mut *pending)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => f.write_str("error"),
+            Severity::Warning => f.write_str("warning"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigDiagnostic {
+    pub severity: Severity,
+    pub file: String,
+    /// 1-based line and column of the offending value, when it could be located.
+    pub location: Option<(usize, usize)>,
+    /// Path to the offending field, e.g. `entities[2].template`.
+    pub field: Option<String>,
+    pub message: String,
+}
+
+impl ConfigDiagnostic {
+    pub fn error(file: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            file: file.into(),
+            location: None,
+            field: None,
+            message: message.into(),
+        }
+    }
+
+    pub fn warning(file: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            ..Self::error(file, message)
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl fmt::Display for ConfigDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.file)?;
+        if let Some((line, column)) = self.location {
+            write!(f, ":{line}:{column}")?;
+        }
+        write!(f, ": {}: ", self.severity)?;
+        if let Some(field) = &self.field {
+            write!(f, "`{field}`: ")?;
+        }
+        f.write_str(&self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clones_share_entries_and_repeats_are_kept_once() {
+        let collector = DiagnosticCollector::default();
+        let loader = collector.clone();
+        loader.report(ConfigDiagnostic::error("a.toml", "Broken."));
+        loader.report(ConfigDiagnostic::error("a.toml", "Broken."));
+        collector.report(ConfigDiagnostic::warning("b.toml", "Odd."));
+
+        assert_eq!(collector.drain().len(), 2);
+        assert!(loader.drain().is_empty());
+    }
+
+    #[test]
+    fn separate_collectors_do_not_see_each_other() {
+        let first = DiagnosticCollector::default();
+        let second = DiagnosticCollector::default();
+        first.report(ConfigDiagnostic::error("a.toml", "Broken."));
+
+        assert!(second.drain().is_empty());
+        assert_eq!(first.drain().len(), 1);
+    }
+}
@@ -0,0 +1,11 @@
+mod checks;
+mod collector;
+mod overlay;
+mod report;
+mod source;
+
+pub use checks::{check_includes, check_input_bindings, check_template_refs, check_values};
+pub use collector::{ConfigDiagnostic, DiagnosticCollector, Severity};
+pub use overlay::refresh_diagnostics_overlay;
+pub use report::{collect_config_diagnostics, format_report, ConfigDiagnostics};
+pub use source::SourceFile;
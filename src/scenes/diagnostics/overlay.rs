@@ -0,0 +1,77 @@
+use bevy::{prelude::*, ui::PositionType};
+
+use crate::app_config::AppConfig;
+use crate::scenes::manager::SceneEntity;
+
+use super::report::ConfigDiagnostics;
+
+const MAX_OVERLAY_LINES: usize = 12;
+
+#[derive(Component)]
+#[require(SceneEntity)]
+pub struct ConfigDiagnosticsOverlay;
+
+/// Lists the current diagnostics in the bottom-left corner while in dev mode with
+/// `dev_tools.diagnostics_overlay` enabled.
+pub fn refresh_diagnostics_overlay(
+    mut commands: Commands,
+    diagnostics: Res<ConfigDiagnostics>,
+    app_config: Res<AppConfig>,
+    overlays: Query<Entity, With<ConfigDiagnosticsOverlay>>,
+) {
+    if !diagnostics.is_changed() {
+        return;
+    }
+    for entity in &overlays {
+        commands.entity(entity).try_despawn();
+    }
+    if !app_config.is_dev() || !app_config.dev_tools.diagnostics_overlay {
+        return;
+    }
+    if diagnostics.entries.is_empty() {
+        return;
+    }
+
+    let mut lines = vec![format!(
+        "{} config error(s), {} warning(s)",
+        diagnostics.error_count(),
+        diagnostics.warning_count()
+    )];
+    lines.extend(
+        diagnostics
+            .entries
+            .iter()
+            .take(MAX_OVERLAY_LINES)
+            .map(ToString::to_string),
+    );
+    if diagnostics.entries.len() > MAX_OVERLAY_LINES {
+        lines.push(format!(
+            "... and {} more (see log)",
+            diagnostics.entries.len() - MAX_OVERLAY_LINES
+        ));
+    }
+    let color = if diagnostics.error_count() > 0 {
+        Color::srgb_u8(255, 110, 110)
+    } else {
+        Color::srgb_u8(255, 210, 90)
+    };
+
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(8.0),
+            left: Val::Px(8.0),
+            padding: UiRect::all(Val::Px(6.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.75)),
+        GlobalZIndex(200),
+        Text::new(lines.join("\n")),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(color),
+        ConfigDiagnosticsOverlay,
+    ));
+}
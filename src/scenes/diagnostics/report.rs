@@ -0,0 +1,91 @@
+use std::collections::BTreeMap;
+
+use bevy::{
+    app::AppExit,
+    log::{error, warn},
+    prelude::*,
+};
+
+use crate::app_config::AppConfig;
+
+use super::collector::{ConfigDiagnostic, DiagnosticCollector};
+
+/// Every config problem found while loading the active scene. Cleared on scene switch;
+/// entries for a file are dropped when hot reload picks up a new version of it.
+#[derive(Resource, Debug, Default)]
+pub struct ConfigDiagnostics {
+    pub entries: Vec<ConfigDiagnostic>,
+}
+
+impl ConfigDiagnostics {
+    pub fn error_count(&self) -> usize {
+        self.entries.iter().filter(|entry| entry.is_error()).count()
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.entries.len() - self.error_count()
+    }
+
+    pub fn forget_file(&mut self, file: &str) {
+        self.entries.retain(|entry| entry.file != file);
+    }
+}
+
+/// Moves newly reported diagnostics into `ConfigDiagnostics` and prints them as one
+/// report. Prod builds exit with an error once the report is out if any of them is an
+/// error.
+pub fn collect_config_diagnostics(
+    collector: Res<DiagnosticCollector>,
+    mut diagnostics: ResMut<ConfigDiagnostics>,
+    app_config: Res<AppConfig>,
+    mut app_exit: MessageWriter<AppExit>,
+) {
+    let mut fresh = collector.drain();
+    fresh.retain(|entry| !diagnostics.entries.contains(entry));
+    if fresh.is_empty() {
+        return;
+    }
+
+    let report = format_report(&fresh);
+    if fresh.iter().any(ConfigDiagnostic::is_error) {
+        error!("{report}");
+    } else {
+        warn!("{report}");
+    }
+    diagnostics.entries.extend(fresh);
+
+    if app_config.is_prod() && diagnostics.error_count() > 0 {
+        error!("Refusing to run with config errors in prod mode.");
+        app_exit.write(AppExit::error());
+    }
+}
+
+/// Formats diagnostics grouped by file, each group ordered by line.
+pub fn format_report(entries: &[ConfigDiagnostic]) -> String {
+    let mut by_file: BTreeMap<&str, Vec<&ConfigDiagnostic>> = BTreeMap::new();
+    for entry in entries {
+        by_file.entry(entry.file.as_str()).or_default().push(entry);
+    }
+
+    let errors = entries.iter().filter(|entry| entry.is_error()).count();
+    let mut report = format!(
+        "Config diagnostics: {errors} error(s), {} warning(s)",
+        entries.len() - errors
+    );
+    for (file, mut file_entries) in by_file {
+        file_entries.sort_by_key(|entry| (entry.location, entry.severity));
+        report.push_str(&format!("\n  {file}"));
+        for entry in file_entries {
+            let location = entry
+                .location
+                .map(|(line, column)| format!("{line}:{column}"))
+                .unwrap_or_else(|| "-".to_string());
+            report.push_str(&format!("\n    {location:<8} {:<8}", entry.severity.to_string()));
+            if let Some(field) = &entry.field {
+                report.push_str(&format!("{field}: "));
+            }
+            report.push_str(&entry.message);
+        }
+    }
+    report
+}
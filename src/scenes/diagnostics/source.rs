@@ -0,0 +1,185 @@
+use serde::de::DeserializeOwned;
+use toml_edit::ImDocument;
+
+use super::collector::{ConfigDiagnostic, DiagnosticCollector, Severity};
+
+/// A config file's text, kept around so diagnostics can point at the line and column
+/// of the field they are about, and the collector they go to.
+pub struct SourceFile<'a> {
+    path: &'a str,
+    text: &'a str,
+    document: Option<ImDocument<&'a str>>,
+    diagnostics: &'a DiagnosticCollector,
+}
+
+impl<'a> SourceFile<'a> {
+    pub fn new(path: &'a str, text: &'a str, diagnostics: &'a DiagnosticCollector) -> Self {
+        Self {
+            path,
+            text,
+            document: ImDocument::parse(text).ok(),
+            diagnostics,
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        self.path
+    }
+
+    pub fn diagnostics(&self) -> &'a DiagnosticCollector {
+        self.diagnostics
+    }
+
+    pub fn error(&self, field: &str, message: impl Into<String>) {
+        self.report(Severity::Error, field, message.into());
+    }
+
+    pub fn warning(&self, field: &str, message: impl Into<String>) {
+        self.report(Severity::Warning, field, message.into());
+    }
+
+    /// Reports an error at a byte offset, for parse errors that carry their own span.
+    pub fn error_at(&self, offset: Option<usize>, message: impl Into<String>) {
+        self.diagnostics.report(ConfigDiagnostic {
+            severity: Severity::Error,
+            file: self.path.to_string(),
+            location: offset.map(|offset| self.line_column(offset)),
+            field: None,
+            message: message.into(),
+        });
+    }
+
+    /// Deserializes an already-parsed table, reporting unknown keys as warnings and a
+    /// failed deserialization as an error at the field that caused it.
+    pub fn deserialize<T: DeserializeOwned>(
+        &self,
+        table: toml::Table,
+        fallback: &str,
+    ) -> Option<T> {
+        let mut unknown = Vec::new();
+        let result = {
+            let mut on_ignored = |path: serde_ignored::Path| {
+                let mut field = String::new();
+                ignored_field_path(&path, &mut field);
+                unknown.push(field);
+            };
+            serde_path_to_error::deserialize::<_, T>(serde_ignored::Deserializer::new(
+                toml::Value::Table(table),
+                &mut on_ignored,
+            ))
+        };
+
+        for field in &unknown {
+            self.warning(field, "Unknown key; it is ignored.");
+        }
+
+        match result {
+            Ok(config) => Some(config),
+            Err(err) => {
+                let field = error_field_path(err.path());
+                let message =
+                    format!("{}. {fallback}", err.inner().message().trim_end_matches('.'));
+                self.report(Severity::Error, &field, message);
+                None
+            }
+        }
+    }
+
+    fn report(&self, severity: Severity, field: &str, message: String) {
+        self.diagnostics.report(ConfigDiagnostic {
+            severity,
+            file: self.path.to_string(),
+            location: self.locate(field),
+            field: (!field.is_empty()).then(|| field.to_string()),
+            message,
+        });
+    }
+
+    /// Line and column of the deepest part of `field` present in the file. A missing
+    /// field resolves to its parent table.
+    fn locate(&self, field: &str) -> Option<(usize, usize)> {
+        let document = self.document.as_ref()?;
+        let mut item = document.as_item();
+        let mut span = None;
+        for segment in field_segments(field) {
+            let next = match segment {
+                FieldSegment::Key(key) => item.get(key),
+                FieldSegment::Index(index) => item.get(index),
+            };
+            let Some(next) = next else {
+                break;
+            };
+            item = next;
+            span = item.span().or(span);
+        }
+        span.map(|span| self.line_column(span.start))
+    }
+
+    fn line_column(&self, offset: usize) -> (usize, usize) {
+        let before = &self.text[..offset.min(self.text.len())];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+        (line, before[line_start..].chars().count() + 1)
+    }
+}
+
+enum FieldSegment<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+/// Splits `a.b[2].c` into its keys and indices.
+fn field_segments(field: &str) -> Vec<FieldSegment<'_>> {
+    let mut segments = Vec::new();
+    for part in field.split('.').filter(|part| !part.is_empty()) {
+        let (key, mut rest) = part.split_at(part.find('[').unwrap_or(part.len()));
+        if !key.is_empty() {
+            segments.push(FieldSegment::Key(key));
+        }
+        while let Some(end) = rest.find(']') {
+            if let Ok(index) = rest[1..end].parse() {
+                segments.push(FieldSegment::Index(index));
+            }
+            rest = &rest[end + 1..];
+        }
+    }
+    segments
+}
+
+fn ignored_field_path(path: &serde_ignored::Path, out: &mut String) {
+    match path {
+        serde_ignored::Path::Root => {}
+        serde_ignored::Path::Seq { parent, index } => {
+            ignored_field_path(parent, out);
+            out.push_str(&format!("[{index}]"));
+        }
+        serde_ignored::Path::Map { parent, key } => {
+            ignored_field_path(parent, out);
+            if !out.is_empty() {
+                out.push('.');
+            }
+            out.push_str(key);
+        }
+        serde_ignored::Path::Some { parent }
+        | serde_ignored::Path::NewtypeStruct { parent }
+        | serde_ignored::Path::NewtypeVariant { parent } => ignored_field_path(parent, out),
+    }
+}
+
+fn error_field_path(path: &serde_path_to_error::Path) -> String {
+    let mut out = String::new();
+    for segment in path.iter() {
+        match segment {
+            serde_path_to_error::Segment::Seq { index } => out.push_str(&format!("[{index}]")),
+            serde_path_to_error::Segment::Map { key }
+            | serde_path_to_error::Segment::Enum { variant: key } => {
+                if !out.is_empty() {
+                    out.push('.');
+                }
+                out.push_str(key);
+            }
+            serde_path_to_error::Segment::Unknown => break,
+        }
+    }
+    out
+}
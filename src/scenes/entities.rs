@@ -1,14 +1,29 @@
+use bevy::asset::Asset;
+use bevy::reflect::TypePath;
+use schemars::JsonSchema;
 use serde::Deserialize;
 
-use crate::scenes::config::{EntityOverrides, TransformOverrides};
+use crate::scenes::config::{
+    ArrayConfig, EntityOverrides, IncludeConfig, PositionConfig, ScatterConfig, TransformOverrides,
+};
+use crate::scenes::format::file_stem;
 
-#[derive(Debug, Deserialize, Default)]
+#[derive(Asset, TypePath, Debug, Deserialize, JsonSchema, Default)]
 pub struct EntitiesConfig {
     #[serde(default)]
     pub entities: Vec<EntityPlacement>,
+    /// Files whose placements are appended after this file's own, in order.
+    #[serde(default)]
+    pub include: Vec<IncludeConfig>,
+    /// Generators whose copies follow the file's own placements, arrays first. The loader
+    /// expands them into `entities`.
+    #[serde(default)]
+    pub array: Vec<ArrayConfig>,
+    #[serde(default)]
+    pub scatter: Vec<ScatterConfig>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema, Clone, PartialEq)]
 pub struct EntityPlacement {
     pub template: String,
     #[serde(default)]
@@ -17,4 +32,48 @@ pub struct EntityPlacement {
     pub transform: TransformOverrides,
     #[serde(default)]
     pub overrides: EntityOverrides,
+    /// `name_override` of another placement in the same file to spawn this one as a child
+    /// of. The transform is then relative to the parent, and the placement moves with it.
+    #[serde(default)]
+    pub parent: Option<String>,
+    /// Index of the placement `parent` names among the loaded ones, set by the loader.
+    #[serde(skip)]
+    pub parent_index: Option<usize>,
+    /// Offset and name prefix accumulated from the includes this placement came through.
+    #[serde(skip)]
+    pub offset: PositionConfig,
+    #[serde(skip)]
+    pub name_prefix: String,
+}
+
+impl EntityPlacement {
+    /// The spawned name: `name_override` or `default`, behind any include prefix. `None`
+    /// leaves the spawner's own default in place.
+    pub fn spawn_name(&self, default: &str) -> Option<String> {
+        if self.name_prefix.is_empty() {
+            return self.name_override.clone();
+        }
+        let name = self.name_override.as_deref().unwrap_or(default);
+        Some(format!("{}{name}", self.name_prefix))
+    }
+
+    /// The placement transform with the include offset applied to `position`, or to
+    /// `base` when the placement does not set one. Children already move with their
+    /// parent's offset, so theirs is left out.
+    pub fn spawn_transform(&self, base: &PositionConfig) -> TransformOverrides {
+        let mut transform = self.transform.clone();
+        let offset = &self.offset;
+        let has_offset = offset.x != 0.0 || offset.y != 0.0 || offset.z != 0.0;
+        if has_offset && self.parent_index.is_none() {
+            let position = transform.position.get_or_insert_with(|| base.clone());
+            position.x += offset.x;
+            position.y += offset.y;
+            position.z += offset.z;
+        }
+        transform
+    }
+}
+
+pub fn is_combo_template(template: &str) -> bool {
+    template.starts_with("combo/") || file_stem(template).ends_with(".combo")
 }
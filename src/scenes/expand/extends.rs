@@ -0,0 +1,214 @@
+use std::{fs, io};
+
+use crate::scenes::config::action_config_path;
+use crate::scenes::diagnostics::{ConfigDiagnostic, DiagnosticCollector, SourceFile};
+use crate::scenes::format::SceneFormat;
+use crate::scenes::migrate::{migrate_table, FORMAT_VERSION_KEY};
+
+use super::vars::substitute_vars;
+
+/// Merges the `extends` chain of an entity template under it. Tables merge key by key
+/// at every depth, so a child only lists what differs from its parent; any other value
+/// in the child replaces the parent's. Parents have `scene_vars` substituted like the
+/// child and are read through `read`, which takes the parent's file path.
+pub fn resolve_extends(
+    source: &SourceFile,
+    scene: &str,
+    scene_vars: &toml::Table,
+    table: toml::Table,
+    read: &mut dyn FnMut(&str) -> io::Result<String>,
+) -> Option<toml::Table> {
+    let mut chain = vec![source.path().to_string()];
+    merge_ancestors(source, scene, scene_vars, table, read, &mut chain)
+}
+
+fn merge_ancestors(
+    source: &SourceFile,
+    scene: &str,
+    scene_vars: &toml::Table,
+    table: toml::Table,
+    read: &mut dyn FnMut(&str) -> io::Result<String>,
+    chain: &mut Vec<String>,
+) -> Option<toml::Table> {
+    let Some(parent) = table.get("extends") else {
+        return Some(table);
+    };
+    let Some(parent) = parent.as_str() else {
+        source.error("extends", "`extends` must be a template path.");
+        return None;
+    };
+
+    let parent_path = action_config_path(scene, parent);
+    let cycle = chain.contains(&parent_path);
+    chain.push(parent_path.clone());
+    if cycle {
+        source.error(
+            "extends",
+            format!("Template inheritance cycle: {}.", chain.join(" -> ")),
+        );
+        return None;
+    }
+
+    let Some(parent_table) = read_parent(&parent_path, scene_vars, read, source.diagnostics())
+    else {
+        source.error(
+            "extends",
+            format!("Parent template '{parent}' could not be loaded."),
+        );
+        return None;
+    };
+    let parent_table = merge_ancestors(source, scene, scene_vars, parent_table, read, chain)?;
+    Some(deep_merge(parent_table, table))
+}
+
+fn read_parent(
+    path: &str,
+    scene_vars: &toml::Table,
+    read: &mut dyn FnMut(&str) -> io::Result<String>,
+    diagnostics: &DiagnosticCollector,
+) -> Option<toml::Table> {
+    let contents = match read(path) {
+        Ok(text) => text,
+        Err(err) => {
+            let message = format!("Failed to read: {err}.");
+            diagnostics.report(ConfigDiagnostic::error(path, message));
+            return None;
+        }
+    };
+    let source = SourceFile::new(path, &contents, diagnostics);
+    match SceneFormat::of(path).parse(&contents) {
+        Ok(table) => match migrate_table(&source, &contents, table) {
+            Ok(table) => substitute_vars(&source, scene_vars, table),
+            Err(err) => {
+                source.error(FORMAT_VERSION_KEY, err);
+                None
+            }
+        },
+        Err(err) => {
+            source.error_at(err.offset, format!("Failed to parse: {}.", err.message));
+            None
+        }
+    }
+}
+
+fn deep_merge(mut base: toml::Table, overlay: toml::Table) -> toml::Table {
+    for (key, value) in overlay {
+        match (base.remove(&key), value) {
+            (Some(toml::Value::Table(base_table)), toml::Value::Table(overlay_table)) => {
+                base.insert(key, toml::Value::Table(deep_merge(base_table, overlay_table)));
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+    base
+}
+
+/// Scene-relative paths of every template `template` inherits from, nearest first. Used
+/// by hot reload, so unreadable files and cycles just end the chain.
+pub fn template_ancestors(scene: &str, template: &str) -> Vec<String> {
+    let mut ancestors: Vec<String> = Vec::new();
+    let mut current = template.to_string();
+    loop {
+        let parent = fs::read_to_string(action_config_path(scene, &current))
+            .ok()
+            .and_then(|text| SceneFormat::of(&current).parse(&text).ok())
+            .and_then(|table| table.get("extends")?.as_str().map(str::to_string));
+        let Some(parent) = parent else {
+            break;
+        };
+        if parent == template || ancestors.contains(&parent) {
+            break;
+        }
+        ancestors.push(parent.clone());
+        current = parent;
+    }
+    ancestors
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::scenes::migrate::CURRENT_FORMAT_VERSION;
+
+    use super::*;
+
+    /// Resolves `child` in scene `test` against the templates in `files`, keyed by their
+    /// scene-relative path and written for the current format.
+    fn resolve(
+        child: &str,
+        files: &[(&str, &str)],
+    ) -> (Option<toml::Table>, Vec<ConfigDiagnostic>) {
+        let files: HashMap<String, String> = files
+            .iter()
+            .map(|(file, text)| {
+                let text = format!("{FORMAT_VERSION_KEY} = {CURRENT_FORMAT_VERSION}\n{text}");
+                (action_config_path("test", file), text)
+            })
+            .collect();
+        let mut read = |path: &str| {
+            files
+                .get(path)
+                .cloned()
+                .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+        };
+        let diagnostics = DiagnosticCollector::default();
+        let path = action_config_path("test", "entities/child.3D.toml");
+        let source = SourceFile::new(&path, child, &diagnostics);
+        let table = toml::from_str(child).expect("test template is valid TOML");
+        let resolved = resolve_extends(&source, "test", &toml::Table::new(), table, &mut read);
+        (resolved, diagnostics.drain())
+    }
+
+    #[test]
+    fn children_merge_over_their_parents_table_by_table() {
+        let base = "[shape]\nkind = \"box\"\ncolor = \"red\"\n[physics]\nmass = 2.0\n";
+        let crate_ = "extends = \"entities/base.3D.toml\"\nname = \"crate\"\n";
+        let child = "extends = \"entities/crate.3D.toml\"\n[shape]\ncolor = \"blue\"\n";
+        let (resolved, diagnostics) = resolve(
+            child,
+            &[("entities/base.3D.toml", base), ("entities/crate.3D.toml", crate_)],
+        );
+
+        let expected: toml::Table = toml::from_str(
+            "extends = \"entities/crate.3D.toml\"\nname = \"crate\"\n\
+             [shape]\nkind = \"box\"\ncolor = \"blue\"\n[physics]\nmass = 2.0\n",
+        )
+        .unwrap();
+        assert_eq!(resolved, Some(expected));
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+    }
+
+    #[test]
+    fn inheritance_cycles_are_reported() {
+        let a = "extends = \"entities/b.3D.toml\"\n";
+        let b = "extends = \"entities/a.3D.toml\"\n";
+        let child = "extends = \"entities/a.3D.toml\"\n";
+        let (resolved, diagnostics) =
+            resolve(child, &[("entities/a.3D.toml", a), ("entities/b.3D.toml", b)]);
+
+        assert_eq!(resolved, None);
+        assert_eq!(diagnostics.len(), 1);
+        assert!(
+            diagnostics[0]
+                .message
+                .starts_with("Template inheritance cycle:"),
+            "{diagnostics:?}"
+        );
+    }
+
+    #[test]
+    fn missing_parents_are_reported() {
+        let child = "extends = \"entities/gone.3D.toml\"\n";
+        let (resolved, diagnostics) = resolve(child, &[]);
+
+        assert_eq!(resolved, None);
+        let messages: Vec<&str> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert!(
+            messages.contains(&"Parent template 'entities/gone.3D.toml' could not be loaded."),
+            "{messages:?}"
+        );
+    }
+}
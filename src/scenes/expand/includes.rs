@@ -0,0 +1,114 @@
+use std::fs;
+
+use crate::scenes::config::{action_config_path, SCENE_ROOT};
+use crate::scenes::format::SceneFormat;
+
+/// Scene-relative files matched by an `include` path or glob, sorted so the load order
+/// does not depend on the filesystem. `Err` carries the glob error for invalid patterns.
+pub fn include_paths(scene: &str, pattern: &str) -> Result<Vec<String>, String> {
+    let scene_dir = format!("{SCENE_ROOT}/{scene}");
+    let matches = glob::glob(&format!("{scene_dir}/{pattern}")).map_err(|err| err.to_string())?;
+    let mut paths: Vec<String> = matches
+        .flatten()
+        .filter(|path| path.is_file())
+        .filter_map(|path| {
+            let relative = path.strip_prefix(&scene_dir).ok()?;
+            Some(relative.to_string_lossy().replace('\\', "/"))
+        })
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+/// The `path` of an `include` entry as written, whether a plain string or a table.
+pub fn include_pattern(entry: &toml::Value) -> Option<&str> {
+    match entry {
+        toml::Value::String(path) => Some(path),
+        toml::Value::Table(table) => table.get("path")?.as_str(),
+        _ => None,
+    }
+}
+
+/// Every file `table` includes, directly or through nested includes. Used by hot reload,
+/// so unreadable files are skipped and cycles end the walk.
+pub fn included_files(scene: &str, table: &toml::Table) -> Vec<String> {
+    let mut files = Vec::new();
+    for file in direct_includes(scene, table) {
+        if files.contains(&file) {
+            continue;
+        }
+        let included = read_table(scene, &file);
+        files.push(file);
+        if let Some(included) = included {
+            for nested in included_files(scene, &included) {
+                if !files.contains(&nested) {
+                    files.push(nested);
+                }
+            }
+        }
+    }
+    files
+}
+
+/// The files `table`'s own `include` entries match, without following their includes.
+pub fn direct_includes(scene: &str, table: &toml::Table) -> Vec<String> {
+    let mut files = Vec::new();
+    for entry in raw_list(table, "include") {
+        let Some(pattern) = include_pattern(&entry) else {
+            continue;
+        };
+        for file in include_paths(scene, pattern).unwrap_or_default() {
+            if !files.contains(&file) {
+                files.push(file);
+            }
+        }
+    }
+    files
+}
+
+fn raw_list(table: &toml::Table, key: &str) -> Vec<toml::Value> {
+    match table.get(key) {
+        Some(toml::Value::Array(values)) => values.clone(),
+        _ => Vec::new(),
+    }
+}
+
+fn read_table(scene: &str, file: &str) -> Option<toml::Table> {
+    let text = fs::read_to_string(action_config_path(scene, file)).ok()?;
+    SceneFormat::of(file).parse(&text).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn globs_match_scene_relative_files_in_order() {
+        assert_eq!(
+            include_paths("main", "actions/*.toml").unwrap(),
+            [
+                "actions/fov.toml",
+                "actions/shoot-balls.toml",
+                "actions/sprint.toml",
+                "actions/zoom.toml"
+            ]
+        );
+        assert_eq!(include_paths("main", "props/missing.toml").unwrap(), Vec::<String>::new());
+        assert!(include_paths("main", "props/[").is_err());
+    }
+
+    #[test]
+    fn include_entries_are_paths_or_tables_with_a_path() {
+        let table: toml::Table = toml::from_str(
+            "include = [\"props/a.toml\", { path = \"props/b.toml\", prefix = \"b_\" }, 3]",
+        )
+        .unwrap();
+        let patterns: Vec<Option<&str>> = table["include"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(include_pattern)
+            .collect();
+        assert_eq!(patterns, [Some("props/a.toml"), Some("props/b.toml"), None]);
+    }
+}
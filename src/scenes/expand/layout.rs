@@ -0,0 +1,313 @@
+use std::{f32::consts::TAU, mem};
+
+use bevy::math::{Quat, Vec3};
+
+use crate::scenes::config::{
+    ArrayLayout, CubeRotationConfig, EntityOverrides, PositionConfig, RangeConfig, ScatterArea,
+    ScatterConfig, TransformOverrides,
+};
+use crate::scenes::diagnostics::{ConfigDiagnostic, DiagnosticCollector};
+use crate::scenes::entities::{EntitiesConfig, EntityPlacement};
+
+/// Random spots tried per scatter copy before giving up on keeping `min_spacing`.
+const SCATTER_ATTEMPTS: u32 = 30;
+
+/// Where one generated copy goes relative to its generator's placement.
+struct Slot {
+    offset: Vec3,
+    /// Radians about the vertical axis.
+    yaw: f32,
+    scale: Option<f32>,
+}
+
+/// Replaces the `[[array]]` and `[[scatter]]` entries of an entities file read from `path`
+/// with the placements they generate, appended after the file's own. Copies are moved
+/// through the include offset, so they spawn, reload and save like written placements.
+pub fn expand_layouts(
+    path: &str,
+    config: &mut EntitiesConfig,
+    diagnostics: &DiagnosticCollector,
+) {
+    for array in mem::take(&mut config.array) {
+        let slots = array_slots(&array.layout);
+        config.entities.extend(generated(
+            &array.template,
+            array.name_override.as_deref(),
+            &array.transform,
+            &array.overrides,
+            slots,
+        ));
+    }
+    for (index, scatter) in mem::take(&mut config.scatter).into_iter().enumerate() {
+        let slots = scatter_slots(&scatter);
+        if slots.len() < scatter.count as usize {
+            diagnostics.report(ConfigDiagnostic {
+                field: Some(format!("scatter[{index}].min_spacing")),
+                ..ConfigDiagnostic::warning(
+                    path,
+                    format!(
+                        "Only {} of {} copies of '{}' fit {} apart; the rest are left out.",
+                        slots.len(),
+                        scatter.count,
+                        scatter.template,
+                        scatter.min_spacing
+                    ),
+                )
+            });
+        }
+        config.entities.extend(generated(
+            &scatter.template,
+            scatter.name_override.as_deref(),
+            &scatter.transform,
+            &scatter.overrides,
+            slots,
+        ));
+    }
+}
+
+fn generated(
+    template: &str,
+    name_override: Option<&str>,
+    transform: &TransformOverrides,
+    overrides: &EntityOverrides,
+    slots: Vec<Slot>,
+) -> Vec<EntityPlacement> {
+    slots
+        .into_iter()
+        .enumerate()
+        .map(|(index, slot)| EntityPlacement {
+            template: template.to_string(),
+            name_override: name_override.map(|name| format!("{name}_{}", index + 1)),
+            transform: slot_transform(transform, &slot),
+            overrides: overrides.clone(),
+            parent: None,
+            parent_index: None,
+            offset: PositionConfig {
+                x: slot.offset.x,
+                y: slot.offset.y,
+                z: slot.offset.z,
+            },
+            name_prefix: String::new(),
+        })
+        .collect()
+}
+
+/// The generator's transform with the slot's turn applied on top of its rotation and its
+/// scale multiplied in.
+fn slot_transform(transform: &TransformOverrides, slot: &Slot) -> TransformOverrides {
+    let mut transform = transform.clone();
+    let base = transform.rotation.clone().unwrap_or_default();
+    // A copy looking at a target keeps looking at it from wherever it lands.
+    if slot.yaw != 0.0 && !matches!(base, CubeRotationConfig::LookAt { .. }) {
+        let rotation = Quat::from_rotation_y(slot.yaw) * base.to_quat(Vec3::ZERO);
+        transform.rotation = Some(CubeRotationConfig::from_quat(rotation));
+    }
+    if let Some(scale) = slot.scale {
+        transform.scale = Some(transform.scale.unwrap_or_default() * scale);
+    }
+    transform
+}
+
+/// Grid slots are numbered along x first, then y, then z.
+fn array_slots(layout: &ArrayLayout) -> Vec<Slot> {
+    match layout {
+        ArrayLayout::Grid { count, spacing } => {
+            let spacing = Vec3::new(spacing.x, spacing.y, spacing.z);
+            let mut slots = Vec::new();
+            for z in 0..count.z {
+                for y in 0..count.y {
+                    for x in 0..count.x {
+                        slots.push(Slot {
+                            offset: Vec3::new(x as f32, y as f32, z as f32) * spacing,
+                            yaw: 0.0,
+                            scale: None,
+                        });
+                    }
+                }
+            }
+            slots
+        }
+        ArrayLayout::Circle {
+            count,
+            radius,
+            face_center,
+        } => (0..*count)
+            .map(|index| {
+                let angle = TAU * index as f32 / *count as f32;
+                Slot {
+                    offset: Vec3::new(angle.cos(), 0.0, angle.sin()) * *radius,
+                    // Turns forward (-Z) towards the center.
+                    yaw: if *face_center { TAU / 4.0 - angle } else { 0.0 },
+                    scale: None,
+                }
+            })
+            .collect(),
+    }
+}
+
+/// Up to `count` slots; fewer when `min_spacing` leaves no room for the rest.
+fn scatter_slots(scatter: &ScatterConfig) -> Vec<Slot> {
+    let mut rng = SplitMix64(scatter.seed);
+    let mut slots: Vec<Slot> = Vec::new();
+    for _ in 0..scatter.count {
+        let free = (0..SCATTER_ATTEMPTS)
+            .map(|_| random_point(&scatter.area, &mut rng))
+            .find(|point| {
+                slots.iter().all(|slot| {
+                    let apart = (slot.offset - *point).with_y(0.0).length();
+                    apart >= scatter.min_spacing
+                })
+            });
+        let Some(offset) = free else {
+            continue;
+        };
+        slots.push(Slot {
+            offset,
+            yaw: scatter
+                .yaw
+                .as_ref()
+                .map_or(0.0, |yaw| rng.range(yaw).to_radians()),
+            scale: scatter.scale.as_ref().map(|scale| rng.range(scale)),
+        });
+    }
+    slots
+}
+
+fn random_point(area: &ScatterArea, rng: &mut SplitMix64) -> Vec3 {
+    match area {
+        ScatterArea::Box { size } => Vec3::new(
+            (rng.unit() - 0.5) * size.x,
+            (rng.unit() - 0.5) * size.y,
+            (rng.unit() - 0.5) * size.z,
+        ),
+        ScatterArea::Disc { radius } => {
+            // The square root spreads points evenly over the area instead of bunching
+            // them at the center.
+            let distance = radius * rng.unit().sqrt();
+            let angle = TAU * rng.unit();
+            Vec3::new(angle.cos(), 0.0, angle.sin()) * distance
+        }
+    }
+}
+
+/// Small seeded generator, so a scatter lays out the same way on every platform and
+/// build.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[0, 1)`.
+    fn unit(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    fn range(&mut self, range: &RangeConfig) -> f32 {
+        range.min + (range.max - range.min) * self.unit()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expanded(text: &str) -> (Vec<EntityPlacement>, Vec<ConfigDiagnostic>) {
+        let mut config: EntitiesConfig = toml::from_str(text).expect("test entities parse");
+        let diagnostics = DiagnosticCollector::default();
+        expand_layouts("entities.toml", &mut config, &diagnostics);
+        (config.entities, diagnostics.drain())
+    }
+
+    fn offset(placement: &EntityPlacement) -> Vec3 {
+        Vec3::new(placement.offset.x, placement.offset.y, placement.offset.z)
+    }
+
+    #[test]
+    fn grid_copies_follow_their_written_placements_x_first() {
+        let (entities, diagnostics) = expanded(
+            r#"
+            [[entities]]
+            template = "entities/cart.3D.toml"
+
+            [[array]]
+            template = "entities/crate.3D.toml"
+            name_override = "row"
+            layout = { kind = "grid", count = { x = 2, z = 2 }, spacing = { x = 1.0, z = 3.0 } }
+            "#,
+        );
+        assert!(diagnostics.is_empty());
+        assert_eq!(entities[0].template, "entities/cart.3D.toml");
+        let copies = &entities[1..];
+        let names: Vec<_> = copies.iter().map(|copy| copy.name_override.as_deref()).collect();
+        assert_eq!(names, [Some("row_1"), Some("row_2"), Some("row_3"), Some("row_4")]);
+        let offsets: Vec<_> = copies.iter().map(offset).collect();
+        assert_eq!(
+            offsets,
+            [
+                Vec3::ZERO,
+                Vec3::new(1.0, 0.0, 0.0),
+                Vec3::new(0.0, 0.0, 3.0),
+                Vec3::new(1.0, 0.0, 3.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn circle_copies_can_face_the_center() {
+        let (entities, _) = expanded(
+            r#"
+            [[array]]
+            template = "entities/crate.3D.toml"
+            layout = { kind = "circle", count = 4, radius = 2.0, face_center = true }
+            "#,
+        );
+        assert_eq!(entities.len(), 4);
+        for copy in &entities {
+            // The copy on +Z already faces the center and keeps the template's rotation.
+            let rotation = copy.transform.rotation.clone().unwrap_or_default();
+            let forward = rotation.to_quat(Vec3::ZERO) * Vec3::NEG_Z;
+            assert!(forward.abs_diff_eq(-offset(copy).normalize(), 1e-5), "{forward}");
+            assert!((offset(copy).length() - 2.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn scatters_are_seeded_spaced_and_warn_when_crowded() {
+        let scatter = |count: u32, min_spacing: f32| {
+            format!(
+                r#"
+                [[scatter]]
+                template = "entities/crate.3D.toml"
+                count = {count}
+                seed = 7
+                min_spacing = {min_spacing}
+                area = {{ kind = "box", size = {{ x = 10.0, y = 0.0, z = 4.0 }} }}
+                yaw = {{ min = 0.0, max = 90.0 }}
+                "#
+            )
+        };
+        let (first, diagnostics) = expanded(&scatter(6, 1.0));
+        let (second, _) = expanded(&scatter(6, 1.0));
+        assert!(diagnostics.is_empty());
+        assert_eq!(first.len(), 6);
+        let offsets: Vec<_> = first.iter().map(offset).collect();
+        assert_eq!(offsets, second.iter().map(offset).collect::<Vec<_>>());
+        for (index, a) in offsets.iter().enumerate() {
+            assert!(a.x.abs() <= 5.0 && a.y == 0.0 && a.z.abs() <= 2.0, "{a}");
+            for b in &offsets[index + 1..] {
+                assert!(a.distance(*b) >= 1.0);
+            }
+        }
+
+        let (crowded, diagnostics) = expanded(&scatter(6, 20.0));
+        assert_eq!(crowded.len(), 1);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].field.as_deref(), Some("scatter[0].min_spacing"));
+    }
+}
@@ -0,0 +1,11 @@
+mod extends;
+mod includes;
+mod layout;
+mod parents;
+mod vars;
+
+pub use extends::{resolve_extends, template_ancestors};
+pub use includes::{direct_includes, include_paths, include_pattern, included_files};
+pub use layout::expand_layouts;
+pub use parents::{check_parents, link_parents};
+pub use vars::{file_vars, scene_vars, substitute_vars};
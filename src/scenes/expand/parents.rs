@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+
+use crate::scenes::diagnostics::{ConfigDiagnostic, DiagnosticCollector};
+use crate::scenes::entities::EntityPlacement;
+
+/// Reports the placements of the entities file at `path` whose `parent` names no other
+/// placement of the file, or leads back to themselves, and clears those parents so they
+/// spawn unparented. Runs before includes are appended, as names are local to a file.
+pub fn check_parents(
+    path: &str,
+    entities: &mut [EntityPlacement],
+    diagnostics: &DiagnosticCollector,
+) {
+    let names = placement_names(entities);
+    let mut broken = Vec::new();
+    for (index, placement) in entities.iter().enumerate() {
+        let Some(parent) = placement.parent.as_deref() else {
+            continue;
+        };
+        let message = match names.get(parent) {
+            None => format!(
+                "Unknown parent '{parent}'; no placement in this file has that name_override. \
+                 Placing it without a parent."
+            ),
+            Some(_) => match parent_cycle(entities, &names, index) {
+                Some(cycle) => format!(
+                    "Parent cycle: {}. Placing it without a parent.",
+                    cycle.join(" -> ")
+                ),
+                None => continue,
+            },
+        };
+        diagnostics.report(ConfigDiagnostic {
+            field: Some(format!("entities[{index}].parent")),
+            ..ConfigDiagnostic::error(path, message)
+        });
+        broken.push(index);
+    }
+    for index in broken {
+        entities[index].parent = None;
+    }
+}
+
+/// Points each placement with a `parent` at that placement's index, once every included
+/// file has been appended. Names are matched behind the include prefix the placement came
+/// through, so a file included twice under different prefixes keeps its own parents.
+pub fn link_parents(entities: &mut [EntityPlacement]) {
+    let mut names = HashMap::new();
+    for (index, placement) in entities.iter().enumerate() {
+        if let Some(name) = &placement.name_override {
+            names
+                .entry(format!("{}{name}", placement.name_prefix))
+                .or_insert(index);
+        }
+    }
+    for placement in entities.iter_mut() {
+        placement.parent_index = placement.parent.as_ref().and_then(|parent| {
+            names
+                .get(&format!("{}{parent}", placement.name_prefix))
+                .copied()
+        });
+    }
+}
+
+/// The first placement with each `name_override`.
+fn placement_names(entities: &[EntityPlacement]) -> HashMap<&str, usize> {
+    let mut names = HashMap::new();
+    for (index, placement) in entities.iter().enumerate() {
+        if let Some(name) = placement.name_override.as_deref() {
+            names.entry(name).or_insert(index);
+        }
+    }
+    names
+}
+
+/// Names along the parent chain from `start` back to itself, if it loops.
+fn parent_cycle(
+    entities: &[EntityPlacement],
+    names: &HashMap<&str, usize>,
+    start: usize,
+) -> Option<Vec<String>> {
+    let name = |index: usize| {
+        entities[index]
+            .name_override
+            .clone()
+            .unwrap_or_else(|| entities[index].template.clone())
+    };
+    let mut chain = vec![name(start)];
+    let mut current = start;
+    // Any loop is found within one step per placement.
+    for _ in 0..entities.len() {
+        let parent = entities[current].parent.as_deref()?;
+        current = *names.get(parent)?;
+        chain.push(name(current));
+        if current == start {
+            return Some(chain);
+        }
+    }
+    None
+}
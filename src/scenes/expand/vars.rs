@@ -0,0 +1,435 @@
+use std::{collections::HashMap, fs};
+
+use crate::scenes::config::action_config_path;
+use crate::scenes::format::{scene_file, SceneFormat};
+use crate::scenes::loaders::WORLD_FILE;
+use crate::scenes::diagnostics::SourceFile;
+
+/// Table holding variables, in `world.toml` for the whole scene or in any single file.
+const VARS_KEY: &str = "vars";
+
+/// The `[vars]` table of the scene's `world.toml`, unresolved. Problems with the file are
+/// reported when the world itself loads, so they just leave the scene without variables.
+pub fn scene_vars(scene: &str) -> toml::Table {
+    let path = action_config_path(scene, &scene_file(scene, WORLD_FILE));
+    fs::read_to_string(&path)
+        .map(|text| file_vars(&path, &text))
+        .unwrap_or_default()
+}
+
+/// The `[vars]` table of the file at `path` holding `text`, or none when it does not parse.
+pub fn file_vars(path: &str, text: &str) -> toml::Table {
+    SceneFormat::of(path)
+        .parse(text)
+        .ok()
+        .and_then(|mut table| match table.remove(VARS_KEY) {
+            Some(toml::Value::Table(vars)) => Some(vars),
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+/// Replaces `${name}` references in every string value of `table`, with the file's own
+/// `[vars]` taking precedence over `scene_vars`, and drops the `[vars]` table.
+///
+/// A string that is a single reference takes the variable's value and type. Otherwise the
+/// references are spliced into the text. The result is evaluated as arithmetic (`+ - * /`
+/// and parentheses) only when the string itself, outside its references, has parentheses
+/// or an operator with spaces around it, as in `"${floor_height} + 2.0"`; a name such as
+/// `"${row}-${col}"` stays a string. Variables may reference each other.
+pub fn substitute_vars(
+    source: &SourceFile,
+    scene_vars: &toml::Table,
+    mut table: toml::Table,
+) -> Option<toml::Table> {
+    let mut raw = scene_vars.clone();
+    match table.remove(VARS_KEY) {
+        Some(toml::Value::Table(file_vars)) => raw.extend(file_vars),
+        Some(_) => {
+            source.error(VARS_KEY, "`vars` must be a table.");
+            return None;
+        }
+        None => {}
+    }
+
+    let mut vars = Vars {
+        raw: &raw,
+        resolved: HashMap::new(),
+        resolving: Vec::new(),
+    };
+    let mut failed = false;
+    let table = substitute_table(source, &mut vars, table, "", &mut failed);
+    (!failed).then_some(table)
+}
+
+fn substitute_table(
+    source: &SourceFile,
+    vars: &mut Vars,
+    table: toml::Table,
+    field: &str,
+    failed: &mut bool,
+) -> toml::Table {
+    table
+        .into_iter()
+        .map(|(key, value)| {
+            let child = if field.is_empty() {
+                key.clone()
+            } else {
+                format!("{field}.{key}")
+            };
+            let value = substitute_field(source, vars, value, &child, failed);
+            (key, value)
+        })
+        .collect()
+}
+
+fn substitute_field(
+    source: &SourceFile,
+    vars: &mut Vars,
+    value: toml::Value,
+    field: &str,
+    failed: &mut bool,
+) -> toml::Value {
+    match value {
+        toml::Value::Table(table) => {
+            toml::Value::Table(substitute_table(source, vars, table, field, failed))
+        }
+        toml::Value::Array(values) => toml::Value::Array(
+            values
+                .into_iter()
+                .enumerate()
+                .map(|(index, value)| {
+                    substitute_field(source, vars, value, &format!("{field}[{index}]"), failed)
+                })
+                .collect(),
+        ),
+        toml::Value::String(text) if text.contains("${") => {
+            match vars.substitute_string(&text) {
+                Ok(value) => value,
+                Err(message) => {
+                    source.error(field, format!("{message}."));
+                    *failed = true;
+                    toml::Value::String(text)
+                }
+            }
+        }
+        value => value,
+    }
+}
+
+struct Vars<'a> {
+    raw: &'a toml::Table,
+    resolved: HashMap<String, toml::Value>,
+    /// Variables currently being resolved, to catch ones that reference themselves.
+    resolving: Vec<String>,
+}
+
+impl Vars<'_> {
+    fn get(&mut self, name: &str) -> Result<toml::Value, String> {
+        if let Some(value) = self.resolved.get(name) {
+            return Ok(value.clone());
+        }
+        let raw = self
+            .raw
+            .get(name)
+            .ok_or_else(|| format!("Undefined variable '{name}'"))?
+            .clone();
+        if self.resolving.iter().any(|resolving| resolving == name) {
+            return Err(format!(
+                "Variable cycle: {} -> {name}",
+                self.resolving.join(" -> ")
+            ));
+        }
+
+        self.resolving.push(name.to_string());
+        let value = self.substitute_value(raw);
+        self.resolving.pop();
+        let value = value?;
+        self.resolved.insert(name.to_string(), value.clone());
+        Ok(value)
+    }
+
+    fn substitute_value(&mut self, value: toml::Value) -> Result<toml::Value, String> {
+        match value {
+            toml::Value::String(text) if text.contains("${") => self.substitute_string(&text),
+            toml::Value::Array(values) => values
+                .into_iter()
+                .map(|value| self.substitute_value(value))
+                .collect::<Result<_, _>>()
+                .map(toml::Value::Array),
+            toml::Value::Table(table) => table
+                .into_iter()
+                .map(|(key, value)| Ok((key, self.substitute_value(value)?)))
+                .collect::<Result<_, String>>()
+                .map(toml::Value::Table),
+            value => Ok(value),
+        }
+    }
+
+    fn substitute_string(&mut self, text: &str) -> Result<toml::Value, String> {
+        if let Some(name) = sole_reference(text) {
+            return self.get(name);
+        }
+
+        let mut spliced = String::new();
+        // The text with each reference as a placeholder, to tell whether the string itself
+        // asks for arithmetic rather than the values spliced into it.
+        let mut template = String::new();
+        let mut rest = text;
+        while let Some(start) = rest.find("${") {
+            spliced.push_str(&rest[..start]);
+            template.push_str(&rest[..start]);
+            template.push('_');
+            let end = rest[start..]
+                .find('}')
+                .map(|end| start + end)
+                .ok_or_else(|| format!("Unclosed `${{` in '{text}'"))?;
+            let name = rest[start + 2..end].trim();
+            match self.get(name)? {
+                toml::Value::String(value) => spliced.push_str(&value),
+                toml::Value::Integer(value) => spliced.push_str(&value.to_string()),
+                // Debug keeps the `.0`, so the result stays a float.
+                toml::Value::Float(value) => spliced.push_str(&format!("{value:?}")),
+                toml::Value::Boolean(value) => spliced.push_str(&value.to_string()),
+                _ => return Err(format!("Variable '{name}' is not a single value")),
+            }
+            rest = &rest[end + 1..];
+        }
+        spliced.push_str(rest);
+        template.push_str(rest);
+
+        if !is_arithmetic(&template) {
+            return Ok(toml::Value::String(spliced));
+        }
+        match Expression::new(&spliced).evaluate() {
+            Some((value, _)) if !value.is_finite() => {
+                Err(format!("'{spliced}' does not evaluate to a finite number"))
+            }
+            Some((value, false)) if value.fract() == 0.0 && value.abs() < i64::MAX as f64 => {
+                Ok(toml::Value::Integer(value as i64))
+            }
+            Some((value, _)) => Ok(toml::Value::Float(value)),
+            None => Ok(toml::Value::String(spliced)),
+        }
+    }
+}
+
+/// The variable name when `text` is exactly one `${name}` reference.
+fn sole_reference(text: &str) -> Option<&str> {
+    let name = text.trim().strip_prefix("${")?.strip_suffix('}')?;
+    (!name.contains(['{', '}'])).then(|| name.trim())
+}
+
+/// Whether `template`, a string with its references replaced, has parentheses or a binary
+/// operator set off by spaces.
+fn is_arithmetic(template: &str) -> bool {
+    let characters: Vec<char> = template.chars().collect();
+    characters
+        .iter()
+        .any(|character| matches!(character, '(' | ')'))
+        || characters.windows(3).any(|window| {
+            window[0].is_whitespace()
+                && matches!(window[1], '+' | '-' | '*' | '/')
+                && window[2].is_whitespace()
+        })
+}
+
+/// Recursive descent over `+ - * /`, unary signs, parentheses and number literals. The
+/// flag next to each value records whether a float literal or a division was involved.
+struct Expression<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl<'a> Expression<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { text, position: 0 }
+    }
+
+    /// `None` unless the whole text is one arithmetic expression.
+    fn evaluate(mut self) -> Option<(f64, bool)> {
+        let value = self.sum()?;
+        self.skip_whitespace();
+        (self.position == self.text.len()).then_some(value)
+    }
+
+    fn sum(&mut self) -> Option<(f64, bool)> {
+        let (mut value, mut float) = self.product()?;
+        while let Some(operator) = self.operator(&['+', '-']) {
+            let (rhs, rhs_float) = self.product()?;
+            value = if operator == '+' { value + rhs } else { value - rhs };
+            float |= rhs_float;
+        }
+        Some((value, float))
+    }
+
+    fn product(&mut self) -> Option<(f64, bool)> {
+        let (mut value, mut float) = self.factor()?;
+        while let Some(operator) = self.operator(&['*', '/']) {
+            let (rhs, rhs_float) = self.factor()?;
+            if operator == '*' {
+                value *= rhs;
+            } else {
+                value /= rhs;
+                float = true;
+            }
+            float |= rhs_float;
+        }
+        Some((value, float))
+    }
+
+    fn factor(&mut self) -> Option<(f64, bool)> {
+        if let Some(sign) = self.operator(&['-', '+']) {
+            let (value, float) = self.factor()?;
+            return Some((if sign == '-' { -value } else { value }, float));
+        }
+        if self.operator(&['(']).is_some() {
+            let value = self.sum()?;
+            self.operator(&[')'])?;
+            return Some(value);
+        }
+        self.number()
+    }
+
+    fn number(&mut self) -> Option<(f64, bool)> {
+        self.skip_whitespace();
+        let rest = &self.text[self.position..];
+        let mut length = 0;
+        let mut previous = ' ';
+        for character in rest.chars() {
+            let exponent_sign = matches!(character, '+' | '-') && matches!(previous, 'e' | 'E');
+            if !(character.is_ascii_digit()
+                || matches!(character, '.' | 'e' | 'E' | '_')
+                || exponent_sign)
+            {
+                break;
+            }
+            length += character.len_utf8();
+            previous = character;
+        }
+        let literal = rest[..length].replace('_', "");
+        if !literal.starts_with(|character: char| character.is_ascii_digit()) {
+            return None;
+        }
+        let value = literal.parse::<f64>().ok()?;
+        self.position += length;
+        Some((value, literal.contains(['.', 'e', 'E'])))
+    }
+
+    fn operator(&mut self, operators: &[char]) -> Option<char> {
+        self.skip_whitespace();
+        let next = self.text[self.position..].chars().next()?;
+        operators.contains(&next).then(|| {
+            self.position += next.len_utf8();
+            next
+        })
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.text[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(text: &str) -> Option<(f64, bool)> {
+        Expression::new(text).evaluate()
+    }
+
+    fn substitute(vars: &str, text: &str) -> Result<toml::Value, String> {
+        let raw: toml::Table = toml::from_str(vars).expect("test vars are valid TOML");
+        let mut vars = Vars {
+            raw: &raw,
+            resolved: HashMap::new(),
+            resolving: Vec::new(),
+        };
+        vars.substitute_string(text)
+    }
+
+    #[test]
+    fn expressions_follow_operator_precedence() {
+        assert_eq!(evaluate("1 + 2 * 3"), Some((7.0, false)));
+        assert_eq!(evaluate("(1 + 2) * 3"), Some((9.0, false)));
+        assert_eq!(evaluate("10 - 4 - 3"), Some((3.0, false)));
+        assert_eq!(evaluate("-2 * -(3 + 1)"), Some((8.0, false)));
+    }
+
+    #[test]
+    fn floats_and_division_make_float_results() {
+        assert_eq!(evaluate("1.5 * 2"), Some((3.0, true)));
+        assert_eq!(evaluate("6 / 3"), Some((2.0, true)));
+        assert_eq!(evaluate("1e3 + 1_000"), Some((2000.0, true)));
+    }
+
+    #[test]
+    fn text_that_is_not_one_expression_does_not_evaluate() {
+        assert_eq!(evaluate("1 +"), None);
+        assert_eq!(evaluate("(1 + 2"), None);
+        assert_eq!(evaluate("two * 3"), None);
+        assert_eq!(evaluate("1 2"), None);
+    }
+
+    #[test]
+    fn a_sole_reference_keeps_the_variable_type() {
+        assert_eq!(
+            substitute("power = 75000.0", "${power}"),
+            Ok(toml::Value::Float(75000.0))
+        );
+        assert_eq!(
+            substitute("name = \"lamp\"", " ${ name } "),
+            Ok(toml::Value::String("lamp".to_string()))
+        );
+    }
+
+    #[test]
+    fn spaced_operators_in_the_string_evaluate() {
+        let vars = "spacing = 2\nheight = 1.5";
+        assert_eq!(
+            substitute(vars, "${spacing} * 3"),
+            Ok(toml::Value::Integer(6))
+        );
+        assert_eq!(
+            substitute(vars, "${height} + 2"),
+            Ok(toml::Value::Float(3.5))
+        );
+        assert_eq!(
+            substitute(vars, "(${spacing}+1)*2"),
+            Ok(toml::Value::Integer(6))
+        );
+    }
+
+    #[test]
+    fn spliced_names_stay_strings() {
+        let vars = "row = 3\ncol = 4\nprefix = \"crate\"";
+        assert_eq!(
+            substitute(vars, "${row}-${col}"),
+            Ok(toml::Value::String("3-4".to_string()))
+        );
+        assert_eq!(
+            substitute(vars, "${prefix}_${row}"),
+            Ok(toml::Value::String("crate_3".to_string()))
+        );
+    }
+
+    #[test]
+    fn division_by_zero_is_an_error() {
+        let err = substitute("zero = 0", "1 / ${zero}").unwrap_err();
+        assert!(err.contains("finite"), "{err}");
+    }
+
+    #[test]
+    fn variables_resolve_through_each_other_and_report_cycles() {
+        let vars = "base = 2\ndouble = \"${base} * 2\"\na = \"${b}\"\nb = \"${a}\"";
+        assert_eq!(
+            substitute(vars, "${double} + 1"),
+            Ok(toml::Value::Integer(5))
+        );
+        let cycle = substitute(vars, "${a}").unwrap_err();
+        assert!(cycle.contains("cycle"), "{cycle}");
+        let missing = substitute(vars, "${missing}").unwrap_err();
+        assert!(missing.contains("Undefined"), "{missing}");
+    }
+}
@@ -0,0 +1,418 @@
+use std::{cell::RefCell, collections::VecDeque, fmt, path::Path};
+
+use serde::{
+    de::{DeserializeSeed, Deserializer, Error, MapAccess, SeqAccess, Visitor},
+    Serialize,
+};
+
+use crate::scenes::config::SCENE_ROOT;
+
+/// Extensions scene files may use, in the order a file name's alternatives are tried.
+pub const SCENE_EXTENSIONS: &[&str] = &["toml", "json", "ron"];
+
+/// Syntax of a scene file, picked from its extension. Every format is parsed into the
+/// same `toml::Table`, so variables, includes, `extends` and the config types work the
+/// same way whatever a file is written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SceneFormat {
+    Toml,
+    Json,
+    Ron,
+}
+
+/// A parse failure with the byte offset it points at, when the parser reports one.
+pub struct FormatError {
+    pub message: String,
+    pub offset: Option<usize>,
+}
+
+impl SceneFormat {
+    /// The format of `path`; anything without a `.json` or `.ron` extension is TOML.
+    pub fn of(path: impl AsRef<Path>) -> Self {
+        match path.as_ref().extension().and_then(|ext| ext.to_str()) {
+            Some("json") => SceneFormat::Json,
+            Some("ron") => SceneFormat::Ron,
+            _ => SceneFormat::Toml,
+        }
+    }
+
+    pub fn parse(self, text: &str) -> Result<toml::Table, FormatError> {
+        match self {
+            SceneFormat::Toml => toml::from_str(text).map_err(|err| FormatError {
+                message: err.message().trim_end_matches('.').to_string(),
+                offset: err.span().map(|span| span.start),
+            }),
+            SceneFormat::Json => serde_json::from_str(text).map_err(|err| FormatError {
+                message: strip_position(&err.to_string()),
+                offset: offset_of(text, err.line(), err.column()),
+            }),
+            SceneFormat::Ron => {
+                let names = RefCell::new(ron_unit_names(text));
+                let value = ron::Options::default()
+                    .from_str_seed(text, RonValue(&names))
+                    .map_err(|err| FormatError {
+                        message: err.code.to_string().trim_end_matches('.').to_string(),
+                        offset: offset_of(text, err.position.line, err.position.col),
+                    })?;
+                match value {
+                    toml::Value::Table(table) => Ok(table),
+                    _ => Err(FormatError {
+                        message: "Expected a map or struct at the top of the file".to_string(),
+                        offset: Some(0),
+                    }),
+                }
+            }
+        }
+    }
+
+    /// Writes `value` in this format. JSON has no comments, so only TOML and RON files
+    /// keep a header.
+    pub fn write<T: Serialize>(self, value: &T, header: &str) -> Result<String, String> {
+        match self {
+            SceneFormat::Toml => toml::to_string_pretty(value)
+                .map(|text| format!("{}{text}", comment_lines(header, "#")))
+                .map_err(|err| err.to_string()),
+            SceneFormat::Json => serde_json::to_string_pretty(value)
+                .map(|text| format!("{text}\n"))
+                .map_err(|err| err.to_string()),
+            SceneFormat::Ron => {
+                ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())
+                    .map(|text| format!("{}{text}\n", comment_lines(header, "//")))
+                    .map_err(|err| err.to_string())
+            }
+        }
+    }
+}
+
+/// Whether `path` has one of the `SCENE_EXTENSIONS`.
+pub fn is_scene_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| SCENE_EXTENSIONS.contains(&ext))
+}
+
+/// `path` without its scene file extension, for matching files by name in any format.
+pub fn file_stem(path: &str) -> &str {
+    SCENE_EXTENSIONS
+        .iter()
+        .find_map(|ext| path.strip_suffix(ext)?.strip_suffix('.'))
+        .unwrap_or(path)
+}
+
+/// The name `file` has in `scene`: `file` itself, or the same name in another format when
+/// only that exists. Falls back to `file`, so a missing file is reported by its usual name.
+pub fn scene_file(scene: &str, file: &str) -> String {
+    existing_alternative(&format!("{SCENE_ROOT}/{scene}"), file)
+}
+
+/// Like `scene_file` for a path that is not inside a scene, such as an overlay.
+pub fn resolve_format(path: &str) -> String {
+    existing_alternative("", path)
+}
+
+fn existing_alternative(dir: &str, file: &str) -> String {
+    let full = |name: &str| match dir {
+        "" => name.to_string(),
+        _ => format!("{dir}/{name}"),
+    };
+    if Path::new(&full(file)).is_file() {
+        return file.to_string();
+    }
+    let stem = file_stem(file);
+    SCENE_EXTENSIONS
+        .iter()
+        .map(|ext| format!("{stem}.{ext}"))
+        .find(|name| Path::new(&full(name)).is_file())
+        .unwrap_or_else(|| file.to_string())
+}
+
+fn comment_lines(header: &str, marker: &str) -> String {
+    header
+        .lines()
+        .map(|line| format!("{marker} {line}\n"))
+        .chain((!header.is_empty()).then(|| "\n".to_string()))
+        .collect()
+}
+
+/// Reads RON straight into TOML values, so struct syntax such as `(x: 1.5, y: 0.0)` reads
+/// as a table. RON hands a unit variant such as the `grid` in `kind: grid` to visitors as
+/// `()`, dropping its name, so the names are taken from `ron_unit_names` in the order the
+/// visitor meets them.
+#[derive(Clone, Copy)]
+struct RonValue<'a, 'text>(&'a RefCell<VecDeque<Option<&'text str>>>);
+
+impl<'de> DeserializeSeed<'de> for RonValue<'_, '_> {
+    type Value = toml::Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for RonValue<'_, '_> {
+    type Value = toml::Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a value scene files can hold")
+    }
+
+    fn visit_bool<E: Error>(self, value: bool) -> Result<Self::Value, E> {
+        Ok(toml::Value::Boolean(value))
+    }
+
+    fn visit_i64<E: Error>(self, value: i64) -> Result<Self::Value, E> {
+        Ok(toml::Value::Integer(value))
+    }
+
+    fn visit_u64<E: Error>(self, value: u64) -> Result<Self::Value, E> {
+        i64::try_from(value)
+            .map(toml::Value::Integer)
+            .map_err(|_| E::custom(format!("{value} is too large for a scene file")))
+    }
+
+    fn visit_f64<E: Error>(self, value: f64) -> Result<Self::Value, E> {
+        Ok(toml::Value::Float(value))
+    }
+
+    fn visit_str<E: Error>(self, value: &str) -> Result<Self::Value, E> {
+        Ok(toml::Value::String(value.to_string()))
+    }
+
+    fn visit_unit<E: Error>(self) -> Result<Self::Value, E> {
+        match self.0.borrow_mut().pop_front().flatten() {
+            Some(name) => Ok(toml::Value::String(name.to_string())),
+            None => Err(E::custom("`()` has no meaning in scene files")),
+        }
+    }
+
+    fn visit_none<E: Error>(self) -> Result<Self::Value, E> {
+        Err(E::custom("`None` has no meaning in scene files; leave the field out instead"))
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        self.deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut values = Vec::new();
+        while let Some(value) = seq.next_element_seed(self)? {
+            values.push(value);
+        }
+        Ok(toml::Value::Array(values))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut table = toml::Table::new();
+        while let Some(key) = map.next_key::<String>()? {
+            table.insert(key, map.next_value_seed(self)?);
+        }
+        Ok(toml::Value::Table(table))
+    }
+}
+
+/// Every value RON reads as `()`, in order: the name of each bare identifier that is not a
+/// keyword, field name or struct name, and `None` for a literal `()`.
+fn ron_unit_names(text: &str) -> VecDeque<Option<&str>> {
+    const KEYWORDS: &[&str] =
+        &["true", "false", "None", "inf", "inff32", "inff64", "NaN", "NaNf32", "NaNf64"];
+    let mut names = VecDeque::new();
+    let mut rest = text;
+    let mut after_name = false;
+    while let Some(c) = rest.chars().next() {
+        let len = if rest.starts_with("//") || rest.starts_with("/*") {
+            rest.len() - skip_ron_trivia(rest).len()
+        } else if c == '#' {
+            // An extensions attribute such as `#![enable(implicit_some)]`.
+            rest.find(']').map_or(rest.len(), |end| end + 1)
+        } else if let Some(len) = ron_literal_len(rest) {
+            len
+        } else if c.is_ascii_digit() || matches!(c, '+' | '-' | '.') {
+            ron_number_len(rest)
+        } else if c.is_alphabetic() || c == '_' {
+            let raw = if rest.starts_with("r#") { 2 } else { 0 };
+            let len = raw
+                + rest[raw..]
+                    .find(|c: char| !c.is_alphanumeric() && c != '_')
+                    .unwrap_or(rest.len() - raw);
+            let name = &rest[raw..len];
+            match skip_ron_trivia(&rest[len..]).chars().next() {
+                Some('(') => {
+                    after_name = true;
+                    rest = &rest[len..];
+                    continue;
+                }
+                Some(':') => {}
+                _ if KEYWORDS.contains(&name) => {}
+                _ => names.push_back(Some(name)),
+            }
+            len
+        } else {
+            if c == '(' && !after_name && skip_ron_trivia(&rest[1..]).starts_with(')') {
+                names.push_back(None);
+            }
+            c.len_utf8()
+        };
+        if !c.is_whitespace() {
+            after_name = false;
+        }
+        rest = &rest[len..];
+    }
+    names
+}
+
+/// `text` after any leading whitespace and comments. Block comments nest in RON.
+fn skip_ron_trivia(mut text: &str) -> &str {
+    loop {
+        text = text.trim_start();
+        if text.starts_with("//") {
+            text = text.find('\n').map_or("", |end| &text[end..]);
+        } else if text.starts_with("/*") {
+            let mut depth = 0;
+            let mut end = text.len();
+            for (index, _) in text.char_indices() {
+                if text[index..].starts_with("/*") {
+                    depth += 1;
+                } else if text[index..].starts_with("*/") && index > 0 {
+                    depth -= 1;
+                    if depth == 0 {
+                        end = index + 2;
+                        break;
+                    }
+                }
+            }
+            text = &text[end..];
+        } else {
+            return text;
+        }
+    }
+}
+
+/// Length of the string, raw string, byte string or character literal `text` starts with.
+fn ron_literal_len(text: &str) -> Option<usize> {
+    let prefix = usize::from(text.starts_with('b'));
+    let body = &text[prefix..];
+    if let Some(raw) = body.strip_prefix('r') {
+        let hashes = raw.len() - raw.trim_start_matches('#').len();
+        let quoted = raw[hashes..].strip_prefix('"')?;
+        let closing = format!("\"{}", "#".repeat(hashes));
+        let end = quoted.find(&closing).map_or(quoted.len(), |end| end + closing.len());
+        return Some(prefix + 1 + hashes + 1 + end);
+    }
+    let quote = body.chars().next().filter(|c| matches!(c, '"' | '\''))?;
+    let mut escaped = false;
+    for (index, c) in body.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            _ if c == quote => return Some(prefix + index + 1),
+            _ => {}
+        }
+    }
+    Some(text.len())
+}
+
+/// Length of the number `text` starts with, including its sign and exponent.
+fn ron_number_len(text: &str) -> usize {
+    let mut previous = ' ';
+    text.char_indices()
+        .find(|&(index, c)| {
+            let exponent_sign = matches!(c, '+' | '-') && (index == 0 || "eE".contains(previous));
+            previous = c;
+            !(c.is_alphanumeric() || matches!(c, '_' | '.') || exponent_sign)
+        })
+        .map_or(text.len(), |(index, _)| index)
+}
+
+/// serde_json appends " at line L column C", which the diagnostic location already shows.
+fn strip_position(message: &str) -> String {
+    match message.rfind(" at line ") {
+        Some(index) => message[..index].to_string(),
+        None => message.to_string(),
+    }
+}
+
+/// Byte offset of a 1-based line and column.
+fn offset_of(text: &str, line: usize, column: usize) -> Option<usize> {
+    let line_start = match line {
+        0 => return None,
+        1 => 0,
+        _ => text.match_indices('\n').nth(line - 2)?.0 + 1,
+    };
+    let column_offset = text[line_start..]
+        .char_indices()
+        .nth(column.saturating_sub(1))
+        .map_or(text.len() - line_start, |(offset, _)| offset);
+    Some(line_start + column_offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_format_parses_to_the_same_table() {
+        let texts = [
+            (
+                SceneFormat::Toml,
+                "template = \"crate\"\nkind = \"grid\"\nposition = { x = 1.5, y = 0.0 }\n",
+            ),
+            (
+                SceneFormat::Json,
+                r#"{ "template": "crate", "kind": "grid", "position": { "x": 1.5, "y": 0.0 } }"#,
+            ),
+            (
+                SceneFormat::Ron,
+                r#"{ "template": "crate", "kind": "grid", "position": { "x": 1.5, "y": 0.0 } }"#,
+            ),
+            (
+                SceneFormat::Ron,
+                "#![enable(implicit_some)]\n// A crate.\n(\n    template: \"crate\", /* (a) */\n\
+                 \x20   kind: grid,\n    position: Position(x: 1.5, y: 0.0),\n)\n",
+            ),
+        ];
+        let parse = |format: SceneFormat, text: &str| {
+            format.parse(text).unwrap_or_else(|err| panic!("{format:?}: {}", err.message))
+        };
+        let expected = parse(SceneFormat::Toml, texts[0].1);
+        for (format, text) in texts {
+            let table = parse(format, text);
+            assert_eq!(table, expected, "{format:?}");
+            let written = format.write(&table, "Saved.").unwrap();
+            assert_eq!(parse(format, &written), expected, "{format:?} written back");
+        }
+    }
+
+    #[test]
+    fn ron_unit_values_read_as_their_names() {
+        let text = concat!(
+            r#"(kinds: [box, r#loop, Some(sphere)], s: "not_a_name", c: '\'', "#,
+            "e: 1e-3, u: ())",
+        );
+        let names: Vec<_> = ron_unit_names(text).into_iter().collect();
+        assert_eq!(names, [Some("box"), Some("loop"), Some("sphere"), None]);
+        let err = SceneFormat::Ron.parse(text).err().unwrap();
+        assert_eq!(err.message, "`()` has no meaning in scene files");
+        assert_eq!(err.offset, text.find("())").map(|offset| offset + 2));
+    }
+
+    #[test]
+    fn parse_errors_point_at_the_offending_byte() {
+        let text = "{\n  \"a\": 1,\n  \"b\": ]\n}";
+        let err = SceneFormat::Json.parse(text).err().unwrap();
+        assert!(!err.message.contains(" at line "), "{}", err.message);
+        assert_eq!(err.offset, text.find(']'));
+    }
+
+    #[test]
+    fn files_are_matched_by_name_in_any_format() {
+        assert_eq!(SceneFormat::of("entities/crate.3D.json"), SceneFormat::Json);
+        assert_eq!(SceneFormat::of("combo/stack.ron"), SceneFormat::Ron);
+        assert_eq!(SceneFormat::of("world.toml"), SceneFormat::Toml);
+        assert_eq!(file_stem("entities/crate.3D.json"), "entities/crate.3D");
+        assert_eq!(file_stem("notes.txt"), "notes.txt");
+        assert!(is_scene_file(Path::new("a/b.ron")));
+        assert!(!is_scene_file(Path::new("a/b.txt")));
+        assert_eq!(scene_file("main", "entities.json"), "entities.toml");
+        assert_eq!(scene_file("main", "missing.json"), "missing.json");
+    }
+}
@@ -10,6 +10,7 @@ use bevy_rapier3d::prelude::{
 };
 
 use crate::scenes::bounds::DespawnOutsideBounds;
+use crate::scenes::manager::SceneEntity;
 
 use super::types::{
     SceneCamera, SceneFovConfig, SceneShootConfig, SceneSprintConfig, SceneZoomConfig, SprintState, ZoomState,
@@ -159,6 +160,7 @@ pub fn apply_shoot_action(
             bevy::prelude::MeshMaterial3d(config.material.clone()),
             Transform::from_translation(spawn_pos),
             DespawnOutsideBounds,
+            SceneEntity,
             Velocity {
                 linvel: forward * config.action.velocity,
                 angvel: spin,
@@ -8,8 +8,8 @@ pub use actions::{
 };
 pub use camera::apply_camera_input;
 pub use resolve::{
-    resolve_camera_input_config, resolve_key_or_warn, resolve_mouse_button_or_warn,
-    resolve_overlay_toggles,
+    resolve_camera_input_config, resolve_key, resolve_key_or_warn, resolve_mouse_button,
+    resolve_mouse_button_or_warn, resolve_overlay_toggles,
 };
 pub use types::{
     FovBinding, SceneCamera, SceneFovConfig, SceneInputConfig, SceneShootConfig,
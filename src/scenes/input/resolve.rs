@@ -40,6 +40,7 @@ pub fn resolve_overlay_toggles(overlays: &[OverlayInputConfig]) -> Vec<ResolvedO
         .map(|ovr| ResolvedOverlayToggle {
             name: ovr.name.clone(),
             toggle: resolve_key_or_warn(&ovr.toggle, &format!("overlay toggle '{}'", ovr.name)),
+            dev_only: ovr.dev_only,
         })
         .collect()
 }
@@ -83,7 +84,7 @@ pub fn resolve_mouse_button_or_warn(button: &str, action: &str) -> Option<MouseB
     }
 }
 
-fn resolve_key(key: &str) -> Option<KeyCode> {
+pub fn resolve_key(key: &str) -> Option<KeyCode> {
     let normalized = key.trim().to_ascii_lowercase();
     // Letters
     if normalized.len() == 1 {
@@ -140,6 +141,20 @@ fn resolve_key(key: &str) -> Option<KeyCode> {
         "8" => Some(KeyCode::Digit8),
         "9" => Some(KeyCode::Digit9),
 
+        // Function keys
+        "f1" => Some(KeyCode::F1),
+        "f2" => Some(KeyCode::F2),
+        "f3" => Some(KeyCode::F3),
+        "f4" => Some(KeyCode::F4),
+        "f5" => Some(KeyCode::F5),
+        "f6" => Some(KeyCode::F6),
+        "f7" => Some(KeyCode::F7),
+        "f8" => Some(KeyCode::F8),
+        "f9" => Some(KeyCode::F9),
+        "f10" => Some(KeyCode::F10),
+        "f11" => Some(KeyCode::F11),
+        "f12" => Some(KeyCode::F12),
+
         // Punctuation by name
         "space" => Some(KeyCode::Space),
         "enter" | "return" => Some(KeyCode::Enter),
@@ -172,7 +187,7 @@ fn resolve_key(key: &str) -> Option<KeyCode> {
     }
 }
 
-fn resolve_mouse_button(button: &str) -> Option<MouseButton> {
+pub fn resolve_mouse_button(button: &str) -> Option<MouseButton> {
     match button.trim().to_ascii_lowercase().as_str() {
         "left" | "lmb" => Some(MouseButton::Left),
         "right" | "rmb" => Some(MouseButton::Right),
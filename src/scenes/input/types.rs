@@ -5,11 +5,14 @@ use bevy::{
 };
 
 use crate::scenes::config::{PhysicsConfig, ShapeConfig, ShootActionConfig, SprintActionConfig, ZoomActionConfig};
+use crate::scenes::manager::SceneEntity;
 
 #[derive(Resource, Debug, Clone)]
 pub struct SceneInputConfig {
     pub camera: ResolvedCameraInputConfig,
     pub overlays: Vec<ResolvedOverlayToggle>,
+    pub next_scene: Option<KeyCode>,
+    pub save_scene: Option<KeyCode>,
 }
 
 #[derive(Debug, Clone)]
@@ -47,6 +50,7 @@ pub enum CameraControl {
 pub struct ResolvedOverlayToggle {
     pub name: String,
     pub toggle: Option<KeyCode>,
+    pub dev_only: bool,
 }
 
 #[derive(Resource, Clone)]
@@ -95,4 +99,5 @@ pub struct SprintState {
 }
 
 #[derive(Component)]
+#[require(SceneEntity)]
 pub struct SceneCamera;
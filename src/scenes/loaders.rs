@@ -1,208 +1,470 @@
-use std::fs;
+use std::{fs, io, mem};
+
+use serde::de::DeserializeOwned;
 
 use crate::scenes::config::{
-    action_config_path, ComboTemplate, EntityTemplate, InputConfig, OverlayConfig,
-    ShootActionConfig, SprintActionConfig, ZoomActionConfig,
+    action_config_path, ComboTemplate, EntityTemplate, IncludeConfig, InputConfig, LightEntry,
+    OverlayConfig, ShootActionConfig, SprintActionConfig, ZoomActionConfig,
     input_config_path, overlay_config_path,
 };
-use crate::scenes::entities::EntitiesConfig;
-use crate::scenes::world::WorldConfig;
+use crate::scenes::diagnostics::{
+    check_includes, check_input_bindings, check_template_refs, check_values, ConfigDiagnostic,
+    DiagnosticCollector, SourceFile,
+};
+use crate::scenes::entities::{EntitiesConfig, EntityPlacement};
+use crate::scenes::expand::{
+    check_parents, expand_layouts, include_paths, link_parents, resolve_extends, scene_vars,
+    substitute_vars,
+};
+use crate::scenes::format::{scene_file, SceneFormat};
+use crate::scenes::migrate::{migrate_table, FORMAT_VERSION_KEY};
+use crate::scenes::world::{LightsConfig, WorldConfig};
 use bevy::log::{info, warn};
 
-pub fn load_entity_template_from_path(scene: &str, template_path: &str) -> Option<EntityTemplate> {
-    let path = action_config_path(scene, template_path);
-    let contents = match fs::read_to_string(&path) {
-        Ok(text) => text,
-        Err(err) => {
-            warn!("Failed to read {path}: {err}. Skipping entity template.");
-            return None;
-        }
-    };
+/// Default names of the scene's fixed files; `scene_file` finds them in other formats.
+pub const WORLD_FILE: &str = "world.toml";
+pub const ENTITIES_FILE: &str = "entities.toml";
+pub const INPUT_FILE: &str = "input.toml";
 
-    match toml::from_str::<EntityTemplate>(&contents) {
-        Ok(config) => {
-            info!("Loaded entity template from {path}.");
-            Some(config)
-        }
+const ENTITY_TEMPLATE_FALLBACK: &str = "Skipping entity template.";
+const COMBO_TEMPLATE_FALLBACK: &str = "Skipping combo template.";
+
+/// Where the loaders get a file's text from, given its path: the file system, or files the
+/// asset loaders have already read through their load context.
+pub type ReadFile<'a> = dyn FnMut(&str) -> io::Result<String> + 'a;
+
+/// What reading a scene's files needs besides their paths.
+struct SceneFiles<'a> {
+    scene: &'a str,
+    vars: &'a toml::Table,
+    read: &'a mut ReadFile<'a>,
+    diagnostics: &'a DiagnosticCollector,
+}
+
+fn read_file(path: &str) -> io::Result<String> {
+    fs::read_to_string(path)
+}
+
+/// Reads, parses and deserializes a config file, migrating it to the current format,
+/// substituting `vars` and running the shared value checks plus `checks` on the parsed
+/// table. The file comes from `read`. Every problem goes to `diagnostics`; `None` means
+/// the caller should apply `fallback`, which is appended to the error message.
+fn read_config<T: DeserializeOwned>(
+    path: &str,
+    vars: &toml::Table,
+    fallback: &str,
+    checks: impl FnOnce(&SourceFile, &toml::Table),
+    read: &mut ReadFile,
+    diagnostics: &DiagnosticCollector,
+) -> Option<T> {
+    let contents = read_or_report(path, fallback, read, diagnostics)?;
+    let expand = |_: &SourceFile, table| Some(table);
+    parse_config(path, &contents, vars, fallback, expand, checks, diagnostics)
+}
+
+fn read_or_report(
+    path: &str,
+    fallback: &str,
+    read: &mut ReadFile,
+    diagnostics: &DiagnosticCollector,
+) -> Option<String> {
+    match read(path) {
+        Ok(text) => Some(text),
         Err(err) => {
-            warn!("Failed to parse {path}: {err}. Skipping entity template.");
+            let message = format!("Failed to read: {err}. {fallback}");
+            diagnostics.report(ConfigDiagnostic::error(path, message));
             None
         }
     }
 }
 
-pub fn load_combo_template_from_path(scene: &str, template_path: &str) -> Option<ComboTemplate> {
-    let path = action_config_path(scene, template_path);
-    let contents = match fs::read_to_string(&path) {
-        Ok(text) => text,
+/// The part of `read_config` after reading, with `expand` rewriting the checked table
+/// before it is deserialized. Checks only see this file's values, so they point at its
+/// own lines.
+fn parse_config<T: DeserializeOwned>(
+    path: &str,
+    contents: &str,
+    vars: &toml::Table,
+    fallback: &str,
+    expand: impl FnOnce(&SourceFile, toml::Table) -> Option<toml::Table>,
+    checks: impl FnOnce(&SourceFile, &toml::Table),
+    diagnostics: &DiagnosticCollector,
+) -> Option<T> {
+    let source = SourceFile::new(path, contents, diagnostics);
+
+    let table = match SceneFormat::of(path).parse(contents) {
+        Ok(table) => table,
         Err(err) => {
-            warn!("Failed to read {path}: {err}. Skipping combo template.");
+            source.error_at(err.offset, format!("Failed to parse: {}. {fallback}", err.message));
             return None;
         }
     };
 
-    match toml::from_str::<ComboTemplate>(&contents) {
-        Ok(config) => {
-            info!("Loaded combo template from {path}.");
-            Some(config)
-        }
+    let table = match migrate_table(&source, contents, table) {
+        Ok(table) => table,
         Err(err) => {
-            warn!("Failed to parse {path}: {err}. Skipping combo template.");
-            None
+            source.error(FORMAT_VERSION_KEY, format!("{err} {fallback}"));
+            return None;
         }
-    }
+    };
+    let table = substitute_vars(&source, vars, table)?;
+    check_values(&source, &table);
+    checks(&source, &table);
+    let table = expand(&source, table)?;
+    source.deserialize(table, fallback)
 }
 
-pub fn load_shoot_action_config(scene: &str, action_path: &str) -> Option<ShootActionConfig> {
-    let path = action_config_path(scene, action_path);
-    let contents = match fs::read_to_string(&path) {
-        Ok(text) => text,
-        Err(err) => {
-            warn!("Failed to read {path}: {err}. Action disabled.");
-            return None;
-        }
+pub fn load_entity_template_from_path(
+    scene: &str,
+    template_path: &str,
+    diagnostics: &DiagnosticCollector,
+) -> Option<EntityTemplate> {
+    let path = action_config_path(scene, template_path);
+    let contents = read_or_report(&path, ENTITY_TEMPLATE_FALLBACK, &mut read_file, diagnostics)?;
+    let vars = scene_vars(scene);
+    parse_entity_template(scene, &path, &contents, &vars, &mut read_file, diagnostics)
+}
+
+pub fn load_combo_template_from_path(
+    scene: &str,
+    template_path: &str,
+    diagnostics: &DiagnosticCollector,
+) -> Option<ComboTemplate> {
+    let path = action_config_path(scene, template_path);
+    let contents = read_or_report(&path, COMBO_TEMPLATE_FALLBACK, &mut read_file, diagnostics)?;
+    parse_combo_template(scene, &path, &contents, &scene_vars(scene), diagnostics)
+}
+
+/// Parses an entity template read from `path` with the scene's `vars`, resolving its
+/// `extends` chain through `read_parent`. Shared by the synchronous loader and the
+/// template asset loader.
+pub fn parse_entity_template(
+    scene: &str,
+    path: &str,
+    contents: &str,
+    vars: &toml::Table,
+    read_parent: &mut ReadFile,
+    diagnostics: &DiagnosticCollector,
+) -> Option<EntityTemplate> {
+    let expand =
+        |source: &SourceFile, table| resolve_extends(source, scene, vars, table, read_parent);
+    let fallback = ENTITY_TEMPLATE_FALLBACK;
+    let config = parse_config(path, contents, vars, fallback, expand, |_, _| {}, diagnostics)?;
+    info!("Loaded entity template from {path}.");
+    Some(config)
+}
+
+pub fn parse_combo_template(
+    scene: &str,
+    path: &str,
+    contents: &str,
+    vars: &toml::Table,
+    diagnostics: &DiagnosticCollector,
+) -> Option<ComboTemplate> {
+    let checks = |source: &SourceFile, table: &toml::Table| {
+        check_template_refs(source, table, scene, "parts")
     };
+    let expand = |_: &SourceFile, table| Some(table);
+    let fallback = COMBO_TEMPLATE_FALLBACK;
+    let config = parse_config(path, contents, vars, fallback, expand, checks, diagnostics)?;
+    info!("Loaded combo template from {path}.");
+    Some(config)
+}
 
-    match toml::from_str::<ShootActionConfig>(&contents) {
-        Ok(config) => {
-            info!("Loaded shoot action config from {path}.");
-            Some(config)
-        }
-        Err(err) => {
-            warn!("Failed to parse {path}: {err}. Action disabled.");
-            None
-        }
-    }
+pub fn load_shoot_action_config(
+    scene: &str,
+    action_path: &str,
+    diagnostics: &DiagnosticCollector,
+) -> Option<ShootActionConfig> {
+    let path = action_config_path(scene, action_path);
+    let vars = scene_vars(scene);
+    let fallback = "Action disabled.";
+    let config = read_config(&path, &vars, fallback, |_, _| {}, &mut read_file, diagnostics)?;
+    info!("Loaded shoot action config from {path}.");
+    Some(config)
 }
 
-pub fn load_sprint_action_config(scene: &str, action_path: &str) -> Option<SprintActionConfig> {
+pub fn load_sprint_action_config(
+    scene: &str,
+    action_path: &str,
+    diagnostics: &DiagnosticCollector,
+) -> Option<SprintActionConfig> {
     let path = action_config_path(scene, action_path);
-    let contents = match fs::read_to_string(&path) {
-        Ok(text) => text,
-        Err(err) => {
-            warn!("Failed to read {path}: {err}. Action disabled.");
-            return None;
-        }
+    let vars = scene_vars(scene);
+    let fallback = "Action disabled.";
+    let config = read_config(&path, &vars, fallback, |_, _| {}, &mut read_file, diagnostics)?;
+    info!("Loaded sprint action config from {path}.");
+    Some(config)
+}
+
+pub fn load_zoom_action_config(
+    scene: &str,
+    action_path: &str,
+    diagnostics: &DiagnosticCollector,
+) -> Option<ZoomActionConfig> {
+    let path = action_config_path(scene, action_path);
+    let vars = scene_vars(scene);
+    let fallback = "Action disabled.";
+    let config = read_config(&path, &vars, fallback, |_, _| {}, &mut read_file, diagnostics)?;
+    info!("Loaded zoom action config from {path}.");
+    Some(config)
+}
+
+pub fn load_input_config(scene: &str, diagnostics: &DiagnosticCollector) -> InputConfig {
+    read_input_config(scene, &scene_vars(scene), &mut read_file, diagnostics).unwrap_or_default()
+}
+
+/// `load_input_config` reading through `read`, without the fallback. Used by the input
+/// asset loader.
+pub fn read_input_config(
+    scene: &str,
+    vars: &toml::Table,
+    read: &mut ReadFile,
+    diagnostics: &DiagnosticCollector,
+) -> Option<InputConfig> {
+    let path = input_config_path(scene);
+    let fallback = "Falling back to defaults.";
+    let config = read_config(&path, vars, fallback, check_input_bindings, read, diagnostics)?;
+    info!("Loaded input config from {path}.");
+    Some(config)
+}
+
+pub fn load_world_config(scene: &str, diagnostics: &DiagnosticCollector) -> WorldConfig {
+    read_world_config(scene, &scene_vars(scene), &mut read_file, diagnostics).unwrap_or_default()
+}
+
+/// `load_world_config` reading the world file and its includes through `read`, without
+/// the fallback. Used by the world asset loader.
+pub fn read_world_config(
+    scene: &str,
+    vars: &toml::Table,
+    read: &mut ReadFile,
+    diagnostics: &DiagnosticCollector,
+) -> Option<WorldConfig> {
+    let file = scene_file(scene, WORLD_FILE);
+    let path = action_config_path(scene, &file);
+    let checks = |source: &SourceFile, table: &toml::Table| check_includes(source, table, scene);
+    let fallback = "Falling back to empty world.";
+    let mut config =
+        read_config::<WorldConfig>(&path, vars, fallback, checks, &mut *read, diagnostics)?;
+    info!("Loaded world config from {path}.");
+    let includes = mem::take(&mut config.include);
+    let mut files = SceneFiles {
+        scene,
+        vars,
+        read,
+        diagnostics,
     };
+    config.lights.extend(load_includes(
+        &mut files,
+        &includes,
+        &mut vec![file],
+        |_, included: LightsConfig, _| (included.lights, included.include),
+        place_light,
+    ));
+    Some(config)
+}
 
-    match toml::from_str::<SprintActionConfig>(&contents) {
-        Ok(config) => {
-            info!("Loaded sprint action config from {path}.");
-            Some(config)
-        }
-        Err(err) => {
-            warn!("Failed to parse {path}: {err}. Action disabled.");
-            None
-        }
-    }
+pub fn load_entities_config(scene: &str, diagnostics: &DiagnosticCollector) -> EntitiesConfig {
+    try_load_entities_config(scene, diagnostics).unwrap_or_default()
 }
 
-pub fn load_zoom_action_config(scene: &str, action_path: &str) -> Option<ZoomActionConfig> {
-    let path = action_config_path(scene, action_path);
-    let contents = match fs::read_to_string(&path) {
-        Ok(text) => text,
-        Err(err) => {
-            warn!("Failed to read {path}: {err}. Action disabled.");
-            return None;
-        }
+/// `load_entities_config` without the fallback, for callers that keep what they have
+/// when the file is broken.
+pub fn try_load_entities_config(
+    scene: &str,
+    diagnostics: &DiagnosticCollector,
+) -> Option<EntitiesConfig> {
+    read_entities_config(scene, &scene_vars(scene), &mut read_file, diagnostics)
+}
+
+/// `try_load_entities_config` reading the entities file and its includes through `read`.
+/// Used by the entities asset loader.
+pub fn read_entities_config(
+    scene: &str,
+    vars: &toml::Table,
+    read: &mut ReadFile,
+    diagnostics: &DiagnosticCollector,
+) -> Option<EntitiesConfig> {
+    let file = scene_file(scene, ENTITIES_FILE);
+    let path = action_config_path(scene, &file);
+    let checks =
+        |source: &SourceFile, table: &toml::Table| check_placement_file(source, table, scene);
+    let fallback = "Falling back to empty entities.";
+    let mut config =
+        read_config::<EntitiesConfig>(&path, vars, fallback, checks, &mut *read, diagnostics)?;
+    info!("Loaded entities config from {path}.");
+    expand_layouts(&path, &mut config, diagnostics);
+    check_parents(&path, &mut config.entities, diagnostics);
+    let includes = mem::take(&mut config.include);
+    let mut files = SceneFiles {
+        scene,
+        vars,
+        read,
+        diagnostics,
     };
+    config.entities.extend(load_includes(
+        &mut files,
+        &includes,
+        &mut vec![file],
+        split_entities,
+        place_entity,
+    ));
+    link_parents(&mut config.entities);
+    Some(config)
+}
 
-    match toml::from_str::<ZoomActionConfig>(&contents) {
-        Ok(config) => {
-            info!("Loaded zoom action config from {path}.");
-            Some(config)
-        }
-        Err(err) => {
-            warn!("Failed to parse {path}: {err}. Action disabled.");
-            None
-        }
+/// Checks shared by `entities.toml` and the files it includes.
+fn check_placement_file(source: &SourceFile, table: &toml::Table, scene: &str) {
+    for list in ["entities", "array", "scatter"] {
+        check_template_refs(source, table, scene, list);
     }
+    check_includes(source, table, scene);
 }
 
-pub fn load_input_config(scene: &str) -> InputConfig {
-    let path = input_config_path(scene);
-    let contents = match fs::read_to_string(&path) {
-        Ok(text) => text,
-        Err(err) => {
-            warn!("Failed to read {path}: {err}. Falling back to defaults.");
-            return InputConfig::default();
-        }
-    };
+fn split_entities(
+    path: &str,
+    mut included: EntitiesConfig,
+    diagnostics: &DiagnosticCollector,
+) -> (Vec<EntityPlacement>, Vec<IncludeConfig>) {
+    expand_layouts(path, &mut included, diagnostics);
+    check_parents(path, &mut included.entities, diagnostics);
+    (included.entities, included.include)
+}
 
-    match toml::from_str::<InputConfig>(&contents) {
-        Ok(config) => {
-            info!("Loaded input config from {path}.");
-            config
-        }
-        Err(err) => {
-            warn!("Failed to parse {path}: {err}. Falling back to defaults.");
-            InputConfig::default()
+/// Splits an included file at `path` into its items and the files it includes in turn.
+type SplitInclude<C, T> = fn(&str, C, &DiagnosticCollector) -> (Vec<T>, Vec<IncludeConfig>);
+
+/// Loads the files `includes` match, each followed by its own includes, and returns their
+/// items with the include that brought them in applied by `place`. `chain` holds the
+/// scene-relative files being included from, so a file including itself is reported
+/// instead of recursing.
+fn load_includes<C: DeserializeOwned, T>(
+    files: &mut SceneFiles,
+    includes: &[IncludeConfig],
+    chain: &mut Vec<String>,
+    split: SplitInclude<C, T>,
+    place: fn(&mut T, &IncludeConfig),
+) -> Vec<T> {
+    let (scene, vars, diagnostics) = (files.scene, files.vars, files.diagnostics);
+    let mut items = Vec::new();
+    for include in includes {
+        // Bad patterns and empty matches were flagged by `check_includes`.
+        for file in include_paths(scene, include.path()).unwrap_or_default() {
+            let path = action_config_path(scene, &file);
+            if chain.contains(&file) {
+                let cycle = format!("{} -> {file}", chain.join(" -> "));
+                let including = action_config_path(scene, chain.last().map_or("", String::as_str));
+                diagnostics.report(ConfigDiagnostic::error(
+                    including,
+                    format!("Include cycle: {cycle}. Skipping {file}."),
+                ));
+                continue;
+            }
+
+            let checks = |source: &SourceFile, table: &toml::Table| {
+                check_placement_file(source, table, scene)
+            };
+            let fallback = "Skipping include.";
+            let read = &mut *files.read;
+            let Some(included) = read_config::<C>(&path, vars, fallback, checks, read, diagnostics)
+            else {
+                continue;
+            };
+            info!("Loaded include {path}.");
+            let (mut file_items, nested) = split(&path, included, diagnostics);
+            chain.push(file);
+            file_items.extend(load_includes(files, &nested, chain, split, place));
+            chain.pop();
+
+            for item in &mut file_items {
+                place(item, include);
+            }
+            items.extend(file_items);
         }
     }
+    items
 }
 
-pub fn load_world_config(scene: &str) -> WorldConfig {
-    let path = format!("{root}/{scene}/world.toml", root = crate::scenes::config::SCENE_ROOT);
-    let contents = match fs::read_to_string(&path) {
-        Ok(text) => text,
-        Err(err) => {
-            warn!("Failed to read {path}: {err}. Falling back to empty world.");
-            return WorldConfig::default();
-        }
-    };
+fn place_entity(placement: &mut EntityPlacement, include: &IncludeConfig) {
+    let offset = include.offset();
+    placement.offset.x += offset.x;
+    placement.offset.y += offset.y;
+    placement.offset.z += offset.z;
+    placement.name_prefix.insert_str(0, include.prefix());
+}
+
+/// Lights have no names, so only the offset applies; `look_at` moves along with the
+/// light to keep its direction.
+fn place_light(light: &mut LightEntry, include: &IncludeConfig) {
+    let offset = include.offset();
+    for position in [Some(&mut light.position), light.look_at.as_mut()]
+        .into_iter()
+        .flatten()
+    {
+        position.x += offset.x;
+        position.y += offset.y;
+        position.z += offset.z;
+    }
+}
 
-    match toml::from_str::<WorldConfig>(&contents) {
-        Ok(config) => {
-            info!("Loaded world config from {path}.");
+pub fn load_overlay_config(name: &str, diagnostics: &DiagnosticCollector) -> OverlayConfig {
+    let path = overlay_config_path(name);
+    // Overlays are shared between scenes, so only their own `[vars]` apply.
+    let vars = toml::Table::new();
+    let fallback = "Falling back to empty overlay.";
+    match read_config(&path, &vars, fallback, |_, _| {}, &mut read_file, diagnostics) {
+        Some(config) => {
+            info!("Loaded overlay config from {path}.");
             config
         }
-        Err(err) => {
-            warn!("Failed to parse {path}: {err}. Falling back to empty world.");
-            WorldConfig::default()
-        }
+        None => OverlayConfig::default(),
     }
 }
 
-pub fn load_entities_config(scene: &str) -> EntitiesConfig {
-    let path = format!("{root}/{scene}/entities.toml", root = crate::scenes::config::SCENE_ROOT);
+pub fn load_scene_table(scene: &str, relative_path: &str) -> Option<toml::Table> {
+    let path = action_config_path(scene, &scene_file(scene, relative_path));
     let contents = match fs::read_to_string(&path) {
         Ok(text) => text,
         Err(err) => {
-            warn!("Failed to read {path}: {err}. Falling back to empty entities.");
-            return EntitiesConfig::default();
+            warn!("Failed to read {path}: {err}.");
+            return None;
         }
     };
 
-    match toml::from_str::<EntitiesConfig>(&contents) {
-        Ok(config) => {
-            info!("Loaded entities config from {path}.");
-            config
-        }
+    match SceneFormat::of(&path).parse(&contents) {
+        Ok(table) => Some(table),
         Err(err) => {
-            warn!("Failed to parse {path}: {err}. Falling back to empty entities.");
-            EntitiesConfig::default()
+            warn!("Failed to parse {path}: {}.", err.message);
+            None
         }
     }
 }
 
-pub fn load_overlay_config(name: &str) -> OverlayConfig {
-    let path = overlay_config_path(name);
-    let contents = match fs::read_to_string(&path) {
-        Ok(text) => text,
-        Err(err) => {
-            warn!("Failed to read {path}: {err}. Falling back to empty overlay.");
-            return OverlayConfig::default();
-        }
-    };
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scenes::config::PositionConfig;
 
-    match toml::from_str::<OverlayConfig>(&contents) {
-        Ok(config) => {
-            info!("Loaded overlay config from {path}.");
-            config
-        }
-        Err(err) => {
-            warn!("Failed to parse {path}: {err}. Falling back to empty overlay.");
-            OverlayConfig::default()
-        }
+    fn include(toml_text: &str) -> IncludeConfig {
+        toml::from_str::<toml::Table>(toml_text).unwrap()["include"].clone().try_into().unwrap()
+    }
+
+    #[test]
+    fn nested_includes_accumulate_offsets_and_prefixes() {
+        let mut placement: EntityPlacement = toml::from_str("template = \"crate\"").unwrap();
+        let inner =
+            include("include = { path = \"a.toml\", offset = { x = 1.0 }, prefix = \"a_\" }");
+        let outer = include(
+            "include = { path = \"b.toml\", offset = { x = 2.0, z = -3.0 }, prefix = \"b_\" }",
+        );
+        place_entity(&mut placement, &inner);
+        place_entity(&mut placement, &outer);
+        assert_eq!(placement.offset, PositionConfig { x: 3.0, y: 0.0, z: -3.0 });
+        assert_eq!(placement.name_prefix, "b_a_");
+
+        place_entity(&mut placement, &include("include = \"c.toml\""));
+        assert_eq!(placement.offset.x, 3.0);
+        assert_eq!(placement.name_prefix, "b_a_");
     }
 }
@@ -0,0 +1,67 @@
+use std::{fs, path::Path};
+
+use bevy::{log::warn, prelude::*};
+
+use crate::scenes::config::SCENE_ROOT;
+
+/// Marks every entity a scene creates so it can be torn down when switching scenes.
+#[derive(Component, Default)]
+pub struct SceneEntity;
+
+/// Requests runtime scene switches. The switch happens at the next `Update`, which
+/// unloads everything the current scene created before running the full setup again.
+#[derive(Resource, Debug, Default)]
+pub struct SceneManager {
+    pending: Option<String>,
+}
+
+impl SceneManager {
+    pub fn switch_to(&mut self, scene: impl Into<String>) {
+        let scene = scene.into();
+        if !Path::new(SCENE_ROOT).join(&scene).is_dir() {
+            warn!("Scene '{scene}' not found under {SCENE_ROOT}; ignoring switch request.");
+            return;
+        }
+        self.pending = Some(scene);
+    }
+
+    pub(crate) fn take_pending(&mut self) -> Option<String> {
+        self.pending.take()
+    }
+
+    /// Scene directories under `SCENE_ROOT`, sorted by name.
+    pub fn available_scenes() -> Vec<String> {
+        let Ok(entries) = fs::read_dir(SCENE_ROOT) else {
+            return Vec::new();
+        };
+        let mut scenes: Vec<String> = entries
+            .flatten()
+            .filter(|entry| entry.path().is_dir())
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .collect();
+        scenes.sort();
+        scenes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn switches_to_existing_scenes_are_taken_once() {
+        let mut manager = SceneManager::default();
+        manager.switch_to("main");
+        assert_eq!(manager.take_pending().as_deref(), Some("main"));
+        assert_eq!(manager.take_pending(), None);
+    }
+
+    #[test]
+    fn switches_to_missing_scenes_are_ignored() {
+        let mut manager = SceneManager::default();
+        manager.switch_to("main");
+        manager.switch_to("no-such-scene");
+        assert_eq!(manager.take_pending().as_deref(), Some("main"));
+        assert!(SceneManager::available_scenes().contains(&"main".to_string()));
+    }
+}
@@ -0,0 +1,322 @@
+use std::path::{Path, PathBuf};
+
+use toml_edit::{DocumentMut, Item, TableLike, Value};
+
+use crate::scenes::config::{OVERLAY_ROOT, SCENE_ROOT};
+use crate::scenes::diagnostics::SourceFile;
+use crate::scenes::format::SceneFormat;
+use crate::scenes::validate::collect_scene_files;
+
+/// Key every scene file carries with the format it was written for.
+pub const FORMAT_VERSION_KEY: &str = "format_version";
+
+/// Format this build reads and writes. Files without `format_version` predate versioning
+/// and are read as version 1.
+pub const CURRENT_FORMAT_VERSION: i64 = 2;
+
+const UNVERSIONED: i64 = 1;
+
+/// One step of the upgrade path: rewrites a document written for format `to - 1` into
+/// format `to` and describes each change it made.
+struct Migration {
+    to: i64,
+    apply: fn(&mut toml_edit::Table) -> Vec<String>,
+}
+
+/// Every format change, oldest first. Steps run before variable substitution, so any value
+/// may still be a `${...}` string and must be left alone when it is.
+const MIGRATIONS: &[Migration] = &[Migration {
+    to: 2,
+    apply: name_rotation_axes,
+}];
+
+/// Version 1 rotations named their turns about X, Y and Z roll, pitch and yaw, made in that
+/// order. Version 2 names them by axis and defaults to another order, so the old one is
+/// written out.
+fn name_rotation_axes(document: &mut toml_edit::Table) -> Vec<String> {
+    let mut changes = Vec::new();
+    rename_rotations(document, "", &mut changes);
+    changes
+}
+
+fn rename_rotations(table: &mut dyn TableLike, path: &str, changes: &mut Vec<String>) {
+    for (key, item) in table.iter_mut() {
+        let field = if path.is_empty() {
+            key.get().to_string()
+        } else {
+            format!("{path}.{}", key.get())
+        };
+        if key.get() == "rotation" {
+            if let Some(rotation) = item.as_table_like_mut() {
+                if is_roll_pitch_yaw(rotation) {
+                    rename_roll_pitch_yaw(rotation);
+                    changes.push(format!(
+                        "Renamed roll, pitch and yaw in {field} to x, y and z, with \
+                         order = \"xyz\"."
+                    ));
+                    continue;
+                }
+            }
+        }
+        match item {
+            Item::Table(child) => rename_rotations(child, &field, changes),
+            Item::ArrayOfTables(tables) => {
+                for (index, child) in tables.iter_mut().enumerate() {
+                    rename_rotations(child, &format!("{field}[{index}]"), changes);
+                }
+            }
+            Item::Value(Value::InlineTable(child)) => rename_rotations(child, &field, changes),
+            Item::Value(Value::Array(values)) => {
+                for (index, value) in values.iter_mut().enumerate() {
+                    if let Value::InlineTable(child) = value {
+                        rename_rotations(child, &format!("{field}[{index}]"), changes);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Other `rotation` tables, such as the camera's key bindings, have other keys.
+fn is_roll_pitch_yaw(rotation: &dyn TableLike) -> bool {
+    !rotation.is_empty()
+        && rotation
+            .iter()
+            .all(|(key, _)| matches!(key, "roll" | "pitch" | "yaw"))
+}
+
+fn rename_roll_pitch_yaw(rotation: &mut dyn TableLike) {
+    let entries: Vec<(&str, Item)> = rotation
+        .iter()
+        .map(|(key, item)| {
+            let axis = match key {
+                "roll" => "x",
+                "pitch" => "y",
+                _ => "z",
+            };
+            (axis, item.clone())
+        })
+        .collect();
+    rotation.clear();
+    for (axis, item) in entries {
+        rotation.insert(axis, item);
+    }
+    rotation.insert("order", toml_edit::value("xyz"));
+    rotation.fmt();
+}
+
+/// A file's text brought up to `CURRENT_FORMAT_VERSION`.
+pub struct Migrated {
+    /// The `format_version` the file was written for.
+    pub from: i64,
+    pub text: String,
+    /// What was changed, one line each; empty when the file was already current.
+    pub changes: Vec<String>,
+}
+
+/// Upgrades a config file's text step by step and stamps it with the current
+/// `format_version`. TOML files keep their comments and layout.
+pub fn migrate_text(format: SceneFormat, text: &str) -> Result<Migrated, String> {
+    if format != SceneFormat::Toml {
+        return migrate_converted(format, text);
+    }
+    let mut document = text
+        .parse::<DocumentMut>()
+        .map_err(|err| format!("Failed to parse: {}.", err.message().trim_end_matches('.')))?;
+    let from = format_version(document.get(FORMAT_VERSION_KEY).map(toml_edit::Item::as_integer))?;
+
+    let mut changes = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|migration| migration.to > from) {
+        let applied = (migration.apply)(document.as_table_mut());
+        changes.extend(
+            applied
+                .into_iter()
+                .map(|change| format!("format_version {}: {change}", migration.to)),
+        );
+    }
+
+    let text = match document.get_mut(FORMAT_VERSION_KEY) {
+        Some(version) => {
+            if from != CURRENT_FORMAT_VERSION {
+                *version = toml_edit::value(CURRENT_FORMAT_VERSION);
+                changes.push(format!(
+                    "Set {FORMAT_VERSION_KEY} from {from} to {CURRENT_FORMAT_VERSION}."
+                ));
+            }
+            document.to_string()
+        }
+        None => {
+            changes.push(format!("Added {FORMAT_VERSION_KEY} = {CURRENT_FORMAT_VERSION}."));
+            insert_version(&document.to_string())
+        }
+    };
+    Ok(Migrated {
+        from,
+        text,
+        changes,
+    })
+}
+
+/// JSON and RON files run the steps on a TOML copy and are written back in their own
+/// format, so they lose their layout once a migration changes them.
+fn migrate_converted(format: SceneFormat, text: &str) -> Result<Migrated, String> {
+    let table = format
+        .parse(text)
+        .map_err(|err| format!("Failed to parse: {}.", err.message))?;
+    let toml_text = toml::to_string(&table).map_err(|err| err.to_string())?;
+    let migrated = migrate_text(SceneFormat::Toml, &toml_text)?;
+    let table: toml::Table = toml::from_str(&migrated.text).map_err(|err| err.to_string())?;
+    Ok(Migrated {
+        text: format.write(&table, "")?,
+        ..migrated
+    })
+}
+
+/// Brings a parsed config file up to the current format, warning when that took a
+/// migration, and strips its `format_version` so it does not reach deserialization. `Err`
+/// means the file cannot be read by this build.
+pub fn migrate_table(
+    source: &SourceFile,
+    contents: &str,
+    mut table: toml::Table,
+) -> Result<toml::Table, String> {
+    let from = format_version(table.get(FORMAT_VERSION_KEY).map(toml::Value::as_integer))?;
+    if from < CURRENT_FORMAT_VERSION {
+        let format = SceneFormat::of(source.path());
+        let migrated = migrate_text(format, contents)?;
+        table = format.parse(&migrated.text).map_err(|err| {
+            format!("Migration produced an unreadable file: {}.", err.message)
+        })?;
+        source.warning(
+            FORMAT_VERSION_KEY,
+            format!(
+                "Written for format_version {from}; migrated to {CURRENT_FORMAT_VERSION} in \
+                 memory. Run `cargo run --bin atmos-migrate` to update the file."
+            ),
+        );
+    }
+    table.remove(FORMAT_VERSION_KEY);
+    Ok(table)
+}
+
+/// Every scene file of `scene`, in any format, sorted.
+pub fn scene_files(scene: &str) -> Vec<PathBuf> {
+    sorted_toml_files(&Path::new(SCENE_ROOT).join(scene))
+}
+
+/// The shared overlay files, which every scene reads.
+pub fn overlay_files() -> Vec<PathBuf> {
+    sorted_toml_files(Path::new(OVERLAY_ROOT))
+}
+
+fn sorted_toml_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    collect_scene_files(dir, &mut files);
+    files.sort();
+    files
+}
+
+/// `None` is a missing key; `Some(None)` a value that is not an integer.
+fn format_version(value: Option<Option<i64>>) -> Result<i64, String> {
+    match value {
+        None => Ok(UNVERSIONED),
+        Some(None) => Err(format!("{FORMAT_VERSION_KEY} must be an integer.")),
+        Some(Some(version)) if version < UNVERSIONED => {
+            Err(format!("Unknown {FORMAT_VERSION_KEY} {version}."))
+        }
+        Some(Some(version)) if version > CURRENT_FORMAT_VERSION => Err(format!(
+            "{FORMAT_VERSION_KEY} {version} is newer than this build reads \
+             ({CURRENT_FORMAT_VERSION})."
+        )),
+        Some(Some(version)) => Ok(version),
+    }
+}
+
+/// Adds the version key below the file's opening comment block, which describes the whole
+/// file when a blank line separates it from what follows, and above everything else.
+fn insert_version(text: &str) -> String {
+    let mut comment_end = 0;
+    let mut header_end = 0;
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if trimmed.starts_with('#') {
+            comment_end += line.len();
+        } else {
+            if trimmed.is_empty() && comment_end > 0 {
+                header_end = comment_end;
+            }
+            break;
+        }
+    }
+    let (header, body) = text.split_at(header_end);
+    let body = body.trim_start();
+    let header = if header.is_empty() { String::new() } else { format!("{header}\n") };
+    let line = format!("{FORMAT_VERSION_KEY} = {CURRENT_FORMAT_VERSION}\n");
+    if body.is_empty() {
+        format!("{header}{line}")
+    } else {
+        format!("{header}{line}\n{body}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unversioned_files_get_axis_named_rotations_and_a_version() {
+        let text = "# Crates by the door.\n\n\
+            [[entities]]\n\
+            template = \"crate\" # the big one\n\
+            transform = { rotation = { yaw = 90.0, roll = \"${tilt}\" } }\n\n\
+            [camera.rotation]\n\
+            yaw = \"mouse_x\"\n\
+            speed = 1.0\n";
+        let migrated = migrate_text(SceneFormat::Toml, text).unwrap();
+        assert_eq!(migrated.from, 1);
+        assert_eq!(
+            migrated.text,
+            "# Crates by the door.\n\n\
+             format_version = 2\n\n\
+             [[entities]]\n\
+             template = \"crate\" # the big one\n\
+             transform = { rotation = { z = 90.0, x = \"${tilt}\", order = \"xyz\" } }\n\n\
+             [camera.rotation]\n\
+             yaw = \"mouse_x\"\n\
+             speed = 1.0\n"
+        );
+        assert_eq!(
+            migrated.changes,
+            [
+                "format_version 2: Renamed roll, pitch and yaw in \
+                 entities[0].transform.rotation to x, y and z, with order = \"xyz\".",
+                "Added format_version = 2.",
+            ]
+        );
+    }
+
+    #[test]
+    fn current_files_are_left_alone_and_newer_ones_refused() {
+        let text = "format_version = 2\nrotation = { pitch = 10.0 }\n";
+        let migrated = migrate_text(SceneFormat::Toml, text).unwrap();
+        assert_eq!(migrated.text, text);
+        assert!(migrated.changes.is_empty());
+
+        let err = migrate_text(SceneFormat::Toml, "format_version = 3\n").err().unwrap();
+        assert_eq!(err, "format_version 3 is newer than this build reads (2).");
+    }
+
+    #[test]
+    fn json_files_are_migrated_through_toml() {
+        let text = r#"{ "rotation": { "pitch": 45.0 } }"#;
+        let migrated = migrate_text(SceneFormat::Json, text).unwrap();
+        let table = SceneFormat::Json.parse(&migrated.text).unwrap_or_else(|err| {
+            panic!("{}", err.message)
+        });
+        let expected: toml::Table =
+            toml::from_str("format_version = 2\nrotation = { y = 45.0, order = \"xyz\" }")
+                .unwrap();
+        assert_eq!(table, expected);
+    }
+}
@@ -1,9 +1,34 @@
 mod config;
 mod bounds;
+mod diagnostics;
 mod input;
 mod loaders;
+mod manager;
+mod migrate;
 mod spawn;
+mod templates;
 mod entities;
+mod expand;
+mod format;
+mod validate;
 mod world;
 
-pub use spawn::ScenePlugin;
+pub use bounds::SceneBounds;
+pub use config::{
+    ComboTemplate, EntityTemplate, InputConfig, OverlayConfig, TextAlign, SCENE_ROOT,
+};
+pub use diagnostics::{format_report, ConfigDiagnostic, ConfigDiagnostics, Severity};
+pub use entities::EntitiesConfig;
+pub use format::{SceneFormat, SCENE_EXTENSIONS};
+pub use input::{resolve_key_or_warn, SceneCamera, SceneShootConfig};
+pub use manager::{SceneEntity, SceneManager};
+pub use migrate::{
+    migrate_text, overlay_files, scene_files, Migrated, CURRENT_FORMAT_VERSION,
+    FORMAT_VERSION_KEY,
+};
+pub use spawn::{
+    headless_app, load_headless_scene, simulate_scene, step_headless_scene, EntityState,
+    ScenePlacement, ScenePlugin, SimulationReport, TextLabel, SIMULATION_STEP_SECONDS,
+};
+pub use validate::validate_scene;
+pub use world::WorldConfig;
@@ -8,7 +8,7 @@ use crate::scenes::config::{
     ActiveScene, ComboPart, ComboPhysics, ComboStackConfig, ComboTemplate, EntityOverrides,
     EntityTemplate, TransformOverrides, Vec3Config,
 };
-use crate::scenes::loaders::load_entity_template_from_path;
+use crate::scenes::templates::SceneTemplates;
 
 use super::entities::{
     apply_transform_additive, apply_translation, merge_light, merge_physics, merge_shape,
@@ -20,12 +20,13 @@ pub(super) fn spawn_combo_template(
     placement_transform: &TransformOverrides,
     placement_overrides: &EntityOverrides,
     name_override: Option<&String>,
+    templates: &SceneTemplates,
     commands: &mut Commands,
     meshes: &mut Assets<Mesh>,
     materials: &mut Assets<StandardMaterial>,
     asset_server: &AssetServer,
     active_scene: &ActiveScene,
-) {
+) -> Vec<Entity> {
     let combo_name = name_override
         .cloned()
         .unwrap_or_else(|| combo.name.clone());
@@ -43,9 +44,7 @@ pub(super) fn spawn_combo_template(
 
     let mut loaded_parts = Vec::new();
     for part in &combo.parts {
-        let Some(template) =
-            load_entity_template_from_path(&active_scene.name, &part.template)
-        else {
+        let Some(template) = templates.entity(&part.template).cloned() else {
             warn!(
                 "Failed to load combo part template '{}' in scene '{}'; skipping.",
                 part.template, active_scene.name
@@ -69,6 +68,7 @@ pub(super) fn spawn_combo_template(
         None
     };
 
+    let mut roots = Vec::new();
     for i in 0..stack_count {
         let instance_offset = stack_instance_offset(stack_config, i);
         let instance_suffix = if stack_count > 1 {
@@ -94,6 +94,7 @@ pub(super) fn spawn_combo_template(
                     shared_physics,
                     true,
                     &mut spawned,
+                    &mut roots,
                     commands,
                     meshes,
                     materials,
@@ -102,6 +103,7 @@ pub(super) fn spawn_combo_template(
                 );
                 if let Some(entity) = entity {
                     spawned.insert(root_part.part_name.clone(), entity);
+                    roots.push(entity);
                 }
             }
         }
@@ -121,6 +123,7 @@ pub(super) fn spawn_combo_template(
                 shared_physics,
                 false,
                 &mut spawned,
+                &mut roots,
                 commands,
                 meshes,
                 materials,
@@ -129,9 +132,21 @@ pub(super) fn spawn_combo_template(
             );
             if let Some(entity) = entity {
                 spawned.insert(part.part_name.clone(), entity);
+                if !is_attached(part, &spawned) {
+                    roots.push(entity);
+                }
             }
         }
     }
+
+    roots
+}
+
+fn is_attached(part: &LoadedPart<'_>, spawned: &HashMap<String, Entity>) -> bool {
+    part.part
+        .attach
+        .as_ref()
+        .is_some_and(|attach| spawned.contains_key(&attach.target))
 }
 
 struct LoadedPart<'a> {
@@ -167,6 +182,7 @@ fn spawn_combo_part(
     shared_physics: bool,
     is_root: bool,
     spawned: &mut HashMap<String, Entity>,
+    detached: &mut Vec<Entity>,
     commands: &mut Commands,
     meshes: &mut Assets<Mesh>,
     materials: &mut Assets<StandardMaterial>,
@@ -250,6 +266,8 @@ fn spawn_combo_part(
         );
         if entity_id.is_none() {
             entity_id = light_entity;
+        } else {
+            detached.extend(light_entity);
         }
     }
 
@@ -0,0 +1,193 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{
+    asset::RenderAssetUsages,
+    mesh::{Indices, PrimitiveTopology},
+    prelude::*,
+};
+use bevy_rapier3d::prelude::{Collider, ComputedColliderShape, VHACDParameters};
+
+use crate::scenes::config::{ShapeConfig, ShapeKind};
+
+/// Thickness of the colliders given to flat shapes, which have no volume of their own.
+const FLAT_COLLIDER_THICKNESS: f32 = 0.2;
+
+/// Mesh and collider of a shape, sized from its config.
+pub(super) struct ShapeGeometry {
+    pub mesh: Mesh,
+    /// `None` when no collider could be built for the mesh.
+    pub collider: Option<Collider>,
+    /// Set when the collider goes on a child entity with this transform rather than on
+    /// the shape itself.
+    pub collider_transform: Option<Transform>,
+}
+
+/// Sizes are those of the unscaled shape. Rapier scales colliders by the entity's scale
+/// along each axis, child colliders included, as it does the mesh.
+pub(super) fn shape_geometry(shape: &ShapeConfig) -> ShapeGeometry {
+    let dimensions = shape.dimensions.as_ref().cloned().unwrap_or_default();
+    let size = Vec3::new(dimensions.width, dimensions.height, dimensions.depth);
+    match shape.kind {
+        ShapeKind::Box => ShapeGeometry {
+            mesh: Cuboid::from_size(size).into(),
+            collider: Some(Collider::cuboid(size.x * 0.5, size.y * 0.5, size.z * 0.5)),
+            collider_transform: None,
+        },
+        ShapeKind::Sphere => {
+            let radius = shape.radius.unwrap_or(0.5);
+            ShapeGeometry {
+                mesh: Sphere::new(radius).into(),
+                collider: Some(Collider::ball(radius)),
+                collider_transform: None,
+            }
+        }
+        ShapeKind::Circle => {
+            let radius = shape.radius.unwrap_or(4.0);
+            ShapeGeometry {
+                mesh: Circle::new(radius).into(),
+                collider: Some(Collider::cylinder(FLAT_COLLIDER_THICKNESS * 0.5, radius)),
+                // Stands the cylinder on the circle's +Z normal, so the shape's scale
+                // stretches both along the same axes and they stay the same ellipse.
+                collider_transform: Some(Transform::from_rotation(Quat::from_rotation_x(
+                    FRAC_PI_2,
+                ))),
+            }
+        }
+        ShapeKind::Cylinder => {
+            let radius = shape.radius.unwrap_or(0.5);
+            let height = shape.height.unwrap_or(1.0);
+            ShapeGeometry {
+                mesh: Cylinder::new(radius, height).into(),
+                collider: Some(Collider::cylinder(height * 0.5, radius)),
+                collider_transform: None,
+            }
+        }
+        ShapeKind::Capsule => {
+            let radius = shape.radius.unwrap_or(0.5);
+            // Bevy and Rapier both size a capsule by its straight middle section, which is
+            // the height less the two caps.
+            let middle = (shape.height.unwrap_or(2.0) - 2.0 * radius).max(0.0);
+            ShapeGeometry {
+                mesh: Capsule3d::new(radius, middle).into(),
+                collider: Some(Collider::capsule_y(middle * 0.5, radius)),
+                collider_transform: None,
+            }
+        }
+        ShapeKind::Cone => {
+            let radius = shape.radius.unwrap_or(0.5);
+            let height = shape.height.unwrap_or(1.0);
+            ShapeGeometry {
+                mesh: Cone::new(radius, height).into(),
+                collider: Some(Collider::cone(height * 0.5, radius)),
+                collider_transform: None,
+            }
+        }
+        ShapeKind::Torus => {
+            let radius = shape.radius.unwrap_or(1.0);
+            let tube_radius = shape.tube_radius.unwrap_or(0.25);
+            let mesh: Mesh = Torus {
+                minor_radius: tube_radius,
+                major_radius: radius,
+            }
+            .into();
+            // Rapier has no torus, and a single convex hull would fill the hole.
+            let collider = Collider::from_bevy_mesh(
+                &mesh,
+                &ComputedColliderShape::ConvexDecomposition(VHACDParameters::default()),
+            );
+            ShapeGeometry {
+                mesh,
+                collider,
+                collider_transform: None,
+            }
+        }
+        ShapeKind::Plane => ShapeGeometry {
+            mesh: Plane3d::new(Vec3::Y, Vec2::new(size.x, size.z) * 0.5).into(),
+            collider: Some(Collider::cuboid(
+                size.x * 0.5,
+                FLAT_COLLIDER_THICKNESS * 0.5,
+                size.z * 0.5,
+            )),
+            // Keeps the top of the collider level with the visible surface.
+            collider_transform: Some(Transform::from_xyz(
+                0.0,
+                -FLAT_COLLIDER_THICKNESS * 0.5,
+                0.0,
+            )),
+        },
+        ShapeKind::Wedge => ShapeGeometry {
+            mesh: wedge_mesh(size),
+            collider: Collider::convex_hull(&wedge_corners(size)),
+            collider_transform: None,
+        },
+        ShapeKind::Sprite => ShapeGeometry {
+            mesh: Rectangle::new(size.x, size.y).into(),
+            collider: Some(Collider::cuboid(
+                size.x * 0.5,
+                size.y * 0.5,
+                FLAT_COLLIDER_THICKNESS * 0.5,
+            )),
+            collider_transform: None,
+        },
+        ShapeKind::Model => unreachable!("models are spawned from their glTF file"),
+        ShapeKind::Terrain => unreachable!("terrain is built from its heightmap"),
+        ShapeKind::Text => unreachable!("text is drawn onto its label's quad"),
+    }
+}
+
+/// Bottom corners front-left, front-right, back-right and back-left of a wedge, then its
+/// top back edge from left to right. The wedge is centered on its bounding box.
+fn wedge_corners(size: Vec3) -> [Vec3; 6] {
+    let half = size * 0.5;
+    [
+        Vec3::new(-half.x, -half.y, half.z),
+        Vec3::new(half.x, -half.y, half.z),
+        Vec3::new(half.x, -half.y, -half.z),
+        Vec3::new(-half.x, -half.y, -half.z),
+        Vec3::new(-half.x, half.y, -half.z),
+        Vec3::new(half.x, half.y, -half.z),
+    ]
+}
+
+/// Flat-shaded wedge, with every face's corners listed counter-clockwise as seen from
+/// outside.
+fn wedge_mesh(size: Vec3) -> Mesh {
+    let [front_left, front_right, back_right, back_left, top_left, top_right] = wedge_corners(size);
+    let faces: [&[Vec3]; 5] = [
+        &[back_left, back_right, front_right, front_left],
+        &[back_left, top_left, top_right, back_right],
+        &[top_left, front_left, front_right, top_right],
+        &[back_left, front_left, top_left],
+        &[back_right, top_right, front_right],
+    ];
+
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    let mut indices = Vec::new();
+    for face in faces {
+        let first = positions.len() as u32;
+        let normal = (face[1] - face[0]).cross(face[2] - face[0]).normalize();
+        let face_uvs: &[[f32; 2]] = match face.len() {
+            3 => &[[0.0, 1.0], [1.0, 1.0], [0.0, 0.0]],
+            _ => &[[0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [0.0, 0.0]],
+        };
+        for (corner, uv) in face.iter().zip(face_uvs) {
+            positions.push(corner.to_array());
+            normals.push(normal.to_array());
+            uvs.push(*uv);
+        }
+        for corner in 1..face.len() as u32 - 1 {
+            indices.extend([first, first + corner, first + corner + 1]);
+        }
+    }
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_indices(Indices::U32(indices))
+}
@@ -0,0 +1,348 @@
+use std::collections::HashMap;
+
+use bevy::{
+    camera::{visibility::RenderLayers, ClearColorConfig},
+    log::warn,
+    prelude::*,
+    render::{
+        alpha::AlphaMode,
+        render_resource::{Extent3d, TextureFormat},
+    },
+    text::TextLayoutInfo,
+};
+
+use crate::scenes::config::{ActiveScene, ShapeConfig, TextAlign};
+use crate::scenes::input::SceneCamera;
+
+use super::shape::resolve_shape_color;
+
+/// Pixels per unit of `font_size` that labels are drawn at before being scaled into the world.
+const LABEL_FONT_PIXELS: f32 = 64.0;
+
+/// Text drawn in the world by a text shape. The text is laid out by a camera of its own
+/// into an image, which is shown on a quad child of this entity sized to fit it.
+#[derive(Component, Debug, Clone)]
+pub struct TextLabel {
+    pub text: String,
+    /// World units, or pixels when `fixed_screen_size` is set.
+    pub font_size: f32,
+    pub color: Color,
+    pub align: TextAlign,
+    pub fixed_screen_size: bool,
+}
+
+/// The quad a label is shown on, with the size of its text in pixels once laid out.
+#[derive(Component, Debug, Default)]
+pub(in crate::scenes::spawn) struct LabelQuad {
+    pixels: Option<Vec2>,
+}
+
+/// The `Text2d` a label's camera draws into the quad's image. The camera only runs until
+/// the text has been drawn at its laid-out size, and again when the text changes.
+#[derive(Component)]
+pub(in crate::scenes::spawn) struct LabelText {
+    image: Handle<Image>,
+    quad: Entity,
+    camera: Entity,
+    /// Frames the camera keeps drawing before it is switched off, once the text is laid
+    /// out. `None` while waiting for a layout.
+    renders_left: Option<u8>,
+}
+
+/// Render layers handed out to labels, each label's camera drawing only its own layer.
+/// Layers of despawned labels are handed out again.
+#[derive(Resource, Default)]
+pub(in crate::scenes::spawn) struct LabelLayers {
+    in_use: HashMap<Entity, usize>,
+    free: Vec<usize>,
+    /// Highest layer handed out so far; layer 0 is the scene's own.
+    last: usize,
+}
+
+impl LabelLayers {
+    fn take(&mut self, text: Entity) -> usize {
+        let layer = self.free.pop().unwrap_or_else(|| {
+            self.last += 1;
+            self.last
+        });
+        self.in_use.insert(text, layer);
+        layer
+    }
+
+    fn release(&mut self, text: Entity) {
+        if let Some(layer) = self.in_use.remove(&text) {
+            self.free.push(layer);
+        }
+    }
+}
+
+/// Spawns the label and its quad. The camera and text that draw it are added by
+/// `spawn_label_targets`, so headless scenes keep the label without rendering it.
+pub(super) fn spawn_text_label(
+    name: &str,
+    shape: &ShapeConfig,
+    transform: Transform,
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    active_scene: &ActiveScene,
+) -> Entity {
+    let text = shape.text.clone().unwrap_or_else(|| {
+        warn!(
+            "Text '{}' in scene '{}' has no text; leaving it blank.",
+            name, active_scene.name
+        );
+        String::new()
+    });
+    let color = resolve_shape_color(shape, active_scene);
+    let align = shape.align.unwrap_or_default();
+    // The quad is a unit square stretched over the text, so alignment shifts it off the
+    // entity's position by half its width.
+    let offset = match align {
+        TextAlign::Left => 0.5,
+        TextAlign::Center => 0.0,
+        TextAlign::Right => -0.5,
+    };
+    let mesh = Mesh::from(Rectangle::new(1.0, 1.0)).translated_by(Vec3::X * offset);
+    let material = StandardMaterial {
+        base_color: Color::WHITE,
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        double_sided: true,
+        cull_mode: None,
+        ..default()
+    };
+
+    commands
+        .spawn((
+            Name::new(name.to_string()),
+            transform,
+            Visibility::default(),
+            TextLabel {
+                text,
+                font_size: shape.font_size.unwrap_or(1.0).max(f32::EPSILON),
+                color: Color::srgb_u8(color[0], color[1], color[2]),
+                align,
+                fixed_screen_size: shape.fixed_screen_size.unwrap_or(false),
+            },
+        ))
+        .with_child((
+            Mesh3d(meshes.add(mesh)),
+            MeshMaterial3d(materials.add(material)),
+            // Empty until the text is laid out.
+            Transform::from_scale(Vec3::ZERO),
+            LabelQuad::default(),
+        ))
+        .id()
+}
+
+/// Gives each new label a camera that draws its text into an image on the label's quad.
+/// Every label gets a render layer of its own, so cameras only see their own text.
+pub(in crate::scenes::spawn) fn spawn_label_targets(
+    mut commands: Commands,
+    labels: Query<(Entity, &TextLabel, &Children), Added<TextLabel>>,
+    quads: Query<&MeshMaterial3d<StandardMaterial>, With<LabelQuad>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+    mut layers: ResMut<LabelLayers>,
+) {
+    for (entity, label, children) in &labels {
+        let Some((quad, material)) = children
+            .iter()
+            .find_map(|child| quads.get(child).ok().map(|material| (child, material)))
+        else {
+            continue;
+        };
+        // Resized to the text once it is laid out.
+        let image = images.add(Image::new_target_texture(
+            1,
+            1,
+            TextureFormat::Rgba8UnormSrgb,
+        ));
+        if let Some(material) = materials.get_mut(&material.0) {
+            material.base_color_texture = Some(image.clone());
+        }
+
+        let text = commands.spawn_empty().id();
+        let layer = RenderLayers::layer(layers.take(text));
+        let justify = match label.align {
+            TextAlign::Left => Justify::Left,
+            TextAlign::Center => Justify::Center,
+            TextAlign::Right => Justify::Right,
+        };
+        let camera = commands
+            .spawn((
+                Camera2d,
+                Camera {
+                    target: image.clone().into(),
+                    // Drawn before the scene camera samples the image.
+                    order: -1,
+                    clear_color: ClearColorConfig::Custom(Color::NONE),
+                    ..default()
+                },
+                layer.clone(),
+                ChildOf(entity),
+            ))
+            .id();
+        commands.entity(text).insert((
+            Text2d::new(label.text.clone()),
+            TextFont {
+                font_size: LABEL_FONT_PIXELS,
+                ..default()
+            },
+            TextColor(label.color),
+            TextLayout::new_with_justify(justify),
+            layer,
+            LabelText {
+                image,
+                quad,
+                camera,
+                renders_left: None,
+            },
+            ChildOf(entity),
+        ));
+    }
+}
+
+/// Hands the render layers of despawned labels back for new ones.
+pub(in crate::scenes::spawn) fn release_label_layers(
+    mut removed: RemovedComponents<LabelText>,
+    mut layers: ResMut<LabelLayers>,
+) {
+    for text in removed.read() {
+        layers.release(text);
+    }
+}
+
+/// Redraws a label whose text or color changed after it was spawned.
+pub(in crate::scenes::spawn) fn update_label_text(
+    labels: Query<(&TextLabel, &Children), Changed<TextLabel>>,
+    mut texts: Query<(&LabelText, &mut Text2d, &mut TextColor)>,
+    mut cameras: Query<&mut Camera>,
+) {
+    for (label, children) in &labels {
+        for child in children.iter() {
+            let Ok((text, mut text2d, mut color)) = texts.get_mut(child) else {
+                continue;
+            };
+            if text2d.0 == label.text && color.0 == label.color {
+                continue;
+            }
+            text2d.0.clone_from(&label.text);
+            color.0 = label.color;
+            if let Ok(mut camera) = cameras.get_mut(text.camera) {
+                camera.is_active = true;
+            }
+        }
+    }
+}
+
+/// Switches a label's camera off once it has drawn the laid-out text into the image,
+/// which keeps it until the text changes.
+pub(in crate::scenes::spawn) fn park_label_cameras(
+    mut texts: Query<&mut LabelText>,
+    mut cameras: Query<&mut Camera>,
+) {
+    for mut text in &mut texts {
+        match text.renders_left {
+            Some(0) => {
+                if let Ok(mut camera) = cameras.get_mut(text.camera) {
+                    camera.is_active = false;
+                }
+                text.renders_left = None;
+            }
+            Some(renders) => text.renders_left = Some(renders - 1),
+            None => {}
+        }
+    }
+}
+
+/// Resizes a label's image and quad to its text whenever the text is laid out again, and
+/// keeps the label's camera on for the frame that draws it at that size.
+pub(in crate::scenes::spawn) fn fit_text_labels(
+    mut texts: Query<(&mut LabelText, &TextLayoutInfo), Changed<TextLayoutInfo>>,
+    mut quads: Query<&mut LabelQuad>,
+    mut images: ResMut<Assets<Image>>,
+    mut cameras: Query<&mut Camera>,
+) {
+    for (mut text, layout) in &mut texts {
+        let pixels = layout.size.ceil().max(Vec2::ONE);
+        if let Some(image) = images.get_mut(&text.image) {
+            image.resize(Extent3d {
+                width: pixels.x as u32,
+                height: pixels.y as u32,
+                ..default()
+            });
+        }
+        if let Ok(mut quad) = quads.get_mut(text.quad) {
+            quad.pixels = Some(pixels);
+        }
+        if let Ok(mut camera) = cameras.get_mut(text.camera) {
+            camera.is_active = true;
+        }
+        // The resized image is first drawn this frame; one more covers a frame of delay
+        // before the render world picks it up.
+        text.renders_left = Some(1);
+    }
+}
+
+/// Scales each label's quad to its font size, keeping labels with `fixed_screen_size` the
+/// same size on the scene camera's screen.
+pub(in crate::scenes::spawn) fn scale_text_labels(
+    cameras: Query<(&Camera, &Projection, &GlobalTransform), With<SceneCamera>>,
+    labels: Query<(&TextLabel, &GlobalTransform, &Children)>,
+    mut quads: Query<(&LabelQuad, &mut Transform)>,
+) {
+    let camera = cameras.iter().next();
+    for (label, label_transform, children) in &labels {
+        let mut units_per_pixel = label.font_size / LABEL_FONT_PIXELS;
+        if label.fixed_screen_size {
+            match camera.and_then(|camera| screen_pixel_size(camera, label_transform)) {
+                Some(pixel_size) => units_per_pixel *= pixel_size,
+                None => continue,
+            }
+        }
+        for child in children.iter() {
+            let Ok((quad, mut transform)) = quads.get_mut(child) else {
+                continue;
+            };
+            if let Some(pixels) = quad.pixels {
+                transform.scale = (pixels * units_per_pixel).extend(1.0);
+            }
+        }
+    }
+}
+
+/// World units covered by one screen pixel at the label's distance from a perspective camera.
+fn screen_pixel_size(
+    (camera, projection, camera_transform): (&Camera, &Projection, &GlobalTransform),
+    label_transform: &GlobalTransform,
+) -> Option<f32> {
+    let Projection::Perspective(perspective) = projection else {
+        return None;
+    };
+    let viewport = camera.logical_viewport_size()?;
+    let depth = (label_transform.translation() - camera_transform.translation())
+        .dot(*camera_transform.forward())
+        .max(perspective.near);
+    Some(2.0 * depth * (perspective.fov * 0.5).tan() / viewport.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn released_label_layers_are_handed_out_again() {
+        let mut world = World::new();
+        let [first, second, third] = [(); 3].map(|_| world.spawn_empty().id());
+        let mut layers = LabelLayers::default();
+
+        assert_eq!(layers.take(first), 1);
+        assert_eq!(layers.take(second), 2);
+        layers.release(first);
+        layers.release(first);
+        assert_eq!(layers.take(third), 1);
+        assert_eq!(layers.take(first), 3);
+    }
+}
@@ -42,18 +42,7 @@ pub(in crate::scenes::spawn) fn spawn_light_component(
 
     let color = parse_color(&entry.color).unwrap_or([255, 255, 255]);
     let color = Color::srgb_u8(color[0], color[1], color[2]);
-    let base_transform = Transform::from_xyz(
-        transform.position.x,
-        transform.position.y,
-        transform.position.z,
-    )
-    .with_rotation(Quat::from_euler(
-        EulerRot::XYZ,
-        transform.rotation.roll.to_radians(),
-        transform.rotation.pitch.to_radians(),
-        transform.rotation.yaw.to_radians(),
-    ))
-    .with_scale(Vec3::splat(transform.scale));
+    let base_transform = transform.to_transform();
     let offset = Vec3::new(entry.offset.x, entry.offset.y, entry.offset.z);
     let world_pos = base_transform.transform_point(offset);
 
@@ -2,7 +2,11 @@ use bevy::image::ImageLoaderSettings;
 use bevy::prelude::*;
 use bevy::render::alpha::AlphaMode;
 
-use crate::scenes::config::{parse_color, ActiveScene, MaterialConfig, ShapeConfig};
+use crate::scenes::config::{
+    parse_color, ActiveScene, MaterialConfig, MaterialPreset, ShapeConfig,
+};
+
+use super::sprite::SpriteAnimation;
 
 pub(super) fn resolve_material(
     shape: &ShapeConfig,
@@ -11,6 +15,21 @@ pub(super) fn resolve_material(
     asset_server: &AssetServer,
     active_scene: &ActiveScene,
 ) -> Handle<StandardMaterial> {
+    materials.add(resolve_standard_material(
+        shape,
+        material,
+        asset_server,
+        active_scene,
+    ))
+}
+
+/// The material `resolve_material` adds, for shapes that finish it off first.
+pub(super) fn resolve_standard_material(
+    shape: &ShapeConfig,
+    material: Option<&MaterialConfig>,
+    asset_server: &AssetServer,
+    active_scene: &ActiveScene,
+) -> StandardMaterial {
     let shape_color = super::shape::resolve_shape_color(shape, active_scene);
     let base_color_fallback = Color::srgb_u8(shape_color[0], shape_color[1], shape_color[2]);
     let mut resolved = if let Some(material) = material {
@@ -27,7 +46,7 @@ pub(super) fn resolve_material(
         }
     }
 
-    materials.add(resolved)
+    resolved
 }
 
 fn resolve_material_from_config(
@@ -35,18 +54,13 @@ fn resolve_material_from_config(
     base_color_fallback: Color,
     asset_server: &AssetServer,
 ) -> StandardMaterial {
-    let mut material = match config
-        .preset
-        .as_deref()
-        .map(|preset| preset.trim().to_ascii_lowercase().replace('-', "_"))
-        .as_deref()
-    {
-        Some("wood") | Some("wooden") => preset_wood(),
-        Some("metal") | Some("metallic") => preset_metal(),
-        Some("marble") => preset_marble(),
-        Some("stone") => preset_stone(),
-        Some("glass") => preset_glass(),
-        _ => StandardMaterial::default(),
+    let mut material = match config.preset.as_deref().and_then(MaterialPreset::parse) {
+        Some(MaterialPreset::Wood) => preset_wood(),
+        Some(MaterialPreset::Metal) => preset_metal(),
+        Some(MaterialPreset::Marble) => preset_marble(),
+        Some(MaterialPreset::Stone) => preset_stone(),
+        Some(MaterialPreset::Glass) => preset_glass(),
+        None => StandardMaterial::default(),
     };
 
     if let Some(color) = config.base_color.as_deref().and_then(parse_color) {
@@ -64,6 +78,9 @@ fn resolve_material_from_config(
     if let Some(path) = config.base_color_texture.as_deref() {
         material.base_color_texture = Some(load_texture(asset_server, path, true));
     }
+    if let Some(sheet) = &config.sprite_sheet {
+        material.uv_transform = SpriteAnimation::new(sheet).frame_uv(0);
+    }
 
     if let Some(metallic) = config.metallic {
         material.metallic = metallic.clamp(0.0, 1.0);
@@ -1,6 +1,8 @@
+use bevy::math::Vec3;
+
 use crate::scenes::config::{
-    EntityTransformConfig, LightComponent, LightOverridesConfig, PhysicsConfig, PhysicsOverrides,
-    ShapeConfig, ShapeOverrides, TransformOverrides,
+    CubeRotationConfig, EntityTransformConfig, LightComponent, LightOverridesConfig,
+    PhysicsConfig, PhysicsOverrides, ShapeConfig, ShapeOverrides, TransformOverrides,
 };
 
 pub(in crate::scenes::spawn) fn merge_shape(
@@ -18,6 +20,18 @@ pub(in crate::scenes::spawn) fn merge_shape(
         if let Some(radius) = ovr.radius {
             merged.radius = Some(radius);
         }
+        if let Some(height) = ovr.height {
+            merged.height = Some(height);
+        }
+        if let Some(tube_radius) = ovr.tube_radius {
+            merged.tube_radius = Some(tube_radius);
+        }
+        if let Some(collider) = ovr.collider {
+            merged.collider = collider;
+        }
+        if let Some(text) = &ovr.text {
+            merged.text = Some(text.clone());
+        }
     }
     merged
 }
@@ -88,12 +102,14 @@ pub(in crate::scenes::spawn) fn apply_transform_additive(
         base.position.z += position.z;
     }
     if let Some(rotation) = &delta.rotation {
-        base.rotation.roll += rotation.roll;
-        base.rotation.pitch += rotation.pitch;
-        base.rotation.yaw += rotation.yaw;
+        // Both are resolved where the entity ends up, which is what a look-at needs.
+        let position = Vec3::new(base.position.x, base.position.y, base.position.z);
+        base.rotation = CubeRotationConfig::from_quat(
+            rotation.to_quat(position) * base.rotation.to_quat(position),
+        );
     }
     if let Some(scale) = delta.scale {
-        base.scale *= scale;
+        base.scale = base.scale * scale;
     }
     base
 }
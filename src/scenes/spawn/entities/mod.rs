@@ -1,13 +1,26 @@
+mod geometry;
+mod label;
 mod light;
 mod material;
 mod merge;
+mod model;
 mod shape;
+mod sprite;
 mod template;
+mod terrain;
 
+pub use label::TextLabel;
 pub use template::spawn_entity_from_template;
 
+pub(super) use label::{
+    fit_text_labels, park_label_cameras, release_label_layers, scale_text_labels,
+    spawn_label_targets, update_label_text, LabelLayers,
+};
 pub(super) use light::spawn_light_component;
 pub(super) use merge::{
     apply_transform_additive, apply_translation, merge_light, merge_physics, merge_shape,
 };
+pub(super) use model::{spawn_loaded_models, PendingModel};
 pub(super) use shape::spawn_shape_instance;
+pub(super) use sprite::{animate_sprites, face_camera};
+pub(super) use terrain::{spawn_loaded_terrains, PendingTerrain, SplatTextures};
@@ -0,0 +1,314 @@
+use bevy::{
+    asset::RecursiveDependencyLoadState,
+    gltf::{Gltf, GltfLoaderSettings, GltfMesh, GltfNode},
+    log::{info, warn},
+    mesh::VertexAttributeValues,
+    prelude::*,
+};
+use bevy_rapier3d::prelude::{
+    AdditionalMassProperties, Collider, Friction, Restitution, RigidBody,
+};
+
+use crate::scenes::bounds::DespawnOutsideBounds;
+use crate::scenes::config::{ModelCollider, PhysicsConfig, ShapeConfig};
+
+use super::shape::resolve_rigid_body;
+
+/// A model waiting for its glTF file to load. Its meshes and collider are added by
+/// `spawn_loaded_models`, which then removes this.
+#[derive(Component)]
+pub(in crate::scenes::spawn) struct PendingModel {
+    gltf: Handle<Gltf>,
+    scene: Option<String>,
+    node: Option<String>,
+    collider: ModelCollider,
+    /// Replaces the model's own materials when the template sets a color or material.
+    material: Option<Handle<StandardMaterial>>,
+    physics: Option<PhysicsConfig>,
+}
+
+/// Starts loading the model and returns the entity its meshes will be spawned under.
+pub(super) fn spawn_model(
+    name: &str,
+    shape: &ShapeConfig,
+    material: Option<Handle<StandardMaterial>>,
+    physics: Option<&PhysicsConfig>,
+    transform: Transform,
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+) -> Entity {
+    let path = shape.model.clone().unwrap_or_default();
+    // The scene list in the glTF source is the only record of which nodes a scene holds.
+    let gltf = asset_server.load_with_settings(path, |settings: &mut GltfLoaderSettings| {
+        settings.include_source = true;
+    });
+    commands
+        .spawn((
+            Name::new(name.to_string()),
+            transform,
+            Visibility::default(),
+            PendingModel {
+                gltf,
+                scene: shape.scene.clone(),
+                node: shape.node.clone(),
+                collider: shape.collider,
+                material,
+                physics: physics.filter(|physics| physics.enabled).cloned(),
+            },
+        ))
+        .id()
+}
+
+/// Spawns the meshes of each pending model whose glTF file has loaded, with the collider
+/// and physics of its template. Models that fail to load are left empty.
+pub(in crate::scenes::spawn) fn spawn_loaded_models(
+    mut commands: Commands,
+    pending: Query<(Entity, &Name, &PendingModel)>,
+    asset_server: Res<AssetServer>,
+    gltfs: Res<Assets<Gltf>>,
+    gltf_nodes: Res<Assets<GltfNode>>,
+    gltf_meshes: Res<Assets<GltfMesh>>,
+    meshes: Res<Assets<Mesh>>,
+) {
+    for (entity, name, model) in &pending {
+        match asset_server.get_recursive_dependency_load_state(&model.gltf) {
+            Some(RecursiveDependencyLoadState::Loaded) => {}
+            Some(RecursiveDependencyLoadState::Failed(err)) => {
+                warn!("Failed to load model '{name}': {err}");
+                commands.entity(entity).remove::<PendingModel>();
+                continue;
+            }
+            _ => continue,
+        }
+        commands.entity(entity).remove::<PendingModel>();
+        let Some(gltf) = gltfs.get(&model.gltf) else {
+            continue;
+        };
+        let Some(roots) = model_roots(gltf, model, &gltf_nodes, name) else {
+            continue;
+        };
+
+        let mut outline = ModelOutline::default();
+        commands.entity(entity).with_children(|parent| {
+            for (root, to_model) in roots {
+                spawn_node(
+                    parent,
+                    &root,
+                    to_model,
+                    model,
+                    &gltf_nodes,
+                    &gltf_meshes,
+                    &meshes,
+                    &mut outline,
+                );
+            }
+        });
+
+        let Some(physics) = &model.physics else {
+            info!("Spawned model '{name}'.");
+            continue;
+        };
+        let rigid_body = resolve_rigid_body(&physics.body_type);
+        let mut root = commands.entity(entity);
+        root.insert((rigid_body, DespawnOutsideBounds));
+        if matches!(rigid_body, RigidBody::Dynamic) && physics.mass > 0.0 {
+            root.insert(AdditionalMassProperties::Mass(physics.mass));
+        }
+        match outline.collider(model.collider) {
+            Some((collider, None)) => {
+                root.insert((
+                    collider,
+                    Restitution::coefficient(physics.restitution),
+                    Friction::coefficient(physics.friction),
+                ));
+            }
+            Some((collider, Some(collider_transform))) => {
+                root.with_children(|parent| {
+                    parent.spawn((
+                        collider_transform,
+                        collider,
+                        Restitution::coefficient(physics.restitution),
+                        Friction::coefficient(physics.friction),
+                    ));
+                });
+            }
+            None => warn!("Could not build a collider for model '{name}'; it has no collisions."),
+        }
+    }
+}
+
+/// Top-level nodes of the model's `node`, its named `scene`, or else its default scene,
+/// each with the transform from its parent to the model's origin.
+fn model_roots(
+    gltf: &Gltf,
+    model: &PendingModel,
+    gltf_nodes: &Assets<GltfNode>,
+    name: &Name,
+) -> Option<Vec<(Handle<GltfNode>, Mat4)>> {
+    if let Some(node) = &model.node {
+        let found = gltf.named_nodes.get(node.as_str()).cloned();
+        if found.is_none() {
+            warn!("Model '{name}' has no node named '{node}'.");
+        }
+        return found.map(|node| {
+            let to_model = ancestors_to_model(gltf, gltf_nodes, &node);
+            vec![(node, to_model)]
+        });
+    }
+
+    let source = gltf.source.as_ref()?;
+    let scene = match &model.scene {
+        Some(scene) => source
+            .scenes()
+            .find(|candidate| candidate.name() == Some(scene.as_str())),
+        None => source.default_scene().or_else(|| source.scenes().next()),
+    };
+    let Some(scene) = scene else {
+        match &model.scene {
+            Some(scene) => warn!("Model '{name}' has no scene named '{scene}'."),
+            None => warn!("Model '{name}' has no scenes."),
+        }
+        return None;
+    };
+    let root_indices: Vec<usize> = scene.nodes().map(|node| node.index()).collect();
+    Some(
+        gltf.nodes
+            .iter()
+            .filter(|handle| {
+                gltf_nodes
+                    .get(*handle)
+                    .is_some_and(|node| root_indices.contains(&node.index))
+            })
+            .map(|handle| (handle.clone(), Mat4::IDENTITY))
+            .collect(),
+    )
+}
+
+/// The transform from `node`'s parent to the model's origin, so a node picked from deep
+/// inside the model keeps the place its ancestors give it.
+fn ancestors_to_model(gltf: &Gltf, gltf_nodes: &Assets<GltfNode>, node: &Handle<GltfNode>) -> Mat4 {
+    let mut to_model = Mat4::IDENTITY;
+    let mut child = node.id();
+    while let Some((handle, parent)) = gltf.nodes.iter().find_map(|handle| {
+        let parent = gltf_nodes.get(handle)?;
+        parent
+            .children
+            .iter()
+            .any(|candidate| candidate.id() == child)
+            .then_some((handle, parent))
+    }) {
+        to_model = parent.transform.to_matrix() * to_model;
+        child = handle.id();
+    }
+    to_model
+}
+
+/// Spawns the meshes of `node` and its children under `parent`, `to_model` being the
+/// transform from `node`'s parent to the model's origin.
+fn spawn_node(
+    parent: &mut ChildSpawnerCommands,
+    node: &Handle<GltfNode>,
+    to_model: Mat4,
+    model: &PendingModel,
+    gltf_nodes: &Assets<GltfNode>,
+    gltf_meshes: &Assets<GltfMesh>,
+    meshes: &Assets<Mesh>,
+    outline: &mut ModelOutline,
+) {
+    let Some(node) = gltf_nodes.get(node) else {
+        return;
+    };
+    let to_model = to_model * node.transform.to_matrix();
+    let mesh = node.mesh.as_ref().and_then(|mesh| gltf_meshes.get(mesh));
+    for primitive in mesh.into_iter().flat_map(|mesh| &mesh.primitives) {
+        let material = model
+            .material
+            .clone()
+            .or_else(|| primitive.material.clone())
+            .unwrap_or_default();
+        parent.spawn((
+            Mesh3d(primitive.mesh.clone()),
+            MeshMaterial3d(material),
+            Transform::from_matrix(to_model),
+        ));
+        if let Some(mesh) = meshes.get(&primitive.mesh) {
+            outline.add(mesh, to_model);
+        }
+    }
+    for child in &node.children {
+        spawn_node(
+            parent,
+            child,
+            to_model,
+            model,
+            gltf_nodes,
+            gltf_meshes,
+            meshes,
+            outline,
+        );
+    }
+}
+
+/// Triangles of every mesh in a model, in the model's own space.
+#[derive(Default)]
+struct ModelOutline {
+    vertices: Vec<Vec3>,
+    triangles: Vec<[u32; 3]>,
+}
+
+impl ModelOutline {
+    fn add(&mut self, mesh: &Mesh, to_model: Mat4) {
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            return;
+        };
+        let first = self.vertices.len() as u32;
+        self.vertices.extend(
+            positions
+                .iter()
+                .map(|position| to_model.transform_point3(Vec3::from_array(*position))),
+        );
+        let indices: Vec<u32> = match mesh.indices() {
+            Some(indices) => indices.iter().map(|index| first + index as u32).collect(),
+            None => (first..self.vertices.len() as u32).collect(),
+        };
+        self.triangles.extend(
+            indices
+                .chunks_exact(3)
+                .map(|triangle| [triangle[0], triangle[1], triangle[2]]),
+        );
+    }
+
+    /// The collider, and the transform of the child it goes on when it is not centered
+    /// on the model.
+    fn collider(self, kind: ModelCollider) -> Option<(Collider, Option<Transform>)> {
+        if self.vertices.is_empty() {
+            return None;
+        }
+        match kind {
+            ModelCollider::ConvexHull => {
+                Collider::convex_hull(&self.vertices).map(|collider| (collider, None))
+            }
+            ModelCollider::ConvexDecomposition => Some((
+                Collider::convex_decomposition(&self.vertices, &self.triangles),
+                None,
+            )),
+            ModelCollider::Trimesh => Collider::trimesh(self.vertices, self.triangles)
+                .ok()
+                .map(|collider| (collider, None)),
+            ModelCollider::BoundingBox => {
+                let min = self.vertices.iter().copied().reduce(Vec3::min)?;
+                let max = self.vertices.iter().copied().reduce(Vec3::max)?;
+                let half = (max - min) * 0.5;
+                let center = (max + min) * 0.5;
+                let collider = Collider::cuboid(half.x, half.y, half.z);
+                if center == Vec3::ZERO {
+                    Some((collider, None))
+                } else {
+                    Some((collider, Some(Transform::from_translation(center))))
+                }
+            }
+        }
+    }
+}
@@ -9,11 +9,16 @@ use bevy_rapier3d::prelude::{
 use crate::scenes::bounds::DespawnOutsideBounds;
 use crate::scenes::config::{
     default_circle_color_name, default_circle_rgb, default_color_name, default_color_rgb,
-    parse_color, ActiveScene, EntityTransformConfig, MaterialConfig, PhysicsConfig, ShapeConfig,
-    ShapeKind,
+    parse_color, parse_rigid_body, ActiveScene, EntityTransformConfig, MaterialConfig,
+    PhysicsConfig, ShapeConfig, ShapeKind,
 };
 
+use super::geometry::shape_geometry;
+use super::label::spawn_text_label;
 use super::material::resolve_material;
+use super::model::spawn_model;
+use super::sprite::{Billboard, SpriteAnimation};
+use super::terrain::PendingTerrain;
 
 pub(in crate::scenes::spawn) fn spawn_shape_instance(
     name: &str,
@@ -27,143 +32,111 @@ pub(in crate::scenes::spawn) fn spawn_shape_instance(
     asset_server: &AssetServer,
     active_scene: &ActiveScene,
 ) -> Entity {
-    let rotation = Quat::from_euler(
-        EulerRot::XYZ,
-        transform.rotation.roll.to_radians(),
-        transform.rotation.pitch.to_radians(),
-        transform.rotation.yaw.to_radians(),
-    );
-
-    let material_handle = resolve_material(
-        shape,
-        material,
-        materials,
-        asset_server,
-        active_scene,
-    );
+    let spawn_transform = transform.to_transform();
 
-    let entity_id = match shape.kind {
-        ShapeKind::Box => {
-            let dimensions = shape.dimensions.as_ref().cloned().unwrap_or_default();
-            let half_extents = Vec3::new(
-                dimensions.width * 0.5,
-                dimensions.height * 0.5,
-                dimensions.depth * 0.5,
+    if shape.kind == ShapeKind::Model {
+        // Models keep their own materials unless the template asks for another look.
+        let material_handle = (material.is_some() || shape.color.is_some()).then(|| {
+            resolve_material(shape, material, materials, asset_server, active_scene)
+        });
+        let entity = spawn_model(
+            name,
+            shape,
+            material_handle,
+            physics,
+            spawn_transform,
+            commands,
+            asset_server,
+        );
+        if let Some(mode) = shape.billboard {
+            commands.entity(entity).insert(Billboard(mode));
+        }
+        return entity;
+    }
+    if shape.kind == ShapeKind::Text {
+        if physics.is_some_and(|physics| physics.enabled) {
+            warn!(
+                "Ignoring physics on text '{}' in scene '{}'; text has no collider.",
+                name, active_scene.name
             );
-            let mut entity = commands.spawn((
-                Name::new(name.to_string()),
-                Mesh3d(meshes.add(Cuboid::new(
-                    dimensions.width,
-                    dimensions.height,
-                    dimensions.depth,
-                ))),
-                MeshMaterial3d(material_handle.clone()),
-                Transform::from_xyz(
-                    transform.position.x,
-                    transform.position.y,
-                    transform.position.z,
-                )
-                .with_rotation(rotation)
-                .with_scale(Vec3::splat(transform.scale)),
-                Visibility::default(),
-                InheritedVisibility::default(),
-                ViewVisibility::default(),
-            ));
-
-            if let Some(physics) = physics {
-                if physics.enabled {
-                    let rigid_body = resolve_rigid_body(&physics.body_type);
-                    entity.insert((
-                        rigid_body,
-                        Collider::cuboid(half_extents.x, half_extents.y, half_extents.z),
-                        Restitution::coefficient(physics.restitution),
-                        Friction::coefficient(physics.friction),
-                        DespawnOutsideBounds,
-                    ));
-                    if matches!(rigid_body, RigidBody::Dynamic) && physics.mass > 0.0 {
-                        entity.insert(AdditionalMassProperties::Mass(physics.mass));
-                    }
-                }
-            }
-            entity.id()
         }
-        ShapeKind::Sphere => {
-            let radius = shape.radius.unwrap_or(0.5);
-            let mut entity = commands.spawn((
-                Name::new(name.to_string()),
-                Mesh3d(meshes.add(Sphere::new(radius))),
-                MeshMaterial3d(material_handle.clone()),
-                Transform::from_xyz(
-                    transform.position.x,
-                    transform.position.y,
-                    transform.position.z,
-                )
-                .with_rotation(rotation)
-                .with_scale(Vec3::splat(transform.scale)),
-                Visibility::default(),
-                InheritedVisibility::default(),
-                ViewVisibility::default(),
-            ));
+        let entity = spawn_text_label(
+            name,
+            shape,
+            spawn_transform,
+            commands,
+            meshes,
+            materials,
+            active_scene,
+        );
+        if let Some(mode) = shape.billboard {
+            commands.entity(entity).insert(Billboard(mode));
+        }
+        return entity;
+    }
 
-            if let Some(physics) = physics {
-                if physics.enabled {
-                    let rigid_body = resolve_rigid_body(&physics.body_type);
-                    entity.insert((
-                        rigid_body,
-                        Collider::ball(radius),
-                        Restitution::coefficient(physics.restitution),
-                        Friction::coefficient(physics.friction),
-                        DespawnOutsideBounds,
-                    ));
-                    if matches!(rigid_body, RigidBody::Dynamic) && physics.mass > 0.0 {
-                        entity.insert(AdditionalMassProperties::Mass(physics.mass));
-                    }
-                }
-            }
-            entity.id()
+    if shape.kind == ShapeKind::Terrain {
+        if shape.billboard.is_some() {
+            warn!(
+                "Ignoring billboard on terrain '{}' in scene '{}'; terrain stays level.",
+                name, active_scene.name
+            );
         }
-        ShapeKind::Circle => {
-            let radius = shape.radius.unwrap_or(4.0);
-            let collider_thickness = 0.2;
-            let mut entity = commands.spawn((
+        if material.is_some_and(|material| material.sprite_sheet.is_some()) {
+            warn!(
+                "Ignoring sprite sheet on terrain '{}' in scene '{}'; terrain is splatted instead.",
+                name, active_scene.name
+            );
+        }
+        // The mesh, material and collider follow once the heightmap has loaded.
+        return commands
+            .spawn((
                 Name::new(name.to_string()),
-                Mesh3d(meshes.add(Circle::new(radius))),
-                MeshMaterial3d(material_handle.clone()),
-                Transform::from_xyz(
-                    transform.position.x,
-                    transform.position.y,
-                    transform.position.z,
-                )
-                .with_rotation(rotation)
-                .with_scale(Vec3::splat(transform.scale)),
+                spawn_transform,
                 Visibility::default(),
-                InheritedVisibility::default(),
-                ViewVisibility::default(),
-            ));
+                PendingTerrain::new(name, shape, material, physics, asset_server, active_scene),
+            ))
+            .id();
+    }
 
-            if let Some(physics) = physics {
-                if physics.enabled {
-                    let rigid_body = resolve_rigid_body(&physics.body_type);
-                    entity.insert((rigid_body, DespawnOutsideBounds));
-                    if matches!(rigid_body, RigidBody::Dynamic) && physics.mass > 0.0 {
-                        entity.insert(AdditionalMassProperties::Mass(physics.mass));
-                    }
+    if material.is_some_and(|material| !material.splat.is_empty()) {
+        warn!(
+            "Ignoring splat layers on {} '{}' in scene '{}'; only terrain is splatted.",
+            shape.kind.as_str(),
+            name,
+            active_scene.name
+        );
+    }
+    let material_handle = resolve_material(shape, material, materials, asset_server, active_scene);
+    let geometry = shape_geometry(shape);
+    let mut entity = commands.spawn((
+        Name::new(name.to_string()),
+        Mesh3d(meshes.add(geometry.mesh)),
+        MeshMaterial3d(material_handle),
+        spawn_transform,
+        Visibility::default(),
+        InheritedVisibility::default(),
+        ViewVisibility::default(),
+    ));
+    if let Some(mode) = shape.billboard {
+        entity.insert(Billboard(mode));
+    }
+    if let Some(sheet) = material.and_then(|material| material.sprite_sheet.as_ref()) {
+        entity.insert(SpriteAnimation::new(sheet));
+    }
 
-                    let collider_rotation = rotation.inverse();
-                    entity.with_children(|parent| {
-                        parent.spawn((
-                            Transform::from_rotation(collider_rotation),
-                            Collider::cylinder(collider_thickness * 0.5, radius),
-                            Restitution::coefficient(physics.restitution),
-                            Friction::coefficient(physics.friction),
-                        ));
-                    });
-                }
-            }
-            entity.id()
+    if let Some(physics) = physics.filter(|physics| physics.enabled) {
+        if geometry.collider.is_none() {
+            warn!(
+                "Could not build a collider for {} '{}' in scene '{}'; it has no collisions.",
+                shape.kind.as_str(),
+                name,
+                active_scene.name
+            );
         }
-    };
-
+        insert_physics(&mut entity, physics, geometry.collider, geometry.collider_transform);
+    }
+    let entity_id = entity.id();
     if physics.is_none() {
         info!("Spawned shape '{}' in scene '{}'.", name, active_scene.name);
     }
@@ -171,6 +144,38 @@ pub(in crate::scenes::spawn) fn spawn_shape_instance(
     entity_id
 }
 
+/// Makes `entity` a rigid body of `physics` with `collider`, which goes on a child when it
+/// has a transform of its own.
+pub(super) fn insert_physics(
+    entity: &mut EntityCommands,
+    physics: &PhysicsConfig,
+    collider: Option<Collider>,
+    collider_transform: Option<Transform>,
+) {
+    let rigid_body = resolve_rigid_body(&physics.body_type);
+    entity.insert((rigid_body, DespawnOutsideBounds));
+    if matches!(rigid_body, RigidBody::Dynamic) && physics.mass > 0.0 {
+        entity.insert(AdditionalMassProperties::Mass(physics.mass));
+    }
+    let Some(collider) = collider else {
+        return;
+    };
+    let contact = (
+        Restitution::coefficient(physics.restitution),
+        Friction::coefficient(physics.friction),
+    );
+    match collider_transform {
+        None => {
+            entity.insert((collider, contact));
+        }
+        Some(collider_transform) => {
+            entity.with_children(|parent| {
+                parent.spawn((collider_transform, collider, contact));
+            });
+        }
+    }
+}
+
 pub(in crate::scenes::spawn) fn resolve_shape_color(
     shape: &ShapeConfig,
     active_scene: &ActiveScene,
@@ -183,19 +188,30 @@ pub(in crate::scenes::spawn) fn resolve_shape_color(
 
 fn fallback_color(kind: ShapeKind, active_scene: &ActiveScene) -> [u8; 3] {
     match kind {
-        ShapeKind::Circle => {
+        // Flat shapes and terrain are usually ground, so they share the circle's color.
+        ShapeKind::Circle | ShapeKind::Plane | ShapeKind::Terrain => {
             warn!(
-                "Falling back to default color '{}' for circle in scene '{}'.",
+                "Falling back to default color '{}' for {} in scene '{}'.",
                 default_circle_color_name(),
+                kind.as_str(),
                 active_scene.name
             );
             default_circle_rgb()
         }
         ShapeKind::Sphere => [255, 165, 0],
-        ShapeKind::Box => {
+        ShapeKind::Text => [255, 255, 255],
+        ShapeKind::Box
+        | ShapeKind::Cylinder
+        | ShapeKind::Capsule
+        | ShapeKind::Cone
+        | ShapeKind::Torus
+        | ShapeKind::Wedge
+        | ShapeKind::Model
+        | ShapeKind::Sprite => {
             warn!(
-                "Falling back to default color '{}' for box in scene '{}'.",
+                "Falling back to default color '{}' for {} in scene '{}'.",
                 default_color_name(),
+                kind.as_str(),
                 active_scene.name
             );
             default_color_rgb()
@@ -203,15 +219,6 @@ fn fallback_color(kind: ShapeKind, active_scene: &ActiveScene) -> [u8; 3] {
     }
 }
 
-fn resolve_rigid_body(body_type: &str) -> RigidBody {
-    match body_type.trim().to_ascii_lowercase().as_str() {
-        "fixed" | "static" => RigidBody::Fixed,
-        "kinematic_position" | "kinematic_position_based" => {
-            RigidBody::KinematicPositionBased
-        }
-        "kinematic_velocity" | "kinematic_velocity_based" => {
-            RigidBody::KinematicVelocityBased
-        }
-        _ => RigidBody::Dynamic,
-    }
+pub(super) fn resolve_rigid_body(body_type: &str) -> RigidBody {
+    parse_rigid_body(body_type).unwrap_or(RigidBody::Dynamic)
 }
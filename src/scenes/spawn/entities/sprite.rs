@@ -0,0 +1,102 @@
+use bevy::math::Affine2;
+use bevy::prelude::*;
+
+use crate::scenes::config::{BillboardMode, SpriteSheetConfig};
+use crate::scenes::input::SceneCamera;
+
+/// Turns a shape's +Z face toward the scene camera every frame. Meant for fixed or
+/// kinematic shapes; a dynamic body's own rotation is overwritten.
+#[derive(Component, Debug, Clone, Copy)]
+pub(in crate::scenes::spawn) struct Billboard(pub BillboardMode);
+
+/// Steps a shape's material through the cells of its sprite sheet.
+#[derive(Component, Debug, Clone)]
+pub(in crate::scenes::spawn) struct SpriteAnimation {
+    columns: u32,
+    rows: u32,
+    frames: u32,
+    fps: f32,
+    elapsed: f32,
+    frame: u32,
+}
+
+impl SpriteAnimation {
+    pub(in crate::scenes::spawn) fn new(sheet: &SpriteSheetConfig) -> Self {
+        let columns = sheet.columns.max(1);
+        let rows = sheet.rows.max(1);
+        Self {
+            columns,
+            rows,
+            frames: sheet
+                .frames
+                .unwrap_or(columns * rows)
+                .clamp(1, columns * rows),
+            fps: sheet.fps.max(0.0),
+            elapsed: 0.0,
+            frame: 0,
+        }
+    }
+
+    /// Maps a material's UVs onto cell `frame` of the sheet, counting cells left to right
+    /// and then top to bottom.
+    pub(in crate::scenes::spawn) fn frame_uv(&self, frame: u32) -> Affine2 {
+        let cell = Vec2::new(1.0 / self.columns as f32, 1.0 / self.rows as f32);
+        let offset = Vec2::new((frame % self.columns) as f32, (frame / self.columns) as f32);
+        Affine2::from_scale_angle_translation(cell, 0.0, offset * cell)
+    }
+}
+
+/// Rotates every `Billboard` toward the first `SceneCamera`, before transforms propagate so
+/// the mesh and its collider agree within the frame.
+pub(in crate::scenes::spawn) fn face_camera(
+    cameras: Query<&GlobalTransform, With<SceneCamera>>,
+    mut billboards: Query<(&Billboard, &mut Transform, Option<&ChildOf>)>,
+    parents: Query<&GlobalTransform>,
+) {
+    let Some(camera) = cameras.iter().next() else {
+        return;
+    };
+    let (_, camera_rotation, camera_position) = camera.to_scale_rotation_translation();
+    for (billboard, mut transform, child_of) in &mut billboards {
+        let parent = child_of.and_then(|child_of| parents.get(child_of.parent()).ok());
+        let (parent_rotation, position) = match parent {
+            Some(parent) => (
+                parent.to_scale_rotation_translation().1,
+                parent.transform_point(transform.translation),
+            ),
+            None => (Quat::IDENTITY, transform.translation),
+        };
+        let rotation = match billboard.0 {
+            BillboardMode::All => camera_rotation,
+            BillboardMode::YAxis => {
+                let toward = camera_position - position;
+                if toward.x.abs() < f32::EPSILON && toward.z.abs() < f32::EPSILON {
+                    continue;
+                }
+                Quat::from_rotation_y(toward.x.atan2(toward.z))
+            }
+        };
+        transform.rotation = parent_rotation.inverse() * rotation;
+    }
+}
+
+/// Advances sprite sheets by the frame time, touching a material only when its frame turns.
+pub(in crate::scenes::spawn) fn animate_sprites(
+    time: Res<Time>,
+    mut sprites: Query<(&mut SpriteAnimation, &MeshMaterial3d<StandardMaterial>)>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for (mut animation, material) in &mut sprites {
+        let period = animation.frames as f32 / animation.fps;
+        animation.elapsed = (animation.elapsed + time.delta_secs()) % period;
+        let frame = (animation.elapsed * animation.fps) as u32 % animation.frames;
+        if frame == animation.frame {
+            continue;
+        }
+        animation.frame = frame;
+        let Some(material) = materials.get_mut(&material.0) else {
+            continue;
+        };
+        material.uv_transform = animation.frame_uv(frame);
+    }
+}
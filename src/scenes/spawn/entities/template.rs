@@ -17,7 +17,7 @@ pub fn spawn_entity_from_template(
     materials: &mut Assets<StandardMaterial>,
     asset_server: &AssetServer,
     active_scene: &ActiveScene,
-) {
+) -> Vec<Entity> {
     let transform = merge_transform(&template.transform, placement_transform, None);
     let shape = template
         .shape
@@ -36,8 +36,9 @@ pub fn spawn_entity_from_template(
         .cloned()
         .unwrap_or_else(|| template.name.clone());
 
+    let mut spawned = Vec::new();
     if let Some(shape) = shape {
-        spawn_shape_instance(
+        spawned.push(spawn_shape_instance(
             &base_name,
             &shape,
             material,
@@ -48,18 +49,20 @@ pub fn spawn_entity_from_template(
             materials,
             asset_server,
             active_scene,
-        );
+        ));
     }
 
     if let Some(light) = light {
-        let _ = spawn_light_component(
+        spawned.extend(spawn_light_component(
             &base_name,
             &light,
             &transform,
             commands,
             active_scene,
-        );
+        ));
     }
+
+    spawned
 }
 
 fn merge_transform(
@@ -0,0 +1,475 @@
+use std::collections::HashMap;
+
+use bevy::{
+    asset::{LoadState, RenderAssetUsages},
+    image::ImageLoaderSettings,
+    log::{info, warn},
+    mesh::{Indices, PrimitiveTopology},
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use bevy_rapier3d::prelude::Collider;
+
+use crate::scenes::config::{
+    parse_color, ActiveScene, MaterialConfig, PhysicsConfig, ShapeConfig, SplatLayerConfig,
+};
+
+use super::geometry::ShapeGeometry;
+use super::material::resolve_standard_material;
+use super::shape::insert_physics;
+
+/// Texels along each side of a splat texture when the material does not say.
+const DEFAULT_SPLAT_RESOLUTION: u32 = 128;
+
+/// A terrain waiting for its heightmap and splat textures to load. Its mesh, material and
+/// collider are added by `spawn_loaded_terrains`, which then removes this.
+#[derive(Component)]
+pub(in crate::scenes::spawn) struct PendingTerrain {
+    shape: ShapeConfig,
+    heightmap: Option<Handle<Image>>,
+    /// The texture of each splat layer, in the material's order.
+    splat_textures: Vec<Option<Handle<Image>>>,
+    splat: Vec<SplatLayerConfig>,
+    splat_resolution: Option<u32>,
+    /// The template's material, which the splat texture is baked over.
+    material: StandardMaterial,
+    physics: Option<PhysicsConfig>,
+}
+
+/// Splat textures baked for the active scene, so placements of one terrain template
+/// share a single bake. Keyed by everything the bake reads; replaced on scene switch.
+#[derive(Resource, Default)]
+pub(in crate::scenes::spawn) struct SplatTextures(HashMap<String, Handle<Image>>);
+
+impl PendingTerrain {
+    /// Starts loading the terrain's heightmap and splat textures.
+    pub(super) fn new(
+        name: &str,
+        shape: &ShapeConfig,
+        material: Option<&MaterialConfig>,
+        physics: Option<&PhysicsConfig>,
+        asset_server: &AssetServer,
+        active_scene: &ActiveScene,
+    ) -> Self {
+        let heightmap = shape.heightmap.as_deref().map(|path| {
+            // Heights are data, so the heightmap is read without color space conversion.
+            let settings = |settings: &mut ImageLoaderSettings| settings.is_srgb = false;
+            asset_server.load_with_settings(path.to_string(), settings)
+        });
+        if heightmap.is_none() {
+            warn!(
+                "Terrain '{}' in scene '{}' has no heightmap; leaving it flat.",
+                name, active_scene.name
+            );
+        }
+        let splat = material.map_or_else(Vec::new, |material| material.splat.clone());
+        let splat_textures = splat
+            .iter()
+            .map(|layer| layer.texture.clone().map(|path| asset_server.load(path)))
+            .collect();
+        Self {
+            shape: shape.clone(),
+            heightmap,
+            splat_textures,
+            splat,
+            splat_resolution: material.and_then(|material| material.splat_resolution),
+            material: resolve_standard_material(shape, material, asset_server, active_scene),
+            physics: physics.filter(|physics| physics.enabled).cloned(),
+        }
+    }
+}
+
+/// Adds the mesh, material and collider of each pending terrain whose images have loaded
+/// or failed. A terrain whose heightmap failed is flat, and a splat layer whose texture
+/// failed uses its color alone.
+pub(in crate::scenes::spawn) fn spawn_loaded_terrains(
+    mut commands: Commands,
+    terrains: Query<(Entity, &Name, &PendingTerrain)>,
+    asset_server: Res<AssetServer>,
+    images: Res<Assets<Image>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut splat_textures: ResMut<SplatTextures>,
+) {
+    for (entity, name, pending) in &terrains {
+        let mut handles = pending.heightmap.iter().chain(pending.splat_textures.iter().flatten());
+        if handles.any(|handle| is_loading(&asset_server, handle)) {
+            continue;
+        }
+        commands.entity(entity).remove::<PendingTerrain>();
+
+        let heightmap = pending.heightmap.as_ref().and_then(|handle| {
+            let image = loaded_image(&asset_server, &images, handle);
+            if let Err(err) = &image {
+                let path = pending.shape.heightmap.as_deref().unwrap_or_default();
+                warn!(
+                    "Failed to read heightmap '{path}' of terrain '{name}': {err}; leaving it \
+                     flat."
+                );
+            }
+            image.ok()
+        });
+        let terrain = Terrain::new(&pending.shape, heightmap);
+
+        let mut material = pending.material.clone();
+        if !pending.splat.is_empty() {
+            let key = splat_key(pending);
+            let texture = splat_textures.0.entry(key).or_insert_with(|| {
+                let layers = splat_layers(pending, name, &asset_server, &images);
+                let texture =
+                    terrain.splat_texture(&layers, pending.splat_resolution, material.base_color);
+                asset_server.add(texture)
+            });
+            material.base_color = Color::WHITE;
+            material.base_color_texture = Some(texture.clone());
+        }
+
+        let geometry = terrain.geometry();
+        let mut root = commands.entity(entity);
+        root.insert((
+            Mesh3d(meshes.add(geometry.mesh)),
+            MeshMaterial3d(materials.add(material)),
+        ));
+        let Some(physics) = &pending.physics else {
+            info!("Spawned terrain '{name}'.");
+            continue;
+        };
+        insert_physics(&mut root, physics, geometry.collider, geometry.collider_transform);
+    }
+}
+
+/// The material's splat layers with the textures that loaded.
+fn splat_layers<'a>(
+    pending: &'a PendingTerrain,
+    name: &Name,
+    asset_server: &AssetServer,
+    images: &'a Assets<Image>,
+) -> Vec<SplatLayer<'a>> {
+    pending
+        .splat
+        .iter()
+        .zip(&pending.splat_textures)
+        .map(|(layer, texture)| {
+            let texture = texture.as_ref().and_then(|handle| {
+                let image = loaded_image(asset_server, images, handle);
+                if let Err(err) = &image {
+                    let path = layer.texture.as_deref().unwrap_or_default();
+                    warn!(
+                        "Failed to read splat texture '{path}' of terrain '{name}': {err}; using \
+                         its color alone."
+                    );
+                }
+                image.ok()
+            });
+            SplatLayer::new(layer, texture)
+        })
+        .collect()
+}
+
+/// Whether `handle` is still on its way; loaded and failed images are both settled.
+fn is_loading(asset_server: &AssetServer, handle: &Handle<Image>) -> bool {
+    !matches!(
+        asset_server.load_state(handle),
+        LoadState::Loaded | LoadState::Failed(_)
+    )
+}
+
+fn loaded_image<'a>(
+    asset_server: &AssetServer,
+    images: &'a Assets<Image>,
+    handle: &Handle<Image>,
+) -> Result<&'a Image, String> {
+    if let LoadState::Failed(err) = asset_server.load_state(handle) {
+        return Err(err.to_string());
+    }
+    images.get(handle).ok_or_else(|| "the image was unloaded".to_string())
+}
+
+/// Everything the baked splat texture of `terrain` depends on.
+fn splat_key(terrain: &PendingTerrain) -> String {
+    let shape = &terrain.shape;
+    format!(
+        "{:?}",
+        (
+            &shape.heightmap,
+            shape.resolution,
+            &shape.dimensions,
+            terrain.material.base_color,
+            terrain.splat_resolution,
+            &terrain.splat,
+        )
+    )
+}
+
+/// Heights of a terrain on a grid of `rows` along Z by `columns` along X, row after row
+/// from the back (-Z), each from 0 at the bottom of `size` to 1 at its top. The grid is
+/// laid out as Rapier lays out a heightfield, so the mesh and collider share triangles.
+pub(super) struct Terrain {
+    heights: Vec<f32>,
+    rows: usize,
+    columns: usize,
+    size: Vec3,
+}
+
+impl Terrain {
+    /// Samples `heightmap` over the shape's size; without one the terrain is flat.
+    fn new(shape: &ShapeConfig, heightmap: Option<&Image>) -> Self {
+        let dimensions = shape.dimensions.clone().unwrap_or_default();
+        let size = Vec3::new(dimensions.width, dimensions.height, dimensions.depth);
+        match heightmap {
+            Some(heightmap) => Self::sample(heightmap, shape.resolution, size),
+            None => Self::flat(size),
+        }
+    }
+
+    fn flat(size: Vec3) -> Self {
+        Self {
+            heights: vec![0.0; 4],
+            rows: 2,
+            columns: 2,
+            size,
+        }
+    }
+
+    fn sample(heightmap: &Image, resolution: Option<u32>, size: Vec3) -> Self {
+        let width = heightmap.width().max(1);
+        let height = heightmap.height().max(1);
+        let pixels: Vec<f32> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                heightmap
+                    .get_color_at(x, y)
+                    .map(|color| color.to_linear().red)
+                    .unwrap_or_default()
+            })
+            .collect();
+        let pixel = |x: u32, y: u32| pixels[(y * width + x) as usize];
+
+        let columns = resolution.unwrap_or(width).max(2) as usize;
+        let rows = resolution.unwrap_or(height).max(2) as usize;
+        let mut heights = Vec::with_capacity(rows * columns);
+        for row in 0..rows {
+            for column in 0..columns {
+                // Bilinear between the four pixels around the sample.
+                let x = column as f32 / (columns - 1) as f32 * (width - 1) as f32;
+                let y = row as f32 / (rows - 1) as f32 * (height - 1) as f32;
+                let (x0, y0) = (x.floor() as u32, y.floor() as u32);
+                let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+                let (tx, ty) = (x.fract(), y.fract());
+                let top = pixel(x0, y0).lerp(pixel(x1, y0), tx);
+                let bottom = pixel(x0, y1).lerp(pixel(x1, y1), tx);
+                heights.push(top.lerp(bottom, ty));
+            }
+        }
+        Self {
+            heights,
+            rows,
+            columns,
+            size,
+        }
+    }
+
+    fn geometry(&self) -> ShapeGeometry {
+        ShapeGeometry {
+            mesh: self.mesh(),
+            collider: Some(self.collider()),
+            collider_transform: None,
+        }
+    }
+
+    fn mesh(&self) -> Mesh {
+        let mut positions = Vec::with_capacity(self.heights.len());
+        let mut normals = Vec::with_capacity(self.heights.len());
+        let mut uvs = Vec::with_capacity(self.heights.len());
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                let uv = self.uv(row, column);
+                positions.push(self.point(row, column).to_array());
+                normals.push(self.normal(row, column).to_array());
+                uvs.push(uv.to_array());
+            }
+        }
+
+        // The same split as Rapier's heightfield cells, wound counter-clockwise from above.
+        let mut indices = Vec::with_capacity((self.rows - 1) * (self.columns - 1) * 6);
+        let vertex = |row: usize, column: usize| (row * self.columns + column) as u32;
+        for row in 0..self.rows - 1 {
+            for column in 0..self.columns - 1 {
+                let back_left = vertex(row, column);
+                let front_left = vertex(row + 1, column);
+                let back_right = vertex(row, column + 1);
+                let front_right = vertex(row + 1, column + 1);
+                indices.extend([back_left, front_left, back_right]);
+                indices.extend([front_left, front_right, back_right]);
+            }
+        }
+
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+        .with_inserted_indices(Indices::U32(indices))
+    }
+
+    fn collider(&self) -> Collider {
+        // Rapier takes the heights column by column.
+        let heights = (0..self.columns)
+            .flat_map(|column| (0..self.rows).map(move |row| (row, column)))
+            .map(|(row, column)| self.height(row, column))
+            .collect();
+        Collider::heightfield(heights, self.rows, self.columns, self.size)
+    }
+
+    fn height(&self, row: usize, column: usize) -> f32 {
+        self.heights[row * self.columns + column]
+    }
+
+    fn uv(&self, row: usize, column: usize) -> Vec2 {
+        Vec2::new(
+            column as f32 / (self.columns - 1) as f32,
+            row as f32 / (self.rows - 1) as f32,
+        )
+    }
+
+    fn point(&self, row: usize, column: usize) -> Vec3 {
+        let uv = self.uv(row, column);
+        Vec3::new(
+            (uv.x - 0.5) * self.size.x,
+            self.height(row, column) * self.size.y,
+            (uv.y - 0.5) * self.size.z,
+        )
+    }
+
+    /// Rise in world units per unit along X and Z, from the neighbouring vertices.
+    fn gradient(&self, row: usize, column: usize) -> Vec2 {
+        let left = column.saturating_sub(1);
+        let right = (column + 1).min(self.columns - 1);
+        let back = row.saturating_sub(1);
+        let front = (row + 1).min(self.rows - 1);
+        let along_x = self.point(row, right) - self.point(row, left);
+        let along_z = self.point(front, column) - self.point(back, column);
+        Vec2::new(along_x.y / along_x.x, along_z.y / along_z.z)
+    }
+
+    fn normal(&self, row: usize, column: usize) -> Vec3 {
+        let gradient = self.gradient(row, column);
+        Vec3::new(-gradient.x, 1.0, -gradient.y).normalize()
+    }
+
+    /// Height in world units and slope in degrees at `uv`, between the nearest vertices.
+    fn surface_at(&self, uv: Vec2) -> (f32, f32) {
+        let x = uv.x.clamp(0.0, 1.0) * (self.columns - 1) as f32;
+        let y = uv.y.clamp(0.0, 1.0) * (self.rows - 1) as f32;
+        let (column, row) = (x.floor() as usize, y.floor() as usize);
+        let (next_column, next_row) = (
+            (column + 1).min(self.columns - 1),
+            (row + 1).min(self.rows - 1),
+        );
+        let (tx, ty) = (x.fract(), y.fract());
+        let sample = |value: &dyn Fn(usize, usize) -> f32| {
+            let back = value(row, column).lerp(value(row, next_column), tx);
+            let front = value(next_row, column).lerp(value(next_row, next_column), tx);
+            back.lerp(front, ty)
+        };
+        let height = sample(&|row, column| self.height(row, column) * self.size.y);
+        let slope = sample(&|row, column| self.gradient(row, column).length().atan().to_degrees());
+        (height, slope)
+    }
+
+    /// Bakes `layers` over `base` into a texture covering the terrain once.
+    fn splat_texture(&self, layers: &[SplatLayer], resolution: Option<u32>, base: Color) -> Image {
+        let resolution = resolution.unwrap_or(DEFAULT_SPLAT_RESOLUTION).max(1);
+        let mut image = Image::new_fill(
+            Extent3d {
+                width: resolution,
+                height: resolution,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[0, 0, 0, 255],
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+        let base = base.to_linear();
+        for y in 0..resolution {
+            for x in 0..resolution {
+                let uv = (Vec2::new(x as f32, y as f32) + 0.5) / resolution as f32;
+                let (height, slope) = self.surface_at(uv);
+                let color = layers.iter().fold(base, |color, layer| {
+                    color.mix(&layer.color_at(uv), layer.weight(height, slope))
+                });
+                // Every texel is inside the image, which is in a color format.
+                let _ = image.set_color_at(x, y, color.into());
+            }
+        }
+        image
+    }
+}
+
+/// A splat layer with its texture loaded.
+struct SplatLayer<'a> {
+    config: &'a SplatLayerConfig,
+    color: LinearRgba,
+    texture: Option<&'a Image>,
+}
+
+impl<'a> SplatLayer<'a> {
+    fn new(config: &'a SplatLayerConfig, texture: Option<&'a Image>) -> Self {
+        let color = config
+            .color
+            .as_deref()
+            .and_then(parse_color)
+            .map_or(LinearRgba::WHITE, |color| {
+                Color::srgb_u8(color[0], color[1], color[2]).to_linear()
+            });
+        Self {
+            config,
+            color,
+            texture,
+        }
+    }
+
+    fn color_at(&self, uv: Vec2) -> LinearRgba {
+        let Some(texture) = self.texture else {
+            return self.color;
+        };
+        let uv = (uv * self.config.tiling.unwrap_or(1.0)).fract_gl();
+        let x = ((uv.x * texture.width() as f32) as u32).min(texture.width().saturating_sub(1));
+        let y = ((uv.y * texture.height() as f32) as u32).min(texture.height().saturating_sub(1));
+        let texel = texture
+            .get_color_at(x, y)
+            .map_or(LinearRgba::WHITE, |texel| texel.to_linear());
+        LinearRgba::from_vec4(texel.to_vec4() * self.color.to_vec4())
+    }
+
+    /// How much of the layer shows at `height` and `slope`, from 0 to 1.
+    fn weight(&self, height: f32, slope: f32) -> f32 {
+        let config = self.config;
+        range_weight(
+            height,
+            config.min_height,
+            config.max_height,
+            config.height_blend.unwrap_or(0.0),
+        ) * range_weight(
+            slope,
+            config.min_slope,
+            config.max_slope,
+            config.slope_blend.unwrap_or(0.0),
+        )
+    }
+}
+
+/// 1 inside `min..=max` and 0 outside, fading smoothly over `blend` centered on each bound.
+fn range_weight(value: f32, min: Option<f32>, max: Option<f32>, blend: f32) -> f32 {
+    let fade = |inside: f32| {
+        if blend <= 0.0 {
+            return if inside >= 0.0 { 1.0 } else { 0.0 };
+        }
+        let t = (inside / blend + 0.5).clamp(0.0, 1.0);
+        t * t * (3.0 - 2.0 * t)
+    };
+    min.map_or(1.0, |min| fade(value - min)) * max.map_or(1.0, |max| fade(max - value))
+}
@@ -0,0 +1,262 @@
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use bevy::{
+    asset::AssetPlugin,
+    gltf::GltfPlugin,
+    image::{CompressedImageFormatSupport, CompressedImageFormats, ImageLoader, ImagePlugin},
+    input::InputPlugin,
+    log::{tracing_subscriber, Level, LogPlugin},
+    mesh::MeshPlugin,
+    prelude::*,
+    scene::ScenePlugin as BevyScenePlugin,
+};
+use bevy_rapier3d::prelude::*;
+use serde::Serialize;
+
+use crate::app_config::AppConfig;
+use crate::scenes::config::{ActiveScene, EulerOrder};
+use crate::scenes::diagnostics::{format_report, ConfigDiagnostics};
+
+use super::entities::{PendingModel, PendingTerrain};
+use super::plugin::{SceneConfigHandles, ScenePlugin};
+use super::world::PendingPlacements;
+
+/// Length of one physics step, the rate the windowed game runs at.
+pub const SIMULATION_STEP_SECONDS: f32 = 1.0 / 60.0;
+
+/// How long the scene's templates may take to load before the run is abandoned.
+const LOAD_TIMEOUT: Duration = Duration::from_secs(30);
+const LOAD_POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// State of a scene after a headless run.
+#[derive(Debug, Serialize)]
+pub struct SimulationReport {
+    pub scene: String,
+    pub steps: u32,
+    pub step_seconds: f32,
+    /// Every named entity, sorted by name.
+    pub entities: Vec<EntityState>,
+}
+
+/// World-space pose of a named entity, with rotations in degrees like the scene files.
+#[derive(Debug, Serialize)]
+pub struct EntityState {
+    pub name: String,
+    pub position: [f32; 3],
+    /// Turns about X, Y and Z, made in the default `yxz` order as scene files read them.
+    pub rotation: [f32; 3],
+    pub scale: [f32; 3],
+    /// `None` for entities without a rigid body.
+    pub linear_velocity: Option<[f32; 3]>,
+    /// Degrees per second about X, Y and Z.
+    pub angular_velocity: Option<[f32; 3]>,
+}
+
+/// Loads `scene` without a window or GPU, waits for every placement to spawn, then
+/// advances physics by `steps` fixed steps and reports where everything ended up. Config
+/// errors fail the run instead of being simulated around.
+pub fn simulate_scene(scene: &str, steps: u32) -> Result<SimulationReport, String> {
+    let mut app = headless_app(scene, AssetPlugin::default());
+    app.add_plugins(LogPlugin {
+        level: Level::WARN,
+        // Keeps stdout free for the report.
+        fmt_layer: |_| {
+            Some(Box::new(
+                tracing_subscriber::fmt::Layer::default().with_writer(std::io::stderr),
+            ))
+        },
+        ..default()
+    });
+    load_headless_scene(&mut app)?;
+
+    let diagnostics = app.world().resource::<ConfigDiagnostics>();
+    if diagnostics.error_count() > 0 {
+        return Err(format!(
+            "Scene '{scene}' has config errors: {}",
+            format_report(&diagnostics.entries)
+        ));
+    }
+
+    step_headless_scene(&mut app, steps);
+    Ok(SimulationReport {
+        scene: scene.to_string(),
+        steps,
+        step_seconds: SIMULATION_STEP_SECONDS,
+        entities: entity_states(app.world_mut()),
+    })
+}
+
+/// An app that runs `scene` without a window or GPU, in dev mode, with physics stepping
+/// by `SIMULATION_STEP_SECONDS`. Scene files are read relative to the working directory
+/// and templates through `assets`. Nothing is logged unless a `LogPlugin` is added.
+pub fn headless_app(scene: &str, assets: AssetPlugin) -> App {
+    let mut app = App::new();
+    app.insert_resource(AppConfig {
+        scene: scene.to_string(),
+        msaa_samples: None,
+        ..default()
+    });
+    app.add_plugins((
+        MinimalPlugins,
+        assets,
+        ImagePlugin::default(),
+        MeshPlugin,
+        TransformPlugin,
+        InputPlugin,
+        BevyScenePlugin,
+    ));
+    app.init_asset::<StandardMaterial>();
+    // Without a GPU there are no compressed texture formats to offer models. The render
+    // plugin would register the image loader, which terrain heightmaps are read through.
+    app.insert_resource(CompressedImageFormatSupport(CompressedImageFormats::NONE));
+    app.register_asset_loader(ImageLoader::new(CompressedImageFormats::NONE));
+    app.add_plugins(GltfPlugin::default());
+    app.add_plugins(RapierPhysicsPlugin::<NoUserData>::default());
+    app.insert_resource(TimestepMode::Fixed {
+        dt: SIMULATION_STEP_SECONDS,
+        substeps: 1,
+    });
+    app.add_plugins(ScenePlugin::new(scene).headless());
+    app.add_systems(PostUpdate, track_velocities.before(PhysicsSet::SyncBackend));
+    app
+}
+
+/// Finishes building an app from `headless_app` and updates it until every placement,
+/// model and terrain has spawned. Physics is left paused, so nothing has moved yet when this returns.
+pub fn load_headless_scene(app: &mut App) -> Result<(), String> {
+    app.finish();
+    app.cleanup();
+
+    // Physics holds still until everything is in, so placements whose templates load late
+    // start from the same state as the rest. The scene files and templates load
+    // asynchronously, so nothing has spawned by the end of the first update.
+    app.update();
+    set_physics_active(app, false);
+    let started = Instant::now();
+    while is_loading(app) {
+        // Taken before updating, so loads that finished during a long update, such as one
+        // building convex decompositions, are still picked up by the update after it.
+        let timed_out = started.elapsed() > LOAD_TIMEOUT;
+        thread::sleep(LOAD_POLL_INTERVAL);
+        app.update();
+        if timed_out && is_loading(app) {
+            let scene = &app.world().resource::<ActiveScene>().name;
+            return Err(format!(
+                "Scene '{scene}' did not finish loading within {}s.",
+                LOAD_TIMEOUT.as_secs()
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Resumes physics and advances it by `steps` fixed steps.
+pub fn step_headless_scene(app: &mut App, steps: u32) {
+    set_physics_active(app, true);
+    for _ in 0..steps {
+        app.update();
+    }
+}
+
+fn is_loading(app: &mut App) -> bool {
+    app.world().contains_resource::<SceneConfigHandles>()
+        || !app.world().resource::<PendingPlacements>().is_empty()
+        || has_pending::<PendingModel>(app)
+        || has_pending::<PendingTerrain>(app)
+}
+
+fn has_pending<T: Component>(app: &mut App) -> bool {
+    let world = app.world_mut();
+    world.query_filtered::<(), With<T>>().iter(world).next().is_some()
+}
+
+fn set_physics_active(app: &mut App, active: bool) {
+    let world = app.world_mut();
+    let mut contexts =
+        world.query_filtered::<&mut RapierConfiguration, With<DefaultRapierContext>>();
+    for mut config in contexts.iter_mut(world) {
+        config.physics_pipeline_active = active;
+    }
+}
+
+/// Gives every rigid body a `Velocity` for Rapier to write back into. Runs before the
+/// bodies are created, so the zero velocity it starts from is the one they have anyway.
+fn track_velocities(
+    mut commands: Commands,
+    bodies: Query<Entity, (With<RigidBody>, Without<Velocity>)>,
+) {
+    for entity in &bodies {
+        commands.entity(entity).insert(Velocity::default());
+    }
+}
+
+fn entity_states(world: &mut World) -> Vec<EntityState> {
+    let mut query = world.query::<(&Name, &GlobalTransform, Option<&Velocity>)>();
+    let mut states: Vec<EntityState> = query
+        .iter(world)
+        .map(|(name, transform, velocity)| {
+            let (scale, rotation, translation) = transform.to_scale_rotation_translation();
+            EntityState {
+                name: name.to_string(),
+                position: translation.to_array(),
+                rotation: EulerOrder::default().angles(rotation).to_array(),
+                scale: scale.to_array(),
+                linear_velocity: velocity.map(|velocity| velocity.linvel.to_array()),
+                angular_velocity: velocity
+                    .map(|velocity| velocity.angvel.to_array().map(f32::to_degrees)),
+            }
+        })
+        .collect();
+    states.sort_by(|a, b| a.name.cmp(&b.name));
+    states
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entity_states_are_sorted_by_name_with_velocities_of_bodies() {
+        let mut world = World::new();
+        world.spawn((
+            Name::new("wall"),
+            GlobalTransform::from(Transform::from_xyz(0.0, 1.0, -4.0)),
+        ));
+        world.spawn((
+            Name::new("ball"),
+            GlobalTransform::from(Transform::from_xyz(2.0, 0.5, 0.0)),
+            Velocity {
+                linvel: Vec3::new(0.0, -1.0, 0.0),
+                angvel: Vec3::new(0.0, std::f32::consts::PI, 0.0),
+            },
+        ));
+        world.spawn(GlobalTransform::default());
+
+        let states = entity_states(&mut world);
+        let names: Vec<_> = states.iter().map(|state| state.name.as_str()).collect();
+        assert_eq!(names, ["ball", "wall"]);
+        assert_eq!(states[0].position, [2.0, 0.5, 0.0]);
+        assert_eq!(states[0].linear_velocity, Some([0.0, -1.0, 0.0]));
+        assert_eq!(states[0].angular_velocity, Some([0.0, 180.0, 0.0]));
+        assert_eq!(states[1].linear_velocity, None);
+        assert_eq!(states[1].scale, [1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn entity_rotations_use_the_scene_file_euler_order() {
+        let mut world = World::new();
+        let degrees = Vec3::new(20.0, 45.0, -10.0);
+        let rotation = EulerOrder::default().to_quat(degrees);
+        world.spawn((
+            Name::new("crate"),
+            GlobalTransform::from(Transform::from_rotation(rotation)),
+        ));
+
+        let states = entity_states(&mut world);
+        let reported = Vec3::from_array(states[0].rotation);
+        assert!(reported.abs_diff_eq(degrees, 1e-3), "{reported}");
+    }
+}
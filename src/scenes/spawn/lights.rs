@@ -4,6 +4,12 @@ use bevy::{
 };
 
 use crate::scenes::config::{LightEntry, LightKind};
+use crate::scenes::manager::SceneEntity;
+
+/// Marks lights spawned from the `[[lights]]` list in `world.toml`.
+#[derive(Component)]
+#[require(SceneEntity)]
+pub struct SceneWorldLight;
 
 pub(super) fn spawn_lights(lights: &[LightEntry], commands: &mut Commands) {
     let mut ambient_set = false;
@@ -25,6 +31,7 @@ pub(super) fn spawn_lights(lights: &[LightEntry], commands: &mut Commands) {
             }
             LightKind::Point => {
                 commands.spawn((
+                    SceneWorldLight,
                     PointLight {
                         intensity: light.intensity,
                         range: light.range.unwrap_or(20.0),
@@ -53,6 +60,7 @@ pub(super) fn spawn_lights(lights: &[LightEntry], commands: &mut Commands) {
                         );
                 }
                 commands.spawn((
+                    SceneWorldLight,
                     DirectionalLight {
                         illuminance: light.intensity,
                         shadows_enabled: light.shadows,
@@ -1,10 +1,19 @@
 mod entities;
 mod combo;
+mod headless;
 mod lights;
 mod logging;
 mod plugin;
+mod reload;
+mod save;
 mod sun;
 mod world;
 mod overlay;
 
+pub use headless::{
+    headless_app, load_headless_scene, simulate_scene, step_headless_scene, EntityState,
+    SimulationReport, SIMULATION_STEP_SECONDS,
+};
+pub use entities::TextLabel;
 pub use plugin::ScenePlugin;
+pub use world::ScenePlacement;
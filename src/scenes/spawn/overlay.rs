@@ -3,11 +3,15 @@ use bevy::{
     ui::{PositionType, UiTransform, Val, Val2},
 };
 
+use crate::app_config::AppConfig;
 use crate::scenes::config::{parse_color, OverlayAnchor, OverlayElement, TextOverlay};
+use crate::scenes::diagnostics::DiagnosticCollector;
 use crate::scenes::input::SceneInputConfig;
 use crate::scenes::loaders::load_overlay_config;
+use crate::scenes::manager::SceneEntity;
 
 #[derive(Component)]
+#[require(SceneEntity)]
 pub struct OverlayTag {
     pub name: String,
 }
@@ -16,14 +20,23 @@ pub fn spawn_overlays_from_config(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     input: Option<Res<SceneInputConfig>>,
+    app_config: Res<AppConfig>,
+    diagnostics: Res<DiagnosticCollector>,
 ) {
-    let names: Vec<String> = input
-        .as_ref()
-        .map(|cfg| cfg.overlays.iter().map(|o| o.name.clone()).collect())
-        .unwrap_or_else(|| vec!["debug".to_string()]);
+    let dev = app_config.is_dev();
+    let names: Vec<String> = match input.as_ref() {
+        Some(cfg) => cfg
+            .overlays
+            .iter()
+            .filter(|o| dev || !o.dev_only)
+            .map(|o| o.name.clone())
+            .collect(),
+        None if dev => vec!["debug".to_string()],
+        None => Vec::new(),
+    };
 
     for name in names {
-        let overlay = load_overlay_config(&name);
+        let overlay = load_overlay_config(&name, &diagnostics);
         for element in overlay.elements {
             match element {
                 OverlayElement::Text(text) => {
@@ -1,4 +1,5 @@
 use bevy::{
+    asset::{AssetLoadError, LoadState},
     log::{info, warn},
     prelude::*,
 };
@@ -13,7 +14,16 @@ use bevy_rapier3d::prelude::{DefaultRapierContext, RapierConfiguration};
 use crate::app_config::AppConfig;
 use crate::scenes::{
     bounds::{despawn_out_of_bounds, SceneBounds},
-    config::{ActiveScene, BloomConfig, FogConfig, FogFalloffConfig, InputConfig, RenderConfig},
+    config::{
+        parse_tonemapping, scene_asset_path, ActiveScene, BloomConfig, CameraConfig, FogConfig,
+        FogFalloffConfig, InputConfig, RenderConfig, SkyboxConfig, ASSET_ROOT,
+    },
+    diagnostics::{
+        collect_config_diagnostics, refresh_diagnostics_overlay, ConfigDiagnostic,
+        ConfigDiagnostics, DiagnosticCollector,
+    },
+    entities::EntitiesConfig,
+    format::{file_stem, scene_file},
     input::{
         apply_camera_input, apply_fov_action, apply_shoot_action, apply_sprint_toggle,
         apply_zoom_action, resolve_camera_input_config, resolve_overlay_toggles, FovBinding,
@@ -21,90 +31,353 @@ use crate::scenes::{
         SceneZoomConfig, SprintState, ZoomState,
     },
     loaders::{
-        load_entity_template_from_path, load_entities_config, load_input_config,
-        load_shoot_action_config, load_sprint_action_config, load_world_config,
-        load_zoom_action_config,
+        load_entity_template_from_path, load_shoot_action_config, load_sprint_action_config,
+        load_zoom_action_config, ENTITIES_FILE, INPUT_FILE, WORLD_FILE,
     },
+    manager::{SceneEntity, SceneManager},
+    templates::{TemplateAssetsPlugin, TemplateCache},
     world::WorldConfig,
 };
 
+use super::entities::{
+    animate_sprites, face_camera, fit_text_labels, park_label_cameras, release_label_layers,
+    scale_text_labels, spawn_label_targets, spawn_loaded_models, spawn_loaded_terrains,
+    update_label_text, LabelLayers, SplatTextures,
+};
 use super::lights::spawn_lights;
 use super::logging::{log_camera, log_lights};
 use super::overlay::{spawn_overlays_from_config, OverlayTag};
+use super::reload::{
+    poll_scene_files, reload_scene_changes, respawn_reloaded_templates, SceneFileWatcher,
+    SceneSnapshot,
+};
+use super::save::save_scene_layout;
 use super::sun::spawn_sun;
-use super::world::spawn_world_entities;
+use super::world::{
+    merge_child_bodies, queue_world_entities, record_spawned_poses, spawn_pending_placements,
+    PendingPlacements,
+};
 
 pub struct ScenePlugin {
-    scene: &'static str,
+    scene: String,
+    headless: bool,
 }
 
 impl ScenePlugin {
-    pub const fn new(scene: &'static str) -> Self {
-        Self { scene }
+    pub fn new(scene: impl Into<String>) -> Self {
+        Self {
+            scene: scene.into(),
+            headless: false,
+        }
+    }
+
+    /// Runs the scene without a window or GPU: cameras, the sun, overlays, input, hot
+    /// reload and the drawing of text labels are left out, and only placements, lights,
+    /// physics and the scene resources remain.
+    pub fn headless(mut self) -> Self {
+        self.headless = true;
+        self
     }
 }
 
+/// Present when the scene runs through `ScenePlugin::headless`.
+#[derive(Resource)]
+pub(super) struct Headless;
+
+/// The active scene's world, entities and input files, requested from the asset server
+/// when the scene starts. `SceneSetup` runs once they have all loaded or failed.
+#[derive(Resource)]
+pub(super) struct SceneConfigHandles {
+    world: Handle<WorldConfig>,
+    entities: Handle<EntitiesConfig>,
+    input: Handle<InputConfig>,
+}
+
+/// `setup_scene` and the systems reporting on what it spawned, run once per scene start.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+struct SceneSetup;
+
 impl Plugin for ScenePlugin {
     fn build(&self, app: &mut App) {
+        let dev = app
+            .world()
+            .get_resource::<AppConfig>()
+            .is_none_or(AppConfig::is_dev);
+
         app.insert_resource(ActiveScene {
-            name: self.scene.to_string(),
+            name: self.scene.clone(),
         });
-        app.add_systems(Startup, setup_scene);
+        app.init_resource::<SceneManager>();
+        app.init_resource::<DiagnosticCollector>();
+        app.init_resource::<ConfigDiagnostics>();
+        app.init_resource::<PendingPlacements>();
+        app.init_resource::<SplatTextures>();
+        app.add_plugins(TemplateAssetsPlugin);
+        app.add_systems(Startup, request_scene_configs);
+        app.configure_sets(Update, SceneSetup.run_if(scene_configs_loaded));
+        app.add_systems(Update, setup_scene.in_set(SceneSetup));
+        app.add_systems(Update, (spawn_pending_placements, record_spawned_poses).chain());
+        app.add_systems(Update, spawn_loaded_models.after(spawn_pending_placements));
+        app.add_systems(Update, spawn_loaded_terrains.after(spawn_pending_placements));
+        app.add_systems(
+            Update,
+            merge_child_bodies
+                .after(spawn_loaded_models)
+                .after(spawn_loaded_terrains)
+                .after(record_spawned_poses),
+        );
+        app.add_systems(Update, despawn_out_of_bounds);
+        app.add_systems(Update, animate_sprites);
+        app.add_systems(PostUpdate, face_camera.before(TransformSystems::Propagate));
+        app.add_systems(Update, log_lights.in_set(SceneSetup).after(setup_scene));
+        app.add_systems(Last, collect_config_diagnostics);
+        if self.headless {
+            app.insert_resource(Headless);
+            return;
+        }
+
         app.add_systems(Update, apply_camera_input);
         app.add_systems(Update, apply_fov_action);
         app.add_systems(Update, apply_shoot_action);
         app.add_systems(Update, apply_sprint_toggle);
         app.add_systems(Update, apply_zoom_action);
-        app.add_systems(Update, despawn_out_of_bounds);
+        app.init_resource::<LabelLayers>();
+        app.add_systems(
+            Update,
+            (
+                release_label_layers,
+                spawn_label_targets,
+                update_label_text,
+                park_label_cameras,
+                fit_text_labels,
+            )
+                .chain(),
+        );
+        app.add_systems(
+            PostUpdate,
+            scale_text_labels.before(TransformSystems::Propagate),
+        );
         app.add_systems(
-            PostStartup,
-            (log_lights, log_camera, spawn_overlays_from_config).after(setup_scene),
+            Update,
+            (log_camera, spawn_overlays_from_config)
+                .in_set(SceneSetup)
+                .after(setup_scene),
         );
         app.add_systems(Update, toggle_overlays);
+        if dev {
+            app.add_systems(
+                Update,
+                (poll_scene_files, reload_scene_changes, respawn_reloaded_templates).chain(),
+            );
+            // Saving writes over the scene files, which a shipped build must leave alone.
+            app.add_systems(Update, save_scene_layout);
+        }
+        app.add_systems(Update, (cycle_scenes, apply_scene_switch).chain());
+        app.add_systems(Last, refresh_diagnostics_overlay.after(collect_config_diagnostics));
     }
 }
 
+fn cycle_scenes(
+    keys: Res<ButtonInput<KeyCode>>,
+    config: Option<Res<SceneInputConfig>>,
+    active_scene: Res<ActiveScene>,
+    mut manager: ResMut<SceneManager>,
+) {
+    let Some(key) = config.and_then(|config| config.next_scene) else {
+        return;
+    };
+    if !keys.just_pressed(key) {
+        return;
+    }
+    let scenes = SceneManager::available_scenes();
+    let next = scenes
+        .iter()
+        .position(|scene| *scene == active_scene.name)
+        .map(|index| (index + 1) % scenes.len())
+        .unwrap_or(0);
+    if let Some(scene) = scenes.get(next) {
+        manager.switch_to(scene.clone());
+    }
+}
+
+/// Unloads everything the active scene created and runs the full scene setup again for
+/// the scene requested through `SceneManager`.
+fn apply_scene_switch(
+    mut manager: ResMut<SceneManager>,
+    active_scene: Res<ActiveScene>,
+    mut commands: Commands,
+    scene_entities: Query<Entity, With<SceneEntity>>,
+    mut rapier_config: Query<&mut RapierConfiguration, With<DefaultRapierContext>>,
+) {
+    let Some(scene) = manager.take_pending() else {
+        return;
+    };
+    info!("Switching scene '{}' -> '{}'.", active_scene.name, scene);
+
+    for entity in &scene_entities {
+        commands.entity(entity).try_despawn();
+    }
+
+    commands.remove_resource::<SceneInputConfig>();
+    commands.remove_resource::<SceneShootConfig>();
+    commands.remove_resource::<SceneSprintConfig>();
+    commands.remove_resource::<SprintState>();
+    commands.remove_resource::<SceneZoomConfig>();
+    commands.remove_resource::<ZoomState>();
+    commands.remove_resource::<SceneFovConfig>();
+    commands.remove_resource::<SceneBounds>();
+    commands.remove_resource::<SceneSnapshot>();
+    commands.remove_resource::<SceneFileWatcher>();
+    commands.insert_resource(ConfigDiagnostics::default());
+    commands.insert_resource(TemplateCache::default());
+    commands.insert_resource(PendingPlacements::default());
+    commands.insert_resource(SplatTextures::default());
+    commands.insert_resource(ClearColor::default());
+    commands.insert_resource(AmbientLight::default());
+    if let Ok(mut config) = rapier_config.single_mut() {
+        config.gravity = Vec3::Y * -9.81;
+    }
+
+    commands.insert_resource(ActiveScene { name: scene });
+    commands.run_system_cached(request_scene_configs);
+}
+
+fn request_scene_configs(
+    active_scene: Res<ActiveScene>,
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+) {
+    let scene = &active_scene.name;
+    let path = |file: &str| scene_asset_path(scene, &scene_file(scene, file));
+    commands.insert_resource(SceneConfigHandles {
+        world: asset_server.load(path(WORLD_FILE)),
+        entities: asset_server.load(path(ENTITIES_FILE)),
+        input: asset_server.load(path(INPUT_FILE)),
+    });
+}
+
+fn scene_configs_loaded(
+    handles: Option<Res<SceneConfigHandles>>,
+    asset_server: Res<AssetServer>,
+) -> bool {
+    let Some(handles) = handles else {
+        return false;
+    };
+    [
+        handles.world.id().untyped(),
+        handles.entities.id().untyped(),
+        handles.input.id().untyped(),
+    ]
+    .into_iter()
+    .all(|id| matches!(asset_server.load_state(id), LoadState::Loaded | LoadState::Failed(_)))
+}
+
+/// Takes a scene file's config out of `assets`, or `None` when it failed to load. Files
+/// the loader rejected were reported with the details; a file the loader never got, such
+/// as a missing one, is reported here.
+fn take_config<A: Asset>(
+    handle: &Handle<A>,
+    assets: &mut Assets<A>,
+    asset_server: &AssetServer,
+    diagnostics: &DiagnosticCollector,
+    fallback: &str,
+) -> Option<A> {
+    if let LoadState::Failed(err) = asset_server.load_state(handle.id()) {
+        if !matches!(*err, AssetLoadError::AssetLoaderError(_)) {
+            let path = handle.path().map_or_else(String::new, |path| {
+                format!("{ASSET_ROOT}/{}", path.path().display())
+            });
+            let message = format!("Failed to read: {err}. {fallback}");
+            diagnostics.report(ConfigDiagnostic::error(path, message));
+        }
+        return None;
+    }
+    assets.remove(handle)
+}
+
 fn setup_scene(
+    handles: Res<SceneConfigHandles>,
+    asset_server: Res<AssetServer>,
+    mut world_configs: ResMut<Assets<WorldConfig>>,
+    mut entities_configs: ResMut<Assets<EntitiesConfig>>,
+    mut input_configs: ResMut<Assets<InputConfig>>,
     active_scene: Res<ActiveScene>,
     app_config: Res<AppConfig>,
+    diagnostics: Res<DiagnosticCollector>,
+    headless: Option<Res<Headless>>,
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
-    asset_server: Res<AssetServer>,
+    mut pending_placements: ResMut<PendingPlacements>,
     mut rapier_config: Query<&mut RapierConfiguration, With<DefaultRapierContext>>,
 ) {
     info!(
-        "Bootstrapping scene '{}' with inspector overlay enabled.",
-        active_scene.name
+        "Bootstrapping scene '{}' in {:?} mode.",
+        active_scene.name, app_config.mode
     );
 
-    let input_config: InputConfig = load_input_config(&active_scene.name);
+    commands.remove_resource::<SceneConfigHandles>();
+    let input_config: InputConfig = take_config(
+        &handles.input,
+        &mut input_configs,
+        &asset_server,
+        &diagnostics,
+        "Falling back to defaults.",
+    )
+    .unwrap_or_default();
     let camera_input =
         resolve_camera_input_config(&input_config.camera.movement, &input_config.camera.rotation);
     commands.insert_resource(SceneInputConfig {
         camera: camera_input,
         overlays: resolve_overlay_toggles(&input_config.overlays),
+        next_scene: crate::scenes::input::resolve_key_or_warn(
+            &input_config.system.next_scene,
+            "next scene",
+        ),
+        save_scene: crate::scenes::input::resolve_key_or_warn(
+            &input_config.system.save_scene,
+            "save scene",
+        ),
     });
 
-    let world_config: WorldConfig = load_world_config(&active_scene.name);
-    let entities_config = load_entities_config(&active_scene.name);
+    let world_config: WorldConfig = take_config(
+        &handles.world,
+        &mut world_configs,
+        &asset_server,
+        &diagnostics,
+        "Falling back to empty world.",
+    )
+    .unwrap_or_default();
+    let entities_config = take_config(
+        &handles.entities,
+        &mut entities_configs,
+        &asset_server,
+        &diagnostics,
+        "Falling back to empty entities.",
+    )
+    .unwrap_or_default();
 
     commands.insert_resource(SceneBounds::from(world_config.bounds.clone()));
+    if app_config.is_dev() && headless.is_none() {
+        commands.insert_resource(SceneSnapshot::capture(&active_scene.name, &diagnostics));
+        commands.insert_resource(SceneFileWatcher::new(&active_scene.name));
+    }
 
     if let Some(action_binding) = input_config
         .actions
         .iter()
-        .find(|action| action.action.ends_with("shoot-balls.toml"))
+        .find(|action| file_stem(&action.action).ends_with("shoot-balls"))
     {
         if let Some(trigger) =
             crate::scenes::input::resolve_mouse_button_or_warn(&action_binding.mouse, "shoot")
         {
             if let Some(action) =
-                load_shoot_action_config(&active_scene.name, &action_binding.action)
+                load_shoot_action_config(&active_scene.name, &action_binding.action, &diagnostics)
             {
                 let Some(projectile) = load_entity_template_from_path(
                     &active_scene.name,
-                    "entities/sphere.3D.toml",
+                    crate::scenes::config::PROJECTILE_TEMPLATE,
+                    &diagnostics,
                 ) else {
                     warn!("Projectile template missing; shoot action disabled.");
                     return;
@@ -139,13 +412,13 @@ fn setup_scene(
     if let Some(action_binding) = input_config
         .actions
         .iter()
-        .find(|action| action.action.ends_with("sprint.toml"))
+        .find(|action| file_stem(&action.action).ends_with("sprint"))
     {
         if let Some(trigger) =
             crate::scenes::input::resolve_key_or_warn(&action_binding.key, "sprint")
         {
             if let Some(action) =
-                load_sprint_action_config(&active_scene.name, &action_binding.action)
+                load_sprint_action_config(&active_scene.name, &action_binding.action, &diagnostics)
             {
                 commands.insert_resource(SceneSprintConfig { action, trigger });
                 commands.insert_resource(SprintState::default());
@@ -156,13 +429,13 @@ fn setup_scene(
     if let Some(action_binding) = input_config
         .actions
         .iter()
-        .find(|action| action.action.ends_with("zoom.toml"))
+        .find(|action| file_stem(&action.action).ends_with("zoom"))
     {
         if let Some(trigger) =
             crate::scenes::input::resolve_key_or_warn(&action_binding.key, "zoom")
         {
             if let Some(action) =
-                load_zoom_action_config(&active_scene.name, &action_binding.action)
+                load_zoom_action_config(&active_scene.name, &action_binding.action, &diagnostics)
             {
                 commands.insert_resource(SceneZoomConfig { action, trigger });
                 commands.insert_resource(ZoomState::default());
@@ -174,7 +447,7 @@ fn setup_scene(
     for action_binding in input_config
         .actions
         .iter()
-        .filter(|action| action.action.ends_with("fov.toml"))
+        .filter(|action| file_stem(&action.action).ends_with("fov"))
     {
         if let Some(trigger) =
             crate::scenes::input::resolve_key_or_warn(&action_binding.key, "fov")
@@ -199,32 +472,8 @@ fn setup_scene(
         });
     }
 
-    spawn_world_entities(
-        &entities_config,
-        &mut commands,
-        &mut meshes,
-        &mut materials,
-        &asset_server,
-        &active_scene,
-    );
-
-    // sun derived from world config
-    spawn_sun(
-        world_config.sun.as_ref(),
-        &mut commands,
-        &mut meshes,
-        &mut materials,
-        &active_scene,
-    );
-
-    // skybox clear color
-    if let Some(skybox) = world_config.skybox.clone() {
-        if let Some(rgb) = crate::scenes::config::parse_color(&skybox.color) {
-            commands.insert_resource(ClearColor(Color::srgb_u8(rgb[0], rgb[1], rgb[2])));
-        } else {
-            warn!("Failed to parse skybox color '{}'; leaving default clear color", skybox.color);
-        }
-    }
+    // Spawned by `spawn_pending_placements` once their templates have loaded.
+    queue_world_entities(entities_config, &mut pending_placements);
 
     if let Some(gravity) = world_config.gravity.as_ref() {
         if let Ok(mut config) = rapier_config.single_mut() {
@@ -235,28 +484,28 @@ fn setup_scene(
     // lights
     spawn_lights(&world_config.lights, &mut commands);
 
+    // skybox clear color
+    apply_skybox(world_config.skybox.as_ref(), &mut commands);
+
+    if headless.is_some() {
+        return;
+    }
+
+    // sun derived from world config
+    spawn_sun(
+        world_config.sun.as_ref(),
+        &mut commands,
+        &mut meshes,
+        &mut materials,
+        &active_scene,
+    );
+
     // camera
     let camera_components = (
         Name::new(world_config.camera.name.clone()),
         Camera3d::default(),
         SceneCamera,
-        Transform::from_xyz(
-            world_config.camera.transform.position.x,
-            world_config.camera.transform.position.y,
-            world_config.camera.transform.position.z,
-        )
-        .looking_at(
-            Vec3::new(
-                world_config.camera.transform.look_at.x,
-                world_config.camera.transform.look_at.y,
-                world_config.camera.transform.look_at.z,
-            ),
-            Vec3::new(
-                world_config.camera.transform.up.x,
-                world_config.camera.transform.up.y,
-                world_config.camera.transform.up.z,
-            ),
-        ),
+        camera_transform(&world_config.camera),
     );
     let mut camera = commands.spawn(camera_components);
     if let Some(msaa) = app_config.msaa_component() {
@@ -269,6 +518,7 @@ fn setup_scene(
     // UI overlay camera
     if let Some(msaa) = app_config.msaa_component() {
         commands.spawn((
+            SceneEntity,
             Camera2d::default(),
             Camera {
                 order: 1,
@@ -284,6 +534,7 @@ fn setup_scene(
         ));
     } else {
         commands.spawn((
+            SceneEntity,
             Camera2d::default(),
             Camera {
                 order: 1,
@@ -318,7 +569,49 @@ fn toggle_overlays(
     }
 }
 
-fn apply_render_settings(camera: &mut EntityCommands, render: &RenderConfig) {
+pub(super) fn camera_transform(camera: &CameraConfig) -> Transform {
+    Transform::from_xyz(
+        camera.transform.position.x,
+        camera.transform.position.y,
+        camera.transform.position.z,
+    )
+    .looking_at(
+        Vec3::new(
+            camera.transform.look_at.x,
+            camera.transform.look_at.y,
+            camera.transform.look_at.z,
+        ),
+        Vec3::new(
+            camera.transform.up.x,
+            camera.transform.up.y,
+            camera.transform.up.z,
+        ),
+    )
+}
+
+pub(super) fn apply_skybox(skybox: Option<&SkyboxConfig>, commands: &mut Commands) {
+    let Some(skybox) = skybox else {
+        return;
+    };
+    if let Some(rgb) = crate::scenes::config::parse_color(&skybox.color) {
+        commands.insert_resource(ClearColor(Color::srgb_u8(rgb[0], rgb[1], rgb[2])));
+    } else {
+        warn!("Failed to parse skybox color '{}'; leaving default clear color", skybox.color);
+    }
+}
+
+/// Strips everything `apply_render_settings` may have added so a changed `[render]`
+/// table can be applied from a clean camera.
+pub(super) fn clear_render_settings(camera: &mut EntityCommands) {
+    camera.remove::<(Hdr, Bloom, DistanceFog)>();
+    camera.insert((
+        Tonemapping::default(),
+        DebandDither::default(),
+        Exposure::default(),
+    ));
+}
+
+pub(super) fn apply_render_settings(camera: &mut EntityCommands, render: &RenderConfig) {
     let bloom_enabled = render.bloom.as_ref().is_some_and(|bloom| bloom.enabled);
     let wants_hdr = render.hdr.unwrap_or(false) || bloom_enabled;
     if wants_hdr {
@@ -366,23 +659,6 @@ fn apply_render_settings(camera: &mut EntityCommands, render: &RenderConfig) {
     }
 }
 
-fn parse_tonemapping(value: &str) -> Option<Tonemapping> {
-    let normalized = value.trim().to_ascii_lowercase().replace('-', "_");
-    match normalized.as_str() {
-        "none" => Some(Tonemapping::None),
-        "reinhard" => Some(Tonemapping::Reinhard),
-        "reinhard_luminance" => Some(Tonemapping::ReinhardLuminance),
-        "aces_fitted" => Some(Tonemapping::AcesFitted),
-        "agx" => Some(Tonemapping::AgX),
-        "somewhat_boring_display_transform" => {
-            Some(Tonemapping::SomewhatBoringDisplayTransform)
-        }
-        "tony_mc_mapface" => Some(Tonemapping::TonyMcMapface),
-        "blender_filmic" => Some(Tonemapping::BlenderFilmic),
-        _ => None,
-    }
-}
-
 fn resolve_bloom(config: &BloomConfig) -> Bloom {
     let mut bloom = match config
         .preset
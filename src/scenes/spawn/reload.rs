@@ -0,0 +1,481 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+    time::SystemTime,
+};
+
+use bevy::{
+    log::{info, warn},
+    prelude::*,
+};
+use bevy_rapier3d::prelude::{DefaultRapierContext, RapierConfiguration};
+
+use crate::scenes::{
+    bounds::SceneBounds,
+    config::{
+        action_config_path, scene_asset_path, ActiveScene, ComboTemplate, EntityTemplate,
+        SCENE_ROOT,
+    },
+    diagnostics::{ConfigDiagnostics, DiagnosticCollector},
+    entities::{is_combo_template, EntityPlacement},
+    expand::{included_files, template_ancestors},
+    format::{is_scene_file, scene_file},
+    input::SceneCamera,
+    loaders::{
+        load_combo_template_from_path, load_entities_config, load_scene_table, load_world_config,
+        try_load_entities_config, ENTITIES_FILE, WORLD_FILE,
+    },
+    templates::TemplateCache,
+};
+
+use super::lights::{spawn_lights, SceneWorldLight};
+use super::plugin::{apply_render_settings, apply_skybox, camera_transform, clear_render_settings};
+use super::sun::{spawn_sun, SceneSun};
+use super::world::{PendingPlacements, ScenePlacement};
+
+const POLL_INTERVAL_SECS: f32 = 0.5;
+const VARS_KEY: &str = "vars";
+
+/// Polls the active scene directory for modified scene files.
+#[derive(Resource)]
+pub struct SceneFileWatcher {
+    scene: String,
+    timer: Timer,
+    modified: HashMap<String, SystemTime>,
+    changed: HashSet<String>,
+}
+
+impl SceneFileWatcher {
+    pub fn new(scene: &str) -> Self {
+        Self {
+            scene: scene.to_string(),
+            timer: Timer::from_seconds(POLL_INTERVAL_SECS, TimerMode::Repeating),
+            modified: scan_scene_files(scene),
+            changed: HashSet::new(),
+        }
+    }
+}
+
+/// The scene files as last spawned, used to work out which parts of a changed file
+/// actually need respawning.
+#[derive(Resource, Default)]
+pub struct SceneSnapshot {
+    /// Raw `world.toml`, compared section by section.
+    world: toml::Table,
+    /// Loaded placements, with includes, arrays and scatters expanded, so a change anywhere
+    /// shows up at the indices it affects.
+    placements: Vec<PlacementSnapshot>,
+}
+
+struct PlacementSnapshot {
+    placement: EntityPlacement,
+    dependencies: Vec<String>,
+}
+
+impl SceneSnapshot {
+    pub fn capture(scene: &str, diagnostics: &DiagnosticCollector) -> Self {
+        let entities = load_entities_config(scene, diagnostics).entities;
+        Self {
+            world: load_scene_table(scene, WORLD_FILE).unwrap_or_default(),
+            placements: placement_snapshots(scene, entities, diagnostics),
+        }
+    }
+}
+
+pub(super) fn poll_scene_files(time: Res<Time>, watcher: Option<ResMut<SceneFileWatcher>>) {
+    let Some(mut watcher) = watcher else {
+        return;
+    };
+    if !watcher.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let current = scan_scene_files(&watcher.scene);
+    let mut changed: Vec<String> = current
+        .iter()
+        .filter(|(path, modified)| watcher.modified.get(*path) != Some(*modified))
+        .map(|(path, _)| path.clone())
+        .collect();
+    changed.extend(
+        watcher
+            .modified
+            .keys()
+            .filter(|path| !current.contains_key(*path))
+            .cloned(),
+    );
+    watcher.changed.extend(changed);
+    watcher.modified = current;
+}
+
+pub(super) fn reload_scene_changes(
+    watcher: Option<ResMut<SceneFileWatcher>>,
+    snapshot: Option<ResMut<SceneSnapshot>>,
+    active_scene: Res<ActiveScene>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    asset_server: Res<AssetServer>,
+    template_cache: Res<TemplateCache>,
+    mut pending: ResMut<PendingPlacements>,
+    mut rapier_config: Query<&mut RapierConfiguration, With<DefaultRapierContext>>,
+    placements: Query<(Entity, &ScenePlacement)>,
+    world_lights: Query<Entity, With<SceneWorldLight>>,
+    sun: Query<Entity, With<SceneSun>>,
+    mut cameras: Query<(Entity, &mut Transform, &mut Name), With<SceneCamera>>,
+    mut diagnostics: ResMut<ConfigDiagnostics>,
+    collector: Res<DiagnosticCollector>,
+) {
+    let (Some(mut watcher), Some(mut snapshot)) = (watcher, snapshot) else {
+        return;
+    };
+    if watcher.changed.is_empty() {
+        return;
+    }
+    let changed: HashSet<String> = watcher.changed.drain().collect();
+    info!(
+        "Scene '{}' files changed: {}",
+        active_scene.name,
+        changed.iter().cloned().collect::<Vec<_>>().join(", ")
+    );
+    // Changed files are loaded again below and report whatever is still wrong with them;
+    // entities.toml is re-read on every reload, so its template checks run again too.
+    let world_file = scene_file(&active_scene.name, WORLD_FILE);
+    let entities_file = scene_file(&active_scene.name, ENTITIES_FILE);
+    for file in changed.iter().chain([&entities_file]) {
+        diagnostics.forget_file(&action_config_path(&active_scene.name, file));
+    }
+
+    // Scene variables can feed any value in any file, so changing them reloads everything.
+    let mut vars_changed = false;
+    let current_world = load_scene_table(&active_scene.name, WORLD_FILE);
+    let world_includes_changed = included_files(&active_scene.name, &snapshot.world)
+        .into_iter()
+        .chain(
+            current_world
+                .iter()
+                .flat_map(|world| included_files(&active_scene.name, world)),
+        )
+        .any(|file| changed.contains(&file));
+    if changed.contains(&world_file) || world_includes_changed {
+        if let Some(world_table) = current_world {
+            let world_config = load_world_config(&active_scene.name, &collector);
+            vars_changed = snapshot.world.get(VARS_KEY) != world_table.get(VARS_KEY);
+            let section_changed =
+                |key: &str| vars_changed || snapshot.world.get(key) != world_table.get(key);
+
+            if section_changed("lights") || section_changed("include") || world_includes_changed {
+                for entity in &world_lights {
+                    commands.entity(entity).try_despawn();
+                }
+                spawn_lights(&world_config.lights, &mut commands);
+                info!("Reloaded lights for scene '{}'.", active_scene.name);
+            }
+            if section_changed("sun") {
+                for entity in &sun {
+                    commands.entity(entity).try_despawn();
+                }
+                spawn_sun(
+                    world_config.sun.as_ref(),
+                    &mut commands,
+                    &mut meshes,
+                    &mut materials,
+                    &active_scene,
+                );
+                info!("Reloaded sun for scene '{}'.", active_scene.name);
+            }
+            if section_changed("skybox") {
+                if world_config.skybox.is_some() {
+                    apply_skybox(world_config.skybox.as_ref(), &mut commands);
+                } else {
+                    commands.insert_resource(ClearColor::default());
+                }
+            }
+            if section_changed("bounds") {
+                commands.insert_resource(SceneBounds::from(world_config.bounds.clone()));
+            }
+            if section_changed("gravity") {
+                if let Ok(mut config) = rapier_config.single_mut() {
+                    config.gravity = world_config
+                        .gravity
+                        .as_ref()
+                        .map(|gravity| Vec3::new(gravity.x, gravity.y, gravity.z))
+                        .unwrap_or(Vec3::Y * -9.81);
+                }
+            }
+            if section_changed("camera") || section_changed("render") {
+                for (entity, mut transform, mut name) in &mut cameras {
+                    if section_changed("camera") {
+                        *transform = camera_transform(&world_config.camera);
+                        *name = Name::new(world_config.camera.name.clone());
+                    }
+                    if section_changed("render") {
+                        let mut camera = commands.entity(entity);
+                        clear_render_settings(&mut camera);
+                        if let Some(render) = world_config.render.as_ref() {
+                            apply_render_settings(&mut camera, render);
+                        }
+                    }
+                }
+                info!("Reloaded camera settings for scene '{}'.", active_scene.name);
+            }
+            snapshot.world = world_table;
+        } else {
+            warn!(
+                "Keeping previous world for scene '{}' until {} parses again.",
+                active_scene.name, world_file
+            );
+        }
+    }
+
+    // Cached templates are reloaded by the asset server; `respawn_reloaded_templates`
+    // picks up the placements using them once the new versions are in.
+    for template in template_cache.paths() {
+        let affected = vars_changed
+            || changed.contains(template)
+            || template_ancestors(&active_scene.name, template)
+                .iter()
+                .any(|ancestor| changed.contains(ancestor));
+        if affected {
+            asset_server.reload(scene_asset_path(&active_scene.name, template));
+        }
+    }
+
+    let Some(entities_config) = try_load_entities_config(&active_scene.name, &collector) else {
+        warn!(
+            "Keeping previous placements for scene '{}' until {} is valid again.",
+            active_scene.name, entities_file
+        );
+        return;
+    };
+    let new = &entities_config.entities;
+
+    let mut stale = Vec::new();
+    for index in 0..snapshot.placements.len().max(new.len()) {
+        let old = snapshot.placements.get(index);
+        let needs_respawn = match (old, new.get(index)) {
+            (Some(old), Some(new)) => vars_changed || &old.placement != new,
+            _ => true,
+        };
+        if needs_respawn {
+            stale.push(index);
+        }
+    }
+    add_stale_children(
+        &mut stale,
+        snapshot
+            .placements
+            .iter()
+            .map(|old| &old.placement)
+            .enumerate()
+            .chain(new.iter().enumerate()),
+    );
+    if stale.is_empty() {
+        return;
+    }
+
+    for (entity, placement) in &placements {
+        if stale.contains(&placement.index) {
+            commands.entity(entity).try_despawn();
+        }
+    }
+    for &index in &stale {
+        if let Some(placement) = entities_config.entities.get(index) {
+            pending.push(index, placement.clone());
+        }
+    }
+    info!(
+        "Respawned {} placement(s) in scene '{}'.",
+        stale.len(),
+        active_scene.name
+    );
+
+    snapshot.placements =
+        placement_snapshots(&active_scene.name, entities_config.entities, &collector);
+}
+
+/// Respawns the placements using a template the asset server has just reloaded, which
+/// `reload_scene_changes` asks for when a template or one of its parents changes.
+pub(super) fn respawn_reloaded_templates(
+    mut entity_events: MessageReader<AssetEvent<EntityTemplate>>,
+    mut combo_events: MessageReader<AssetEvent<ComboTemplate>>,
+    template_cache: Res<TemplateCache>,
+    snapshot: Option<Res<SceneSnapshot>>,
+    active_scene: Res<ActiveScene>,
+    placements: Query<(Entity, &ScenePlacement)>,
+    mut pending: ResMut<PendingPlacements>,
+    mut commands: Commands,
+    collector: Res<DiagnosticCollector>,
+) {
+    let mut reloaded = HashSet::new();
+    for event in entity_events.read() {
+        if let AssetEvent::Modified { id } = event {
+            reloaded.extend(template_cache.entity_path(*id).cloned());
+        }
+    }
+    for event in combo_events.read() {
+        if let AssetEvent::Modified { id } = event {
+            reloaded.extend(template_cache.combo_path(*id).cloned());
+        }
+    }
+    let Some(snapshot) = snapshot else {
+        return;
+    };
+    if reloaded.is_empty() {
+        return;
+    }
+
+    let mut stale: Vec<usize> = snapshot
+        .placements
+        .iter()
+        .enumerate()
+        .filter(|(_, placement)| {
+            placement
+                .dependencies
+                .iter()
+                .any(|dependency| reloaded.contains(dependency))
+        })
+        .map(|(index, _)| index)
+        .collect();
+    if stale.is_empty() {
+        return;
+    }
+    let entities_config = load_entities_config(&active_scene.name, &collector);
+    add_stale_children(
+        &mut stale,
+        snapshot
+            .placements
+            .iter()
+            .map(|old| &old.placement)
+            .enumerate()
+            .chain(entities_config.entities.iter().enumerate()),
+    );
+
+    for (entity, placement) in &placements {
+        if stale.contains(&placement.index) {
+            commands.entity(entity).try_despawn();
+        }
+    }
+    for &index in &stale {
+        if let Some(placement) = entities_config.entities.get(index) {
+            pending.push(index, placement.clone());
+        }
+    }
+    info!(
+        "Respawning {} placement(s) with reloaded templates in scene '{}'.",
+        stale.len(),
+        active_scene.name
+    );
+}
+
+fn placement_snapshots(
+    scene: &str,
+    placements: Vec<EntityPlacement>,
+    diagnostics: &DiagnosticCollector,
+) -> Vec<PlacementSnapshot> {
+    placements
+        .into_iter()
+        .map(|placement| PlacementSnapshot {
+            dependencies: placement_dependencies(scene, &placement, diagnostics),
+            placement,
+        })
+        .collect()
+}
+
+/// Scene-relative files a placement reads while spawning.
+fn placement_dependencies(
+    scene: &str,
+    placement: &EntityPlacement,
+    diagnostics: &DiagnosticCollector,
+) -> Vec<String> {
+    let mut dependencies = vec![placement.template.clone()];
+    if is_combo_template(&placement.template) {
+        let combo = load_combo_template_from_path(scene, &placement.template, diagnostics);
+        if let Some(combo) = combo {
+            for part in &combo.parts {
+                dependencies.push(part.template.clone());
+                dependencies.extend(template_ancestors(scene, &part.template));
+            }
+        }
+    } else {
+        dependencies.extend(template_ancestors(scene, &placement.template));
+    }
+    dependencies
+}
+
+fn scan_scene_files(scene: &str) -> HashMap<String, SystemTime> {
+    let root = Path::new(SCENE_ROOT).join(scene);
+    let mut files = HashMap::new();
+    scan_dir(&root, &root, &mut files);
+    files
+}
+
+fn scan_dir(root: &Path, dir: &Path, files: &mut HashMap<String, SystemTime>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            scan_dir(root, &path, files);
+            continue;
+        }
+        if !is_scene_file(&path) {
+            continue;
+        }
+        let Ok(modified) = entry.metadata().and_then(|meta| meta.modified()) else {
+            continue;
+        };
+        if let Ok(relative) = path.strip_prefix(root) {
+            let relative = relative
+                .components()
+                .map(|part| part.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            files.insert(relative, modified);
+        }
+    }
+}
+
+/// Adds to `stale` every placement under a stale parent, in either the old or the new
+/// `entities.toml`, since despawning a parent despawns its children with it.
+fn add_stale_children<'a>(
+    stale: &mut Vec<usize>,
+    placements: impl Iterator<Item = (usize, &'a EntityPlacement)>,
+) {
+    let links: Vec<(usize, usize)> = placements
+        .filter_map(|(index, placement)| Some((index, placement.parent_index?)))
+        .collect();
+    let mut added = true;
+    while added {
+        added = false;
+        for &(child, parent) in &links {
+            if stale.contains(&parent) && !stale.contains(&child) {
+                stale.push(child);
+                added = true;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scanned_scene_files_are_keyed_by_scene_relative_path() {
+        let root = std::env::temp_dir().join(format!("atmos-reload-scan-{}", std::process::id()));
+        fs::create_dir_all(root.join("entities")).unwrap();
+        for file in ["world.toml", "entities/crate.3D.json", "entities/notes.txt"] {
+            fs::write(root.join(file), "").unwrap();
+        }
+
+        let mut files = HashMap::new();
+        scan_dir(&root, &root, &mut files);
+        fs::remove_dir_all(&root).unwrap();
+
+        let mut paths: Vec<&str> = files.keys().map(String::as_str).collect();
+        paths.sort();
+        assert_eq!(paths, ["entities/crate.3D.json", "world.toml"]);
+    }
+}
@@ -0,0 +1,406 @@
+use std::fs;
+
+use bevy::{
+    log::{info, warn},
+    prelude::*,
+};
+use toml_edit::{DocumentMut, InlineTable, Item, TableLike, Value};
+
+use crate::scenes::{
+    config::{
+        action_config_path, ActiveScene, CubeRotationConfig, EntityTemplate, EulerOrder,
+        PositionConfig, ScaleConfig, TransformOverrides,
+    },
+    diagnostics::DiagnosticCollector,
+    entities::{is_combo_template, EntityPlacement},
+    format::{scene_file, SceneFormat},
+    input::SceneInputConfig,
+    loaders::{try_load_entities_config, ENTITIES_FILE},
+    migrate::migrate_text,
+    templates::SceneTemplates,
+};
+
+use super::world::{ScenePlacement, SpawnedPose};
+
+/// Saved values are rounded to this many decimal places so physics jitter stays out of
+/// diffs.
+const SAVE_DECIMALS: i32 = 4;
+
+/// A placement whose entities have moved since they spawned.
+struct MovedPlacement {
+    /// Index among the loaded placements, which start with the file's own entries.
+    index: usize,
+    /// The transform as loaded, with variables substituted.
+    loaded: TransformOverrides,
+    saved: TransformOverrides,
+}
+
+/// Writes where the active scene's moved placements are now into its entities file. Only
+/// the `transform` keys that changed are rewritten, in the file's own entries, so
+/// comments, variables, includes and array and scatter generators stay as written.
+/// Included placements and generated copies are left alone.
+pub(super) fn save_scene_layout(
+    keys: Res<ButtonInput<KeyCode>>,
+    config: Option<Res<SceneInputConfig>>,
+    active_scene: Res<ActiveScene>,
+    templates: SceneTemplates,
+    placements: Query<(&ScenePlacement, &SpawnedPose, &Transform)>,
+    diagnostics: Res<DiagnosticCollector>,
+) {
+    let Some(key) = config.and_then(|config| config.save_scene) else {
+        return;
+    };
+    if !keys.just_pressed(key) {
+        return;
+    }
+
+    let scene = &active_scene.name;
+    let Some(entities) = try_load_entities_config(scene, &diagnostics) else {
+        warn!("Not saving scene '{scene}' while its entities file has errors.");
+        return;
+    };
+
+    let mut moved = Vec::new();
+    for (placement, spawned, current) in &placements {
+        if !has_moved(&spawned.0, current) {
+            continue;
+        }
+        let Some(entry) = entities.entities.get(placement.index) else {
+            continue;
+        };
+        if entry.template != placement.template {
+            warn!(
+                "Placement {} in scene '{scene}' now uses '{}' instead of '{}'; keeping it as \
+                 written.",
+                placement.index, entry.template, placement.template
+            );
+            continue;
+        }
+        let template = templates.entity(&entry.template);
+        moved.push(MovedPlacement {
+            index: placement.index,
+            loaded: entry.transform.clone(),
+            saved: saved_transform(entry, template, &spawned.0, current),
+        });
+    }
+    if moved.is_empty() {
+        info!("Nothing in scene '{scene}' has moved; not saving.");
+        return;
+    }
+
+    match write_entities(scene, &moved) {
+        Ok((path, saved)) => info!(
+            "Saved {saved} moved placement(s) to {path}; {} included or generated one(s) were \
+             left as written.",
+            moved.len() - saved
+        ),
+        Err(err) => warn!("Failed to save scene '{scene}': {err}."),
+    }
+}
+
+/// Whether an entity has moved from where it spawned by more than saving would round away.
+fn has_moved(spawned: &Transform, current: &Transform) -> bool {
+    let tolerance = 0.5 * 10f32.powi(-SAVE_DECIMALS);
+    !(current.translation.abs_diff_eq(spawned.translation, tolerance)
+        && current.rotation.abs_diff_eq(spawned.rotation, tolerance)
+        && current.scale.abs_diff_eq(spawned.scale, tolerance))
+}
+
+/// The placement transform that puts the primary entity where it is now. Entity templates
+/// get their full pose; combos only move, since their rotation is added to each part's
+/// rather than applied around the combo origin.
+fn saved_transform(
+    placement: &EntityPlacement,
+    template: Option<&EntityTemplate>,
+    spawned: &Transform,
+    current: &Transform,
+) -> TransformOverrides {
+    let mut transform = placement.transform.clone();
+    if is_combo_template(&placement.template) || template.is_none() {
+        let position = transform.position.get_or_insert_with(PositionConfig::default);
+        let delta = current.translation - spawned.translation;
+        position.x = round(position.x + delta.x);
+        position.y = round(position.y + delta.y);
+        position.z = round(position.z + delta.z);
+        return transform;
+    }
+
+    // The placement pose may differ from the spawned one, e.g. for a light offset from
+    // its origin, so the movement is applied to the pose rather than copied.
+    let pose = placement_pose(&transform, template);
+    let moved = current.compute_affine() * spawned.compute_affine().inverse();
+    let saved = Transform::from_matrix((moved * pose.compute_affine()).into());
+    transform.position = Some(PositionConfig {
+        x: round(saved.translation.x),
+        y: round(saved.translation.y),
+        z: round(saved.translation.z),
+    });
+    let order = EulerOrder::default();
+    let angles = order.angles(saved.rotation).map(round);
+    transform.rotation = Some(CubeRotationConfig::Euler {
+        x: angles.x,
+        y: angles.y,
+        z: angles.z,
+        order,
+    });
+    transform.scale = Some(ScaleConfig::from_vec3(saved.scale.map(round)));
+    transform
+}
+
+/// The template transform with the placement's overrides applied, as spawning uses it.
+fn placement_pose(
+    transform: &TransformOverrides,
+    template: Option<&EntityTemplate>,
+) -> Transform {
+    let base = template.map(|template| template.transform.clone()).unwrap_or_default();
+    let position = transform.position.as_ref().unwrap_or(&base.position);
+    let position = Vec3::new(position.x, position.y, position.z);
+    let rotation = transform.rotation.as_ref().unwrap_or(&base.rotation);
+    Transform::from_translation(position)
+        .with_rotation(rotation.to_quat(position))
+        .with_scale(transform.scale.unwrap_or(base.scale).to_vec3())
+}
+
+fn round(value: f32) -> f32 {
+    let factor = 10f32.powi(SAVE_DECIMALS);
+    let rounded = (value * factor).round() / factor;
+    // Avoid writing `-0.0`.
+    if rounded == 0.0 {
+        0.0
+    } else {
+        rounded
+    }
+}
+
+/// Edits the moved placements into the scene's entities file, keeping the previous file
+/// with a `.bak` suffix. Returns the file's path and how many placements it wrote.
+fn write_entities(scene: &str, moved: &[MovedPlacement]) -> Result<(String, usize), String> {
+    let file = scene_file(scene, ENTITIES_FILE);
+    let path = action_config_path(scene, &file);
+    let text = fs::read_to_string(&path).map_err(|err| err.to_string())?;
+    let (text, saved) = edit_entities(SceneFormat::of(&file), &text, moved)?;
+    fs::copy(&path, format!("{path}.bak")).map_err(|err| format!("backup failed: {err}"))?;
+    fs::write(&path, text).map_err(|err| err.to_string())?;
+    Ok((path, saved))
+}
+
+/// `text` with the changed transform keys of each moved placement that is one of the
+/// file's own entries written in, after migrating it to the current format. TOML keeps its
+/// comments and layout; JSON and RON are written out again.
+fn edit_entities(
+    format: SceneFormat,
+    text: &str,
+    moved: &[MovedPlacement],
+) -> Result<(String, usize), String> {
+    let text = migrate_text(format, text)?.text;
+    let mut saved = 0;
+    if format == SceneFormat::Toml {
+        let mut document = text
+            .parse::<DocumentMut>()
+            .map_err(|err| err.message().to_string())?;
+        for placement in moved {
+            let Some(entry) = document_entry(&mut document, placement.index) else {
+                continue;
+            };
+            set_transform_keys(entry, changed_keys(placement)?)?;
+            saved += 1;
+        }
+        return Ok((document.to_string(), saved));
+    }
+
+    let mut table = format.parse(&text).map_err(|err| err.message)?;
+    for placement in moved {
+        let entry = table
+            .get_mut("entities")
+            .and_then(toml::Value::as_array_mut)
+            .and_then(|entries| entries.get_mut(placement.index))
+            .and_then(toml::Value::as_table_mut);
+        let Some(entry) = entry else {
+            continue;
+        };
+        let transform = entry
+            .entry("transform")
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+        if !transform.is_table() {
+            *transform = toml::Value::Table(toml::Table::new());
+        }
+        if let Some(transform) = transform.as_table_mut() {
+            transform.extend(changed_keys(placement)?);
+        }
+        saved += 1;
+    }
+    Ok((format.write(&table, "")?, saved))
+}
+
+/// Entry `index` of the document's `entities`, written as `[[entities]]` tables or as an
+/// array of inline tables. `None` past the file's own entries.
+fn document_entry(document: &mut DocumentMut, index: usize) -> Option<&mut dyn TableLike> {
+    match document.get_mut("entities")? {
+        Item::ArrayOfTables(tables) => tables
+            .get_mut(index)
+            .map(|table| table as &mut dyn TableLike),
+        Item::Value(Value::Array(entries)) => entries
+            .get_mut(index)?
+            .as_inline_table_mut()
+            .map(|table| table as &mut dyn TableLike),
+        _ => None,
+    }
+}
+
+/// Replaces `keys` in the entry's `transform`, keeping each replaced value's comments.
+fn set_transform_keys(
+    entry: &mut dyn TableLike,
+    keys: Vec<(String, toml::Value)>,
+) -> Result<(), String> {
+    if !entry.get("transform").is_some_and(Item::is_table_like) {
+        entry.insert("transform", Item::Value(Value::InlineTable(InlineTable::new())));
+    }
+    let Some(transform) = entry.get_mut("transform").and_then(Item::as_table_like_mut) else {
+        return Ok(());
+    };
+    for (key, value) in keys {
+        let mut value = value.to_string().parse::<Value>().map_err(|err| err.to_string())?;
+        match transform.get(&key).and_then(Item::as_value) {
+            Some(old) => *value.decor_mut() = old.decor().clone(),
+            None => value.decor_mut().clear(),
+        }
+        transform.insert(&key, Item::Value(value));
+    }
+    Ok(())
+}
+
+/// The keys of the saved transform that differ from the loaded one, so unchanged values
+/// written with variables keep them.
+fn changed_keys(placement: &MovedPlacement) -> Result<Vec<(String, toml::Value)>, String> {
+    let loaded = toml::Table::try_from(&placement.loaded).map_err(|err| err.to_string())?;
+    let saved = toml::Table::try_from(&placement.saved).map_err(|err| err.to_string())?;
+    Ok(saved
+        .into_iter()
+        .filter(|(key, value)| loaded.get(key) != Some(value))
+        .map(|(key, mut value)| {
+            shorten_floats(&mut value);
+            (key, value)
+        })
+        .collect())
+}
+
+/// Saved numbers are `f32`s widened to `f64`, which would write `1.1235` as
+/// `1.1234999895095825`; this writes them as their shortest `f32` form instead.
+fn shorten_floats(value: &mut toml::Value) {
+    match value {
+        toml::Value::Float(float) => {
+            *float = (*float as f32).to_string().parse().unwrap_or(*float);
+        }
+        toml::Value::Array(values) => values.iter_mut().for_each(shorten_floats),
+        toml::Value::Table(table) => table.iter_mut().for_each(|(_, value)| shorten_floats(value)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn moved(index: usize, loaded: &str, saved: &str) -> MovedPlacement {
+        MovedPlacement {
+            index,
+            loaded: toml::from_str(loaded).expect("test transform parses"),
+            saved: toml::from_str(saved).expect("test transform parses"),
+        }
+    }
+
+    #[test]
+    fn moved_combos_save_their_rounded_position_only() {
+        let text = r#"
+            template = "combo/stack.toml"
+            transform = { position = { x = 1.0, y = 0.0, z = 2.0 } }
+        "#;
+        let placement: EntityPlacement = toml::from_str(text).unwrap();
+        let spawned = Transform::from_xyz(1.0, 0.5, 2.0);
+        let current = Transform::from_xyz(1.123456, 2.5, 1.99999)
+            .with_rotation(Quat::from_rotation_y(1.0));
+        let saved = saved_transform(&placement, None, &spawned, &current);
+        assert_eq!(saved.position, Some(PositionConfig { x: 1.1235, y: 2.0, z: 2.0 }));
+        assert!(saved.rotation.is_none());
+        assert!(saved.scale.is_none());
+        assert_eq!(round(-0.00001).to_bits(), 0.0f32.to_bits());
+    }
+
+    #[test]
+    fn saves_edit_only_the_moved_entries_of_the_file() {
+        let text = r#"# Crates by the door.
+
+format_version = 2
+
+include = [{ path = "props/*.toml", offset = { x = "${stack_x}" } }]
+
+[[entities]]
+template = "entities/crate.3D.toml"
+name_override = "left"
+[entities.transform]
+position = { x = 0.0, y = 1.0, z = 0.0 } # on the floor
+scale = "${crate_scale}"
+
+[[entities]]
+template = "entities/crate.3D.toml"
+name_override = "right"
+
+[[array]]
+template = "entities/crate.3D.toml"
+layout = { kind = "grid", count = { x = 3 }, spacing = { x = 1.0 } }
+"#;
+        let moved = [
+            moved(
+                0,
+                "position = { x = 0.0, y = 1.0, z = 0.0 }\nscale = 2.0",
+                "position = { x = 1.1235, y = 1.0, z = 0.0 }\nscale = 2.0",
+            ),
+            moved(1, "", "position = { x = 5.0, y = 0.0, z = 0.0 }"),
+            // An array copy, which follows the file's own entries.
+            moved(3, "", "position = { x = 9.0, y = 0.0, z = 0.0 }"),
+        ];
+        let (saved_text, saved) = edit_entities(SceneFormat::Toml, text, &moved).unwrap();
+        assert_eq!(saved, 2);
+        let expected = text
+            .replace(
+                "position = { x = 0.0, y = 1.0, z = 0.0 } # on the floor",
+                "position = { x = 1.1235, y = 1.0, z = 0.0 } # on the floor",
+            )
+            .replace(
+                "name_override = \"right\"\n",
+                "name_override = \"right\"\n\
+                 transform = { position = { x = 5.0, y = 0.0, z = 0.0 } }\n",
+            );
+        assert_eq!(saved_text, expected);
+    }
+
+    #[test]
+    fn json_saves_keep_the_rest_of_the_file() {
+        let text = r#"{
+  "format_version": 2,
+  "include": ["props/*.json"],
+  "entities": [{ "template": "entities/crate.3D.json", "transform": { "scale": "${s}" } }]
+}"#;
+        let moved = [moved(
+            0,
+            "scale = 1.0",
+            "position = { x = 2.0, y = 0.0, z = 0.0 }\nscale = 1.0",
+        )];
+        let (saved_text, saved) = edit_entities(SceneFormat::Json, text, &moved).unwrap();
+        assert_eq!(saved, 1);
+        let table = SceneFormat::Json
+            .parse(&saved_text)
+            .unwrap_or_else(|err| panic!("{}", err.message));
+        let expected: toml::Table = toml::from_str(
+            r#"
+            format_version = 2
+            include = ["props/*.json"]
+            [[entities]]
+            template = "entities/crate.3D.json"
+            transform = { scale = "${s}", position = { x = 2.0, y = 0.0, z = 0.0 } }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(table, expected);
+    }
+}
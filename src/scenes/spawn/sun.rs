@@ -2,6 +2,12 @@ use bevy::light::{NotShadowCaster, NotShadowReceiver};
 use bevy::prelude::*;
 
 use crate::scenes::config::SunConfig;
+use crate::scenes::manager::SceneEntity;
+
+/// Marks the sun's directional light and its visual disc.
+#[derive(Component)]
+#[require(SceneEntity)]
+pub struct SceneSun;
 
 pub(super) fn spawn_sun(
     sun: Option<&SunConfig>,
@@ -22,6 +28,7 @@ pub(super) fn spawn_sun(
 
     // Directional light pointing along dir
     commands.spawn((
+        SceneSun,
         DirectionalLight {
             illuminance: sun.brightness,
             shadows_enabled: sun.shadows,
@@ -42,6 +49,7 @@ pub(super) fn spawn_sun(
         ..default()
     });
     commands.spawn((
+        SceneSun,
         Name::new("sun_sphere"),
         Mesh3d(meshes.add(Sphere::new(sun.size))),
         MeshMaterial3d(sun_material),
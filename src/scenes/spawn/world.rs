@@ -1,70 +1,231 @@
+use std::collections::HashSet;
+
 use bevy::{
     log::warn,
     prelude::*,
 };
+use bevy_rapier3d::prelude::{
+    AdditionalMassProperties, Collider, ColliderMassProperties, RapierColliderHandle, RigidBody,
+};
 
 use crate::scenes::{
-    config::ActiveScene,
-    entities::EntitiesConfig,
-    loaders::{load_combo_template_from_path, load_entity_template_from_path},
+    bounds::DespawnOutsideBounds,
+    config::{ActiveScene, PositionConfig},
+    entities::{is_combo_template, EntitiesConfig, EntityPlacement},
+    manager::SceneEntity,
+    templates::{SceneTemplates, TemplateState},
 };
 
 use super::combo::spawn_combo_template;
 use super::entities::spawn_entity_from_template;
 
-pub(super) fn spawn_world_entities(
-    entities: &EntitiesConfig,
+/// Marks every top-level entity spawned for the placement at `index` in `entities.toml`.
+#[derive(Component, Debug, Clone)]
+#[require(SceneEntity)]
+pub struct ScenePlacement {
+    pub index: usize,
+    pub template: String,
+    /// Set on the first entity spawned for the placement, the one saving reads back.
+    pub primary: bool,
+}
+
+/// The transform a placement's primary entity was spawned with, so saving can tell how
+/// far physics or the inspector has moved it since.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct SpawnedPose(pub Transform);
+
+/// Placements waiting for their templates to load, with their index in `entities.toml`.
+#[derive(Resource, Default)]
+pub struct PendingPlacements {
+    placements: Vec<(usize, EntityPlacement)>,
+}
+
+impl PendingPlacements {
+    pub fn push(&mut self, index: usize, placement: EntityPlacement) {
+        self.placements.retain(|(pending, _)| *pending != index);
+        self.placements.push((index, placement));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.placements.is_empty()
+    }
+}
+
+pub(super) fn queue_world_entities(entities: EntitiesConfig, pending: &mut PendingPlacements) {
+    for (index, placement) in entities.entities.into_iter().enumerate() {
+        pending.push(index, placement);
+    }
+}
+
+/// Spawns each pending placement once its templates have loaded, dropping those whose
+/// template failed to load. Placements with a `parent` also wait for it to spawn, and are
+/// then attached to its primary entity.
+pub(super) fn spawn_pending_placements(
+    mut pending: ResMut<PendingPlacements>,
+    mut templates: SceneTemplates,
+    active_scene: Res<ActiveScene>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    asset_server: Res<AssetServer>,
+    spawned: Query<(Entity, &ScenePlacement)>,
+) {
+    if pending.placements.is_empty() {
+        return;
+    }
+
+    // A parent spawned this frame only shows up in `spawned` next frame.
+    let unspawned: HashSet<usize> = pending.placements.iter().map(|(index, _)| *index).collect();
+    let mut waiting = Vec::new();
+    for (index, placement) in pending.placements.drain(..) {
+        if placement
+            .parent_index
+            .is_some_and(|parent| unspawned.contains(&parent))
+        {
+            waiting.push((index, placement));
+            continue;
+        }
+        match templates.request(&active_scene.name, &placement.template) {
+            TemplateState::Loading => waiting.push((index, placement)),
+            TemplateState::Failed => warn!(
+                "Failed to load template '{}' in scene '{}'; skipping.",
+                placement.template, active_scene.name
+            ),
+            TemplateState::Ready => {
+                let entities = spawn_placement(
+                    index,
+                    &placement,
+                    &templates,
+                    &mut commands,
+                    &mut meshes,
+                    &mut materials,
+                    &asset_server,
+                    &active_scene,
+                );
+                let Some(parent_index) = placement.parent_index else {
+                    continue;
+                };
+                let parent = spawned
+                    .iter()
+                    .find(|(_, spawned)| spawned.primary && spawned.index == parent_index);
+                match parent {
+                    Some((parent, _)) => {
+                        commands.entity(parent).add_children(&entities);
+                    }
+                    None => warn!(
+                        "Parent '{}' of placement {index} in scene '{}' was not spawned; \
+                         placing it without a parent.",
+                        placement.parent.as_deref().unwrap_or_default(),
+                        active_scene.name
+                    ),
+                }
+            }
+        }
+    }
+    pending.placements = waiting;
+}
+
+fn spawn_placement(
+    index: usize,
+    entity: &EntityPlacement,
+    templates: &SceneTemplates,
     commands: &mut Commands,
     meshes: &mut Assets<Mesh>,
     materials: &mut Assets<StandardMaterial>,
     asset_server: &AssetServer,
     active_scene: &ActiveScene,
+) -> Vec<Entity> {
+    let spawned = if is_combo_template(&entity.template) {
+        let Some(combo) = templates.combo(&entity.template) else {
+            return Vec::new();
+        };
+        // Combo placements offset the parts, so an include offset adds to the origin.
+        let transform = entity.spawn_transform(&PositionConfig::default());
+        let name = entity.spawn_name(&combo.name);
+        spawn_combo_template(
+            combo,
+            &transform,
+            &entity.overrides,
+            name.as_ref(),
+            templates,
+            commands,
+            meshes,
+            materials,
+            asset_server,
+            active_scene,
+        )
+    } else {
+        let Some(template) = templates.entity(&entity.template) else {
+            return Vec::new();
+        };
+        let transform = entity.spawn_transform(&template.transform.position);
+        let name = entity.spawn_name(&template.name);
+        spawn_entity_from_template(
+            template,
+            &entity.overrides,
+            &transform,
+            name.as_ref(),
+            commands,
+            meshes,
+            materials,
+            asset_server,
+            active_scene,
+        )
+    };
+
+    for (part, spawned_entity) in spawned.iter().enumerate() {
+        commands.entity(*spawned_entity).insert(ScenePlacement {
+            index,
+            template: entity.template.clone(),
+            primary: part == 0,
+        });
+    }
+    spawned
+}
+
+/// Folds the rigid body of each placement spawned under a parent with a body of its own
+/// into that body: the child's colliders are attached to the parent's body, the same as a
+/// fixed joint but without the solver slack, and its mass is moved onto its collider.
+/// Placements under a parent without a body keep theirs.
+pub(super) fn merge_child_bodies(
+    mut commands: Commands,
+    children: Query<
+        (Entity, Option<&AdditionalMassProperties>, Has<Collider>),
+        (With<ScenePlacement>, With<ChildOf>, With<RigidBody>),
+    >,
+    ancestors: Query<&ChildOf>,
+    bodies: Query<(), With<RigidBody>>,
+    descendants: Query<&Children>,
 ) {
-    for entity in &entities.entities {
-        if entity.template.starts_with("combo/")
-            || entity.template.ends_with(".combo.toml")
+    for (entity, mass, has_collider) in &children {
+        if !ancestors
+            .iter_ancestors(entity)
+            .any(|ancestor| bodies.contains(ancestor))
         {
-            let Some(combo) =
-                load_combo_template_from_path(&active_scene.name, &entity.template)
-            else {
-                warn!(
-                    "Failed to load combo template '{}' in scene '{}'; skipping.",
-                    entity.template, active_scene.name
-                );
-                continue;
-            };
-            spawn_combo_template(
-                &combo,
-                &entity.transform,
-                &entity.overrides,
-                entity.name_override.as_ref(),
-                commands,
-                meshes,
-                materials,
-                asset_server,
-                active_scene,
-            );
-        } else {
-            let Some(template) =
-                load_entity_template_from_path(&active_scene.name, &entity.template)
-            else {
-                warn!(
-                    "Failed to load template '{}' in scene '{}'; skipping.",
-                    entity.template, active_scene.name
-                );
-                continue;
-            };
-            spawn_entity_from_template(
-                &template,
-                &entity.overrides,
-                &entity.transform,
-                entity.name_override.as_ref(),
-                commands,
-                meshes,
-                materials,
-                asset_server,
-                active_scene,
-            );
+            continue;
+        }
+        let mut child = commands.entity(entity);
+        child.remove::<(RigidBody, AdditionalMassProperties, DespawnOutsideBounds)>();
+        if let (Some(AdditionalMassProperties::Mass(mass)), true) = (mass, has_collider) {
+            child.insert(ColliderMassProperties::Mass(*mass));
+        }
+        // Colliders already attached to the child's own body are re-created under the
+        // parent's once their handles are gone.
+        for collider in std::iter::once(entity).chain(descendants.iter_descendants(entity)) {
+            commands.entity(collider).remove::<RapierColliderHandle>();
+        }
+    }
+}
+
+/// Records the spawn transform of newly spawned primary entities. Runs right after
+/// `spawn_pending_placements`, before physics has had a chance to move them.
+pub(super) fn record_spawned_poses(
+    mut commands: Commands,
+    placements: Query<(Entity, &ScenePlacement, &Transform), Added<ScenePlacement>>,
+) {
+    for (entity, placement, transform) in &placements {
+        if placement.primary {
+            commands.entity(entity).insert(SpawnedPose(*transform));
         }
     }
 }
@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+
+use bevy::{asset::LoadState, ecs::system::SystemParam, prelude::*};
+
+use crate::scenes::config::{scene_asset_path, ComboTemplate, EntityTemplate};
+use crate::scenes::entities::is_combo_template;
+
+/// Handles for every template the active scene has asked for, keyed by scene-relative
+/// path, so each file is loaded once per scene however many placements, combo parts and
+/// stack instances use it. Replaced on scene switch, which lets the old templates unload.
+#[derive(Resource, Default)]
+pub struct TemplateCache {
+    entities: HashMap<String, Handle<EntityTemplate>>,
+    combos: HashMap<String, Handle<ComboTemplate>>,
+}
+
+impl TemplateCache {
+    /// Scene-relative paths of every cached template.
+    pub fn paths(&self) -> impl Iterator<Item = &String> {
+        self.entities.keys().chain(self.combos.keys())
+    }
+
+    pub fn entity_path(&self, id: AssetId<EntityTemplate>) -> Option<&String> {
+        self.entities
+            .iter()
+            .find(|(_, handle)| handle.id() == id)
+            .map(|(path, _)| path)
+    }
+
+    pub fn combo_path(&self, id: AssetId<ComboTemplate>) -> Option<&String> {
+        self.combos
+            .iter()
+            .find(|(_, handle)| handle.id() == id)
+            .map(|(path, _)| path)
+    }
+}
+
+/// Whether everything a placement's template needs has finished loading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemplateState {
+    Loading,
+    Ready,
+    Failed,
+}
+
+/// Cached template access for spawning systems.
+#[derive(SystemParam)]
+pub struct SceneTemplates<'w> {
+    asset_server: Res<'w, AssetServer>,
+    cache: ResMut<'w, TemplateCache>,
+    entity_templates: Res<'w, Assets<EntityTemplate>>,
+    combo_templates: Res<'w, Assets<ComboTemplate>>,
+}
+
+impl SceneTemplates<'_> {
+    /// Starts loading `template` in `scene`, plus every part once it is a loaded combo,
+    /// and reports whether it can be spawned. Combos whose parts failed are still ready;
+    /// spawning skips those parts.
+    pub fn request(&mut self, scene: &str, template: &str) -> TemplateState {
+        if !is_combo_template(template) {
+            let handle = self.entity_handle(scene, template);
+            return state_of(self.asset_server.load_state(handle.id()));
+        }
+
+        let handle = self.combo_handle(scene, template);
+        match state_of(self.asset_server.load_state(handle.id())) {
+            TemplateState::Ready => {}
+            state => return state,
+        }
+        let Some(combo) = self.combo_templates.get(&handle) else {
+            return TemplateState::Loading;
+        };
+        let parts: Vec<String> = combo.parts.iter().map(|part| part.template.clone()).collect();
+        let mut state = TemplateState::Ready;
+        for part in parts {
+            let handle = self.entity_handle(scene, &part);
+            if state_of(self.asset_server.load_state(handle.id())) == TemplateState::Loading {
+                state = TemplateState::Loading;
+            }
+        }
+        state
+    }
+
+    pub fn entity(&self, template: &str) -> Option<&EntityTemplate> {
+        let handle = self.cache.entities.get(template)?;
+        self.entity_templates.get(handle)
+    }
+
+    pub fn combo(&self, template: &str) -> Option<&ComboTemplate> {
+        let handle = self.cache.combos.get(template)?;
+        self.combo_templates.get(handle)
+    }
+
+    fn entity_handle(&mut self, scene: &str, template: &str) -> Handle<EntityTemplate> {
+        let asset_server = &self.asset_server;
+        self.cache
+            .entities
+            .entry(template.to_string())
+            .or_insert_with(|| asset_server.load(scene_asset_path(scene, template)))
+            .clone()
+    }
+
+    fn combo_handle(&mut self, scene: &str, template: &str) -> Handle<ComboTemplate> {
+        let asset_server = &self.asset_server;
+        self.cache
+            .combos
+            .entry(template.to_string())
+            .or_insert_with(|| asset_server.load(scene_asset_path(scene, template)))
+            .clone()
+    }
+}
+
+fn state_of(state: LoadState) -> TemplateState {
+    match state {
+        LoadState::Loaded => TemplateState::Ready,
+        LoadState::Failed(_) => TemplateState::Failed,
+        LoadState::NotLoaded | LoadState::Loading => TemplateState::Loading,
+    }
+}
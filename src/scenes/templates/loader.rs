@@ -0,0 +1,329 @@
+use std::{
+    collections::HashMap,
+    fmt, io,
+    path::{Component, Path},
+};
+
+use bevy::asset::{io::Reader, AssetLoader, LoadContext};
+
+use crate::scenes::config::{
+    action_config_path, scene_asset_path, ComboTemplate, EntityTemplate, InputConfig, ASSET_ROOT,
+};
+use crate::scenes::diagnostics::{DiagnosticCollector, SourceFile};
+use crate::scenes::entities::EntitiesConfig;
+use crate::scenes::expand::{direct_includes, file_vars, substitute_vars};
+use crate::scenes::format::{scene_file, SceneFormat};
+use crate::scenes::loaders::{
+    parse_combo_template, parse_entity_template, read_entities_config, read_input_config,
+    read_world_config, WORLD_FILE,
+};
+use crate::scenes::world::WorldConfig;
+
+/// Loads entity templates through the same pipeline as `load_entity_template_from_path`.
+/// Templates may be TOML, JSON or RON, so the loader is picked by asset type, not
+/// extension, and the format by the file's extension. Problems go to the collector of
+/// the app that registered the loader.
+pub struct EntityTemplateLoader {
+    pub diagnostics: DiagnosticCollector,
+}
+
+/// Loads combo templates through the same pipeline as `load_combo_template_from_path`.
+pub struct ComboTemplateLoader {
+    pub diagnostics: DiagnosticCollector,
+}
+
+/// Loads a scene's `world.toml`, with its includes, like `load_world_config`.
+pub struct WorldConfigLoader {
+    pub diagnostics: DiagnosticCollector,
+}
+
+/// Loads a scene's `entities.toml`, with its includes, like `load_entities_config`.
+pub struct EntitiesConfigLoader {
+    pub diagnostics: DiagnosticCollector,
+}
+
+/// Loads a scene's `input.toml` like `load_input_config`.
+pub struct InputConfigLoader {
+    pub diagnostics: DiagnosticCollector,
+}
+
+#[derive(Debug)]
+pub enum SceneAssetError {
+    Io(io::Error),
+    /// The file is not under a scene directory, so scene-relative paths cannot resolve.
+    OutsideScene(String),
+    /// The file is invalid; the details went to the config diagnostics.
+    Invalid(String),
+}
+
+impl fmt::Display for SceneAssetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneAssetError::Io(err) => write!(f, "failed to read scene file: {err}"),
+            SceneAssetError::OutsideScene(path) => {
+                write!(f, "{path} is not inside a scene directory")
+            }
+            SceneAssetError::Invalid(path) => {
+                write!(f, "{path} is invalid; see the config diagnostics")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SceneAssetError {}
+
+impl From<io::Error> for SceneAssetError {
+    fn from(err: io::Error) -> Self {
+        SceneAssetError::Io(err)
+    }
+}
+
+impl AssetLoader for EntityTemplateLoader {
+    type Asset = EntityTemplate;
+    type Settings = ();
+    type Error = SceneAssetError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<EntityTemplate, SceneAssetError> {
+        let contents = read_contents(reader).await?;
+        let (scene, relative) = split_asset_path(load_context.path())?;
+        let path = action_config_path(&scene, &relative);
+        let vars = world_vars(load_context, &scene).await;
+        let files =
+            read_referenced(load_context, &scene, &relative, &contents, &vars, parent_of).await;
+        let mut read_parent = read_from(&files);
+        parse_entity_template(
+            &scene,
+            &path,
+            &contents,
+            &vars,
+            &mut read_parent,
+            &self.diagnostics,
+        )
+        .ok_or(SceneAssetError::Invalid(path))
+    }
+}
+
+impl AssetLoader for ComboTemplateLoader {
+    type Asset = ComboTemplate;
+    type Settings = ();
+    type Error = SceneAssetError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<ComboTemplate, SceneAssetError> {
+        let contents = read_contents(reader).await?;
+        let (scene, relative) = split_asset_path(load_context.path())?;
+        let path = action_config_path(&scene, &relative);
+        let vars = world_vars(load_context, &scene).await;
+        parse_combo_template(&scene, &path, &contents, &vars, &self.diagnostics)
+            .ok_or(SceneAssetError::Invalid(path))
+    }
+}
+
+impl AssetLoader for WorldConfigLoader {
+    type Asset = WorldConfig;
+    type Settings = ();
+    type Error = SceneAssetError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<WorldConfig, SceneAssetError> {
+        let contents = read_contents(reader).await?;
+        let (scene, relative) = split_asset_path(load_context.path())?;
+        let vars = file_vars(&relative, &contents);
+        let files = read_referenced(
+            load_context,
+            &scene,
+            &relative,
+            &contents,
+            &vars,
+            direct_includes,
+        )
+        .await;
+        read_world_config(&scene, &vars, &mut read_from(&files), &self.diagnostics).ok_or(
+            SceneAssetError::Invalid(action_config_path(&scene, &relative)),
+        )
+    }
+}
+
+impl AssetLoader for EntitiesConfigLoader {
+    type Asset = EntitiesConfig;
+    type Settings = ();
+    type Error = SceneAssetError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<EntitiesConfig, SceneAssetError> {
+        let contents = read_contents(reader).await?;
+        let (scene, relative) = split_asset_path(load_context.path())?;
+        let vars = world_vars(load_context, &scene).await;
+        let files = read_referenced(
+            load_context,
+            &scene,
+            &relative,
+            &contents,
+            &vars,
+            direct_includes,
+        )
+        .await;
+        read_entities_config(&scene, &vars, &mut read_from(&files), &self.diagnostics).ok_or(
+            SceneAssetError::Invalid(action_config_path(&scene, &relative)),
+        )
+    }
+}
+
+impl AssetLoader for InputConfigLoader {
+    type Asset = InputConfig;
+    type Settings = ();
+    type Error = SceneAssetError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<InputConfig, SceneAssetError> {
+        let contents = read_contents(reader).await?;
+        let (scene, relative) = split_asset_path(load_context.path())?;
+        let vars = world_vars(load_context, &scene).await;
+        let files = HashMap::from([(action_config_path(&scene, &relative), Ok(contents))]);
+        read_input_config(&scene, &vars, &mut read_from(&files), &self.diagnostics).ok_or(
+            SceneAssetError::Invalid(action_config_path(&scene, &relative)),
+        )
+    }
+}
+
+/// The scene's `[vars]`, read through the load context so the asset is reloaded along
+/// with `world.toml`. A missing or broken world is reported when the world itself loads.
+async fn world_vars(load_context: &mut LoadContext<'_>, scene: &str) -> toml::Table {
+    let file = scene_file(scene, WORLD_FILE);
+    match load_context
+        .read_asset_bytes(scene_asset_path(scene, &file))
+        .await
+    {
+        Ok(bytes) => file_vars(&file, &String::from_utf8_lossy(&bytes)),
+        Err(_) => toml::Table::new(),
+    }
+}
+
+/// `file` plus every file it refers to through `references`, directly or through the
+/// files it refers to, keyed by file path. They are read through the load context so the
+/// asset system knows the asset depends on them; read errors are kept for the loaders to
+/// report.
+async fn read_referenced(
+    load_context: &mut LoadContext<'_>,
+    scene: &str,
+    file: &str,
+    contents: &str,
+    vars: &toml::Table,
+    references: fn(&str, &toml::Table) -> Vec<String>,
+) -> HashMap<String, Result<String, String>> {
+    let mut files = HashMap::new();
+    let mut queue = vec![(file.to_string(), Ok(contents.to_string()))];
+    while let Some((file, read)) = queue.pop() {
+        let referenced = read
+            .as_ref()
+            .ok()
+            .and_then(|text| resolved_table(&file, text, vars))
+            .map(|table| references(scene, &table))
+            .unwrap_or_default();
+        files.insert(action_config_path(scene, &file), read);
+        for reference in referenced {
+            let queued = queue.iter().any(|(queued, _)| *queued == reference);
+            if queued || files.contains_key(&action_config_path(scene, &reference)) {
+                continue;
+            }
+            let read = load_context
+                .read_asset_bytes(scene_asset_path(scene, &reference))
+                .await
+                .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+                .map_err(|err| err.to_string());
+            queue.push((reference, read));
+        }
+    }
+    files
+}
+
+/// Serves the files from `read_referenced` to the loaders.
+fn read_from(
+    files: &HashMap<String, Result<String, String>>,
+) -> impl FnMut(&str) -> io::Result<String> + '_ {
+    |path| match files.get(path) {
+        Some(Ok(text)) => Ok(text.clone()),
+        Some(Err(err)) => Err(io::Error::other(err.clone())),
+        None => Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "not read through the asset server",
+        )),
+    }
+}
+
+/// `contents` with variables substituted, as the loaders will see it, for finding the
+/// files it refers to. Its problems are left for the loaders to report.
+fn resolved_table(file: &str, contents: &str, vars: &toml::Table) -> Option<toml::Table> {
+    let table = SceneFormat::of(file).parse(contents).ok()?;
+    let ignored = DiagnosticCollector::default();
+    substitute_vars(&SourceFile::new(file, contents, &ignored), vars, table)
+}
+
+fn parent_of(_scene: &str, table: &toml::Table) -> Vec<String> {
+    let parent = table.get("extends").and_then(toml::Value::as_str);
+    parent.map(str::to_string).into_iter().collect()
+}
+
+async fn read_contents(reader: &mut dyn Reader) -> Result<String, SceneAssetError> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes).await?;
+    String::from_utf8(bytes)
+        .map_err(|err| SceneAssetError::Io(io::Error::new(io::ErrorKind::InvalidData, err)))
+}
+
+/// Splits an asset path like `scenes/main/entities/cube.3D.toml` into the scene name and
+/// the scene-relative path.
+fn split_asset_path(asset_path: &Path) -> Result<(String, String), SceneAssetError> {
+    let outside =
+        || SceneAssetError::OutsideScene(format!("{ASSET_ROOT}/{}", asset_path.display()));
+    // `scenes//` for an empty scene and file, leaving the scene root's asset path.
+    let scene_root = scene_asset_path("", "");
+    let relative = asset_path
+        .strip_prefix(scene_root.trim_end_matches('/'))
+        .map_err(|_| outside())?;
+    let mut components = relative.components();
+    let Some(Component::Normal(scene)) = components.next() else {
+        return Err(outside());
+    };
+    let file = components.as_path().to_string_lossy().replace('\\', "/");
+    Ok((scene.to_string_lossy().into_owned(), file))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn asset_paths_split_into_scene_and_relative_file() {
+        let path = Path::new("scenes/main/entities/cube.3D.toml");
+        let (scene, file) = split_asset_path(path).unwrap();
+        assert_eq!((scene.as_str(), file.as_str()), ("main", "entities/cube.3D.toml"));
+        for outside in ["models/cube.glb", "scenes"] {
+            assert!(matches!(
+                split_asset_path(Path::new(outside)),
+                Err(SceneAssetError::OutsideScene(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,50 @@
+mod cache;
+mod loader;
+
+use bevy::prelude::*;
+
+use crate::scenes::config::{ComboTemplate, EntityTemplate, InputConfig};
+use crate::scenes::diagnostics::DiagnosticCollector;
+use crate::scenes::entities::EntitiesConfig;
+use crate::scenes::world::WorldConfig;
+
+pub use cache::{SceneTemplates, TemplateCache, TemplateState};
+
+use loader::{
+    ComboTemplateLoader, EntitiesConfigLoader, EntityTemplateLoader, InputConfigLoader,
+    WorldConfigLoader,
+};
+
+/// Registers entity and combo templates as assets, loaded asynchronously by the
+/// `AssetServer` and shared through `TemplateCache`, along with each scene's world,
+/// entities and input files, which `setup_scene` waits for.
+pub struct TemplateAssetsPlugin;
+
+impl Plugin for TemplateAssetsPlugin {
+    fn build(&self, app: &mut App) {
+        let diagnostics = app
+            .init_resource::<DiagnosticCollector>()
+            .world()
+            .resource::<DiagnosticCollector>()
+            .clone();
+        app.init_asset::<EntityTemplate>()
+            .init_asset::<ComboTemplate>()
+            .register_asset_loader(EntityTemplateLoader {
+                diagnostics: diagnostics.clone(),
+            })
+            .register_asset_loader(ComboTemplateLoader {
+                diagnostics: diagnostics.clone(),
+            })
+            .init_resource::<TemplateCache>();
+        app.init_asset::<WorldConfig>()
+            .init_asset::<EntitiesConfig>()
+            .init_asset::<InputConfig>()
+            .register_asset_loader(WorldConfigLoader {
+                diagnostics: diagnostics.clone(),
+            })
+            .register_asset_loader(EntitiesConfigLoader {
+                diagnostics: diagnostics.clone(),
+            })
+            .register_asset_loader(InputConfigLoader { diagnostics });
+    }
+}
@@ -0,0 +1,285 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::scenes::{
+    config::{action_config_path, ComboTemplate, PROJECTILE_TEMPLATE, SCENE_ROOT},
+    diagnostics::{ConfigDiagnostic, DiagnosticCollector, SourceFile},
+    entities::is_combo_template,
+    expand::{scene_vars, substitute_vars},
+    format::{file_stem, is_scene_file, SceneFormat},
+    migrate::migrate_text,
+    loaders::{
+        load_combo_template_from_path, load_entities_config, load_entity_template_from_path,
+        load_input_config, load_overlay_config, load_shoot_action_config,
+        load_sprint_action_config, load_world_config, load_zoom_action_config,
+    },
+};
+
+/// Editor schema mapping; the validator checks scene files against the same schemas.
+const TAPLO_CONFIG: &str = ".taplo.toml";
+
+/// Loads `scene` the way `setup_scene` does, without an app, and returns every diagnostic
+/// found along the way plus any JSON schema violations.
+pub fn validate_scene(scene: &str) -> Vec<ConfigDiagnostic> {
+    let scene_dir = Path::new(SCENE_ROOT).join(scene);
+    if !scene_dir.is_dir() {
+        return vec![ConfigDiagnostic::error(
+            scene_dir.to_string_lossy(),
+            "Scene directory not found.",
+        )];
+    }
+
+    let diagnostics = DiagnosticCollector::default();
+    let input = load_input_config(scene, &diagnostics);
+    for binding in &input.actions {
+        let action = file_stem(&binding.action);
+        if action.ends_with("shoot-balls") {
+            if load_shoot_action_config(scene, &binding.action, &diagnostics).is_some() {
+                load_entity_template_from_path(scene, PROJECTILE_TEMPLATE, &diagnostics);
+            }
+        } else if action.ends_with("sprint") {
+            load_sprint_action_config(scene, &binding.action, &diagnostics);
+        } else if action.ends_with("zoom") {
+            load_zoom_action_config(scene, &binding.action, &diagnostics);
+        }
+    }
+    if input.overlays.is_empty() {
+        load_overlay_config("debug", &diagnostics);
+    }
+    for overlay in &input.overlays {
+        load_overlay_config(&overlay.name, &diagnostics);
+    }
+
+    load_world_config(scene, &diagnostics);
+    for placement in load_entities_config(scene, &diagnostics).entities {
+        if is_combo_template(&placement.template) {
+            let combo = load_combo_template_from_path(scene, &placement.template, &diagnostics);
+            if let Some(combo) = combo {
+                check_combo_parts(scene, &placement.template, &combo, &diagnostics);
+            }
+        } else {
+            load_entity_template_from_path(scene, &placement.template, &diagnostics);
+        }
+    }
+
+    check_schemas(scene, &scene_dir, &diagnostics);
+    diagnostics.drain()
+}
+
+/// Loads every part template and flags `attach.target` and `physics.root` names that
+/// match no part of the combo.
+fn check_combo_parts(
+    scene: &str,
+    template_path: &str,
+    combo: &ComboTemplate,
+    diagnostics: &DiagnosticCollector,
+) {
+    let part_names: Vec<String> = combo
+        .parts
+        .iter()
+        .filter_map(|part| {
+            let template = load_entity_template_from_path(scene, &part.template, diagnostics)?;
+            Some(part.name_override.clone().unwrap_or(template.name))
+        })
+        .collect();
+
+    let path = action_config_path(scene, template_path);
+    let Ok(text) = fs::read_to_string(&path) else {
+        return;
+    };
+    let source = SourceFile::new(&path, &text, diagnostics);
+    for (index, part) in combo.parts.iter().enumerate() {
+        let Some(attach) = &part.attach else {
+            continue;
+        };
+        if !part_names.contains(&attach.target) {
+            source.error(
+                &format!("parts[{index}].attach.target"),
+                format!("Attach target '{}' matches no part of this combo.", attach.target),
+            );
+        }
+    }
+    if let Some(root) = combo.physics.as_ref().and_then(|physics| physics.root.as_ref()) {
+        if !part_names.contains(root) {
+            source.error(
+                "physics.root",
+                format!("Physics root '{root}' matches no part of this combo."),
+            );
+        }
+    }
+}
+
+/// Checks every scene file against the schema `.taplo.toml` assigns to it, after variable
+/// substitution. The mapping only names TOML files, so JSON and RON files go unchecked.
+fn check_schemas(scene: &str, scene_dir: &Path, diagnostics: &DiagnosticCollector) {
+    let rules = schema_rules(diagnostics);
+    let vars = scene_vars(scene);
+    let mut validators = HashMap::new();
+    let mut files = Vec::new();
+    collect_scene_files(scene_dir, &mut files);
+    files.sort();
+
+    for file in files {
+        let path = file.to_string_lossy().replace('\\', "/");
+        let Some((_, schema_path)) = rules.iter().find(|(pattern, _)| pattern.matches(&path))
+        else {
+            continue;
+        };
+        let validator = validators
+            .entry(schema_path.clone())
+            .or_insert_with(|| load_schema(schema_path, diagnostics));
+        let Some(validator) = validator else {
+            continue;
+        };
+        // Files that fail to read or parse were already reported by the loaders.
+        let Ok(text) = fs::read_to_string(&file) else {
+            continue;
+        };
+        // Schemas describe the current format, so older files are checked as migrated.
+        let format = SceneFormat::of(&file);
+        let Ok(migrated) = migrate_text(format, &text) else {
+            continue;
+        };
+        let Ok(table) = format.parse(&migrated.text) else {
+            continue;
+        };
+        let source = SourceFile::new(&path, &text, diagnostics);
+        let Some(table) = substitute_vars(&source, &vars, table) else {
+            continue;
+        };
+        let Ok(instance) = serde_json::to_value(&table) else {
+            continue;
+        };
+
+        for error in validator.iter_errors(&instance) {
+            let field = pointer_to_field(&error.instance_path.to_string());
+            source.error(&field, format!("{error} (schema {schema_path})."));
+        }
+    }
+}
+
+fn schema_rules(diagnostics: &DiagnosticCollector) -> Vec<(glob::Pattern, String)> {
+    let Ok(text) = fs::read_to_string(TAPLO_CONFIG) else {
+        return Vec::new();
+    };
+    let Ok(config) = toml::from_str::<toml::Table>(&text) else {
+        let message = "Failed to parse schema mapping.";
+        diagnostics.report(ConfigDiagnostic::error(TAPLO_CONFIG, message));
+        return Vec::new();
+    };
+
+    let mut rules = Vec::new();
+    for rule in config
+        .get("rule")
+        .and_then(toml::Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let Some(schema) = rule
+            .get("schema")
+            .and_then(|schema| schema.get("path"))
+            .and_then(toml::Value::as_str)
+        else {
+            continue;
+        };
+        for include in rule
+            .get("include")
+            .and_then(toml::Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(toml::Value::as_str)
+        {
+            if let Ok(pattern) = glob::Pattern::new(include) {
+                rules.push((pattern, schema.to_string()));
+            }
+        }
+    }
+    rules
+}
+
+/// A missing schema only skips the schema check for its files, since the loaders have
+/// already checked them against the config types.
+fn load_schema(
+    schema_path: &str,
+    diagnostics: &DiagnosticCollector,
+) -> Option<jsonschema::Validator> {
+    if !Path::new(schema_path).is_file() {
+        let message = "Schema not found, so its files were not checked against it; run \
+                       `cargo run --bin atmos-schemas` to generate it.";
+        diagnostics.report(ConfigDiagnostic::warning(schema_path, message));
+        return None;
+    }
+    let schema = fs::read_to_string(schema_path)
+        .map_err(|err| err.to_string())
+        .and_then(|text| {
+            serde_json::from_str::<serde_json::Value>(&text).map_err(|err| err.to_string())
+        })
+        .and_then(|schema| jsonschema::validator_for(&schema).map_err(|err| err.to_string()));
+    match schema {
+        Ok(validator) => Some(validator),
+        Err(err) => {
+            let message = format!("Unusable schema: {err}.");
+            diagnostics.report(ConfigDiagnostic::error(schema_path, message));
+            None
+        }
+    }
+}
+
+/// Turns a JSON pointer like `/entities/0/template` into `entities[0].template`.
+fn pointer_to_field(pointer: &str) -> String {
+    let mut field = String::new();
+    for segment in pointer.split('/').filter(|segment| !segment.is_empty()) {
+        let segment = segment.replace("~1", "/").replace("~0", "~");
+        if segment.parse::<usize>().is_ok() {
+            field.push_str(&format!("[{segment}]"));
+        } else {
+            if !field.is_empty() {
+                field.push('.');
+            }
+            field.push_str(&segment);
+        }
+    }
+    field
+}
+
+pub(super) fn collect_scene_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_scene_files(&path, files);
+        } else if is_scene_file(&path) {
+            files.push(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_pointers_become_field_paths() {
+        assert_eq!(pointer_to_field(""), "");
+        assert_eq!(
+            pointer_to_field("/entities/0/template"),
+            "entities[0].template"
+        );
+        assert_eq!(pointer_to_field("/lights/2"), "lights[2]");
+        assert_eq!(pointer_to_field("/paths/a~1b/c~0d"), "paths.a/b.c~d");
+    }
+
+    #[test]
+    fn missing_schemas_are_warnings() {
+        let collector = DiagnosticCollector::default();
+        assert!(load_schema("schemas/missing.schema.json", &collector).is_none());
+        let diagnostics = collector.drain();
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics.iter().all(|diagnostic| !diagnostic.is_error()));
+    }
+}
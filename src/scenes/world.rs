@@ -1,11 +1,13 @@
 use bevy::prelude::*;
+use schemars::JsonSchema;
 use serde::Deserialize;
 
 use crate::scenes::config::{
-    BoundingBoxConfig, CameraConfig, LightEntry, RenderConfig, SkyboxConfig, SunConfig, Vec3Config,
+    BoundingBoxConfig, CameraConfig, IncludeConfig, LightEntry, RenderConfig, SkyboxConfig,
+    SunConfig, Vec3Config,
 };
 
-#[derive(Debug, Deserialize)]
+#[derive(Asset, TypePath, Debug, Deserialize, JsonSchema)]
 pub struct WorldConfig {
     #[serde(default)]
     pub camera: CameraConfig,
@@ -21,6 +23,18 @@ pub struct WorldConfig {
     pub sun: Option<SunConfig>,
     #[serde(default)]
     pub render: Option<RenderConfig>,
+    /// Files whose `[[lights]]` are appended after this file's own, in order.
+    #[serde(default)]
+    pub include: Vec<IncludeConfig>,
+}
+
+/// A file included from `world.toml`, holding more lights and possibly further includes.
+#[derive(Debug, Deserialize, JsonSchema, Default)]
+pub struct LightsConfig {
+    #[serde(default)]
+    pub lights: Vec<LightEntry>,
+    #[serde(default)]
+    pub include: Vec<IncludeConfig>,
 }
 
 impl Default for WorldConfig {
@@ -33,6 +47,7 @@ impl Default for WorldConfig {
             skybox: None,
             sun: None,
             render: None,
+            include: Vec::new(),
         }
     }
 }
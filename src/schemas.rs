@@ -0,0 +1,119 @@
+use schemars::{schema_for, Schema};
+use serde_json::json;
+
+use crate::app_config::AppConfig;
+use crate::scenes::{
+    ComboTemplate, EntitiesConfig, EntityTemplate, InputConfig, OverlayConfig, WorldConfig,
+    CURRENT_FORMAT_VERSION, FORMAT_VERSION_KEY,
+};
+
+/// Directory the generated schemas are written to; `.taplo.toml` maps them to files.
+pub const SCHEMA_DIR: &str = "schemas";
+
+/// Every schema file in `SCHEMA_DIR` with its generated contents. The three entity
+/// schemas share `EntityTemplate` but stay separate so editors can map them by file kind.
+pub fn generate_schemas() -> Vec<(&'static str, String)> {
+    let scene_files = [
+        ("3d.entity.schema.json", entity_schema()),
+        ("2d.entity.schema.json", entity_schema()),
+        ("light.entity.schema.json", entity_schema()),
+        ("combo.entity.schema.json", schema_for!(ComboTemplate)),
+        ("world.schema.json", schema_for!(WorldConfig)),
+        ("entities.schema.json", schema_for!(EntitiesConfig)),
+        ("input.schema.json", schema_for!(InputConfig)),
+        ("overlay.schema.json", schema_for!(OverlayConfig)),
+    ];
+    let mut schemas: Vec<_> = scene_files
+        .into_iter()
+        .map(|(file, schema)| (file, render(scene_file(schema))))
+        .collect();
+    schemas.push(("config.schema.json", render(schema_for!(AppConfig))));
+    schemas
+}
+
+/// Adds the keys the scene file loader reads and removes before a file is deserialized,
+/// so they are not in the config types.
+fn scene_file(mut schema: Schema) -> Schema {
+    let properties = schema
+        .as_object_mut()
+        .and_then(|object| object.get_mut("properties"))
+        .and_then(|properties| properties.as_object_mut());
+    if let Some(properties) = properties {
+        properties.insert(
+            FORMAT_VERSION_KEY.to_string(),
+            json!({
+                "description": "Scene format the file is written for; older files are \
+                                migrated when loaded.",
+                "type": "integer",
+                "minimum": 1,
+                "maximum": CURRENT_FORMAT_VERSION,
+            }),
+        );
+        properties.insert(
+            "vars".to_string(),
+            json!({
+                "description": "Variables referenced as ${name} from string values. The \
+                                world file's apply to the whole scene, any other file's \
+                                to that file alone.",
+                "type": "object",
+            }),
+        );
+    }
+    schema
+}
+
+/// A template with `extends` may take any field from its parent, `name` included, so
+/// nothing is required at the top level.
+fn entity_schema() -> Schema {
+    let mut schema = schema_for!(EntityTemplate);
+    if let Some(object) = schema.as_object_mut() {
+        object.remove("required");
+    }
+    schema
+}
+
+fn render(schema: Schema) -> String {
+    let mut text = serde_json::to_string_pretty(&schema).expect("schemas serialize to JSON");
+    text.push('\n');
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use super::*;
+
+    #[test]
+    fn checked_in_schemas_are_up_to_date() {
+        for (file, contents) in generate_schemas() {
+            let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join(SCHEMA_DIR)
+                .join(file);
+            let current = fs::read_to_string(&path).unwrap_or_default();
+            assert!(
+                current == contents,
+                "{} is stale; run `cargo run --bin atmos-schemas`",
+                path.display()
+            );
+        }
+    }
+
+    #[test]
+    fn scene_file_schemas_accept_loader_keys() {
+        for (file, contents) in generate_schemas() {
+            let schema: serde_json::Value = serde_json::from_str(&contents).unwrap();
+            let properties = &schema["properties"];
+            let scene_file = file != "config.schema.json";
+            assert_eq!(
+                properties.get(FORMAT_VERSION_KEY).is_some(),
+                scene_file,
+                "{file}"
+            );
+            assert_eq!(properties.get("vars").is_some(), scene_file, "{file}");
+        }
+        let entity = serde_json::to_value(entity_schema()).unwrap();
+        assert!(entity["properties"].get("extends").is_some());
+        assert!(entity["properties"].get("light").is_some());
+    }
+}
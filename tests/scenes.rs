@@ -0,0 +1,593 @@
+mod support;
+
+use std::time::Duration;
+
+use atmos::scenes::{
+    migrate_text, SceneBounds, SceneCamera, SceneFormat, ScenePlacement, SceneShootConfig,
+    Severity, TextAlign, TextLabel,
+};
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy_rapier3d::prelude::*;
+
+use support::TestScene;
+
+fn half_extents(collider: &Collider) -> Vec3 {
+    match collider.as_unscaled_typed_shape() {
+        ColliderView::Cuboid(cuboid) => cuboid.half_extents(),
+        _ => panic!("collider is not a cuboid"),
+    }
+}
+
+fn base_color(scene: &mut TestScene, name: &str) -> Color {
+    let handle = scene
+        .get::<MeshMaterial3d<StandardMaterial>>(name)
+        .0
+        .clone();
+    let materials = scene.resource::<Assets<StandardMaterial>>();
+    materials
+        .get(&handle)
+        .expect("material is loaded")
+        .base_color
+}
+
+#[test]
+fn every_shape_kind_spawns_with_its_collider() {
+    let mut scene = TestScene::load("shapes");
+    assert_eq!(
+        scene.names(),
+        ["ball", "box", "capsule", "circle", "cone", "cylinder", "plane", "torus", "wedge"]
+    );
+
+    assert_eq!(
+        half_extents(scene.get::<Collider>("box")),
+        Vec3::new(1.0, 0.5, 2.0)
+    );
+    assert_eq!(*scene.get::<RigidBody>("box"), RigidBody::Fixed);
+
+    let ball = scene
+        .get::<Collider>("ball")
+        .as_ball()
+        .expect("ball collider");
+    assert_eq!(ball.radius(), 0.5);
+    assert_eq!(*scene.get::<RigidBody>("ball"), RigidBody::Dynamic);
+
+    // A circle is flat, so its collider is a thin cylinder on a child turned upright.
+    assert!(!scene.has::<Collider>("circle"));
+    let colliders = scene.child_components::<Collider>("circle");
+    let [collider] = colliders.as_slice() else {
+        panic!("circle has {} child colliders", colliders.len());
+    };
+    assert_eq!(
+        collider.as_cylinder().expect("cylinder collider").radius(),
+        3.0
+    );
+    assert_eq!(*scene.get::<RigidBody>("circle"), RigidBody::Fixed);
+
+    let cylinder = scene
+        .get::<Collider>("cylinder")
+        .as_cylinder()
+        .expect("cylinder collider");
+    assert_eq!((cylinder.half_height(), cylinder.radius()), (1.0, 0.5));
+
+    // The height includes both caps.
+    let capsule = scene
+        .get::<Collider>("capsule")
+        .as_capsule()
+        .expect("capsule collider");
+    assert_eq!((capsule.half_height(), capsule.radius()), (1.0, 0.5));
+
+    let cone = scene
+        .get::<Collider>("cone")
+        .as_cone()
+        .expect("cone collider");
+    assert_eq!((cone.half_height(), cone.radius()), (1.0, 1.0));
+
+    // Convex pieces around the ring, leaving the hole open.
+    let torus = scene.get::<Collider>("torus");
+    assert!(torus.as_compound().is_some());
+
+    // The plane's collider hangs below it so its top is level with the surface.
+    assert!(!scene.has::<Collider>("plane"));
+    let colliders = scene.child_components::<Collider>("plane");
+    let [collider] = colliders.as_slice() else {
+        panic!("plane has {} child colliders", colliders.len());
+    };
+    assert_eq!(half_extents(collider), Vec3::new(2.0, 0.1, 3.0));
+
+    let wedge = scene.get::<Collider>("wedge");
+    assert!(wedge.as_convex_polyhedron().is_some());
+
+    assert!(scene.diagnostics().is_empty(), "{:?}", scene.diagnostics());
+}
+
+#[test]
+fn world_settings_become_resources() {
+    let mut scene = TestScene::load("shapes");
+
+    let bounds = scene.resource::<SceneBounds>();
+    assert_eq!(bounds.shape, "rectangle");
+    assert_eq!(bounds.min, Vec3::new(-50.0, -20.0, -50.0));
+    assert_eq!(bounds.max, Vec3::new(50.0, 40.0, 50.0));
+
+    assert_eq!(
+        scene.resource::<ClearColor>().0,
+        Color::srgb_u8(0x33, 0x66, 0x99)
+    );
+
+    let shoot = scene.resource::<SceneShootConfig>();
+    assert_eq!(shoot.name, "projectile_sphere");
+    assert_eq!(shoot.trigger, MouseButton::Left);
+    assert_eq!(shoot.action.velocity, 30.0);
+    assert_eq!(shoot.shape.radius, Some(0.5));
+
+    let world = scene.world_mut();
+    let gravity = world
+        .query::<&RapierConfiguration>()
+        .single(world)
+        .expect("one physics context")
+        .gravity;
+    assert_eq!(gravity, Vec3::new(0.0, -9.81, 0.0));
+}
+
+#[test]
+fn dynamic_bodies_fall_once_physics_steps() {
+    let mut scene = TestScene::load("shapes");
+    assert_eq!(
+        scene.get::<Transform>("ball").translation,
+        Vec3::new(10.0, 10.0, 0.0)
+    );
+
+    scene.step(60);
+
+    assert!(scene.get::<Transform>("ball").translation.y < 9.0);
+    assert!(scene.get::<Velocity>("ball").linvel.y < 0.0);
+    assert_eq!(scene.get::<Transform>("box").translation, Vec3::ZERO);
+}
+
+#[test]
+fn attached_combo_parts_are_children_of_their_target() {
+    let mut scene = TestScene::load("combos");
+
+    assert_eq!(
+        scene.parent("lit_pillar_light").as_deref(),
+        Some("lit_pillar_body")
+    );
+    assert_eq!(scene.parent("lit_pillar_body"), None);
+    // The attach offset is relative to the target.
+    assert_eq!(
+        scene.get::<Transform>("lit_pillar_light").translation,
+        Vec3::new(0.0, 2.5, 0.0)
+    );
+    assert_eq!(
+        scene.get::<Transform>("lit_pillar_body").translation,
+        Vec3::new(5.0, 2.0, 0.0)
+    );
+
+    // Shared physics puts the only rigid body on the root part.
+    assert!(scene.has::<RigidBody>("lit_pillar_body"));
+    assert!(scene.has::<PointLight>("lit_pillar_light"));
+    assert!(!scene.has::<RigidBody>("lit_pillar_light"));
+
+    assert!(scene.diagnostics().is_empty(), "{:?}", scene.diagnostics());
+}
+
+#[test]
+fn text_labels_attach_to_combo_parts() {
+    let mut scene = TestScene::load("combos");
+
+    assert_eq!(
+        scene.parent("lit_pillar_label").as_deref(),
+        Some("lit_pillar_body")
+    );
+    assert_eq!(
+        scene.get::<Transform>("lit_pillar_label").translation,
+        Vec3::new(0.0, 3.5, 0.0)
+    );
+    // The part's override replaces the template's text.
+    let label = scene.get::<TextLabel>("lit_pillar_label");
+    assert_eq!(label.text, "friction 0.2");
+    assert_eq!(label.font_size, 0.5);
+    assert_eq!(label.align, TextAlign::Left);
+    assert_eq!(label.color, Color::srgb_u8(255, 255, 0));
+    assert!(!label.fixed_screen_size);
+
+    // Drawn on a quad of its own, with nothing to collide with.
+    assert_eq!(
+        scene.child_components::<Mesh3d>("lit_pillar_label").len(),
+        1
+    );
+    assert!(!scene.has::<Collider>("lit_pillar_label"));
+}
+
+#[test]
+fn stacked_combos_spawn_one_copy_per_level() {
+    let mut scene = TestScene::load("combos");
+
+    for (level, y) in [(1, 1.0), (2, 3.0), (3, 5.0)] {
+        let name = format!("tower_{level}_slab");
+        assert_eq!(
+            scene.get::<Transform>(&name).translation,
+            Vec3::new(-5.0, y, 0.0)
+        );
+        assert_eq!(scene.get::<ScenePlacement>(&name).index, 1);
+    }
+    assert!(!scene.names().contains(&"tower_4_slab".to_string()));
+}
+
+#[test]
+fn json_templates_and_ron_combos_spawn_like_toml_ones() {
+    let mut scene = TestScene::load("formats");
+
+    assert_eq!(scene.names(), ["crate", "pair_ball", "pair_crate"]);
+    assert_eq!(
+        scene.get::<Transform>("crate").translation,
+        Vec3::new(0.0, 0.5, 0.0)
+    );
+    assert_eq!(half_extents(scene.get::<Collider>("crate")), Vec3::splat(0.5));
+    assert_eq!(
+        scene.get::<Transform>("pair_ball").translation,
+        Vec3::new(5.0, 1.5, 0.0)
+    );
+    // `kind: sphere` is a RON unit variant.
+    assert!(scene.get::<Collider>("pair_ball").as_ball().is_some());
+    assert_eq!(*scene.get::<RigidBody>("pair_ball"), RigidBody::Fixed);
+    assert_eq!(base_color(&mut scene, "pair_crate"), Color::srgb_u8(165, 42, 42));
+
+    assert!(scene.diagnostics().is_empty(), "{:?}", scene.diagnostics());
+}
+
+#[test]
+fn placement_overrides_replace_template_values() {
+    let mut scene = TestScene::load("overrides");
+
+    assert_eq!(
+        half_extents(scene.get::<Collider>("plain_crate")),
+        Vec3::splat(0.5)
+    );
+    assert_eq!(*scene.get::<RigidBody>("plain_crate"), RigidBody::Dynamic);
+    assert_eq!(scene.get::<Transform>("plain_crate").scale, Vec3::ONE);
+    assert_eq!(
+        base_color(&mut scene, "plain_crate"),
+        Color::srgb_u8(255, 0, 0)
+    );
+
+    assert_eq!(
+        half_extents(scene.get::<Collider>("big_crate")),
+        Vec3::new(1.0, 0.5, 0.5)
+    );
+    assert_eq!(*scene.get::<RigidBody>("big_crate"), RigidBody::Fixed);
+    assert_eq!(scene.get::<Transform>("big_crate").scale, Vec3::splat(2.0));
+    assert_eq!(
+        base_color(&mut scene, "big_crate"),
+        Color::srgb_u8(0, 255, 0)
+    );
+
+    let placement = scene.get::<ScenePlacement>("big_crate");
+    assert_eq!(placement.index, 1);
+    assert!(placement.primary);
+
+    assert!(scene.diagnostics().is_empty(), "{:?}", scene.diagnostics());
+}
+
+#[test]
+fn missing_templates_are_reported_and_skipped() {
+    let mut scene = TestScene::load("broken");
+
+    assert!(scene.names().contains(&"crate".to_string()));
+    let missing = scene
+        .diagnostics()
+        .iter()
+        .find(|entry| entry.field.as_deref() == Some("entities[1].template"))
+        .expect("a diagnostic for the missing template");
+    assert_eq!(missing.severity, Severity::Error);
+    assert!(missing.file.ends_with("broken/entities.toml"));
+    assert!(missing.message.contains("entities/missing.3D.toml"));
+    assert!(scene.get_resource::<SceneShootConfig>().is_none());
+}
+
+#[test]
+fn models_spawn_their_meshes_with_a_generated_collider() {
+    let mut scene = TestScene::load("models");
+
+    // The crate's node sits one unit up inside the model.
+    let transforms = scene.child_components::<Transform>("boxed_crate");
+    let [mesh, collider] = transforms.as_slice() else {
+        panic!("boxed_crate has {} children", transforms.len());
+    };
+    assert_eq!(mesh.translation, Vec3::Y);
+    assert_eq!(collider.translation, Vec3::Y);
+    assert!(!scene.has::<Collider>("boxed_crate"));
+    let colliders = scene.child_components::<Collider>("boxed_crate");
+    let [collider] = colliders.as_slice() else {
+        panic!("boxed_crate has {} child colliders", colliders.len());
+    };
+    assert_eq!(half_extents(collider), Vec3::splat(0.5));
+    assert_eq!(*scene.get::<RigidBody>("boxed_crate"), RigidBody::Fixed);
+
+    let hull = scene.get::<Collider>("hull_crate");
+    assert!(hull.as_convex_polyhedron().is_some());
+    let handles = scene.child_components::<MeshMaterial3d<StandardMaterial>>("hull_crate");
+    let [handle] = handles.as_slice() else {
+        panic!("hull_crate has {} child meshes", handles.len());
+    };
+    let handle = handle.0.clone();
+    let materials = scene.resource::<Assets<StandardMaterial>>();
+    assert_eq!(
+        materials
+            .get(&handle)
+            .expect("material is loaded")
+            .base_color,
+        Color::srgb_u8(255, 0, 0)
+    );
+
+    // The lid node sits half a unit up inside a shelf two units along x, and keeps that
+    // offset when picked out of the model on its own.
+    let transforms = scene.child_components::<Transform>("lid");
+    assert_eq!(transforms, [&Transform::from_xyz(2.0, 0.5, 0.0)]);
+
+    assert!(scene.diagnostics().is_empty(), "{:?}", scene.diagnostics());
+}
+
+#[test]
+fn per_axis_scale_stretches_shapes_and_colliders() {
+    let mut scene = TestScene::load("overrides");
+    scene.step(1);
+
+    // Axes left out of the table keep a scale of 1.
+    assert_eq!(
+        scene.get::<Transform>("wall").scale,
+        Vec3::new(4.0, 2.0, 1.0)
+    );
+    assert_eq!(
+        scene.get::<Collider>("wall").scale(),
+        Vec3::new(4.0, 2.0, 1.0)
+    );
+
+    // The circle's collider lies in the circle's plane, so it is stretched into the same
+    // ellipse whichever way the circle is turned. Its height runs along the circle's Z.
+    let colliders = scene.child_components::<Collider>("oval");
+    let [collider] = colliders.as_slice() else {
+        panic!("oval has {} child colliders", colliders.len());
+    };
+    assert!(
+        collider.scale().abs_diff_eq(Vec3::new(2.0, 1.0, 3.0), 1e-4),
+        "{}",
+        collider.scale()
+    );
+
+    assert!(scene.diagnostics().is_empty(), "{:?}", scene.diagnostics());
+}
+
+#[test]
+fn rotations_can_look_at_a_point_or_turn_about_an_axis() {
+    let mut scene = TestScene::load("overrides");
+
+    let forward = scene.get::<Transform>("pointer").forward();
+    assert!(forward.abs_diff_eq(Vec3::X, 1e-5), "{forward:?}");
+    let forward = scene.get::<Transform>("turned").forward();
+    assert!(forward.abs_diff_eq(Vec3::NEG_X, 1e-5), "{forward:?}");
+}
+
+#[test]
+fn child_placements_ride_on_their_parent_body() {
+    let mut scene = TestScene::load("hierarchy");
+
+    assert_eq!(scene.parent("cargo").as_deref(), Some("cart"));
+    // The placement transform is relative to the parent.
+    assert_eq!(
+        scene.get::<Transform>("cargo").translation,
+        Vec3::new(1.0, 0.75, 0.0)
+    );
+    // The crate's collider joins the cart's body instead of keeping a body of its own.
+    assert!(scene.has::<RigidBody>("cart"));
+    assert!(!scene.has::<RigidBody>("cargo"));
+    assert!(scene.has::<Collider>("cargo"));
+    assert!(matches!(
+        scene.get::<ColliderMassProperties>("cargo"),
+        ColliderMassProperties::Mass(mass) if *mass == 1.0
+    ));
+
+    scene.step(60);
+    assert_eq!(
+        scene.get::<Transform>("cargo").translation,
+        Vec3::new(1.0, 0.75, 0.0)
+    );
+    assert!(scene.diagnostics().is_empty(), "{:?}", scene.diagnostics());
+}
+
+#[test]
+fn parents_and_includes_named_by_variables_are_loaded() {
+    let mut scene = TestScene::load("inherit");
+
+    for name in ["blue_crate", "row_front"] {
+        assert_eq!(base_color(&mut scene, name), Color::srgb_u8(0, 0, 255));
+        assert_eq!(*scene.get::<RigidBody>(name), RigidBody::Fixed);
+    }
+    assert_eq!(
+        scene.get::<Transform>("row_front").translation,
+        Vec3::new(5.0, 5.0, 2.0)
+    );
+    assert!(scene.diagnostics().is_empty(), "{:?}", scene.diagnostics());
+}
+
+#[test]
+fn unknown_and_cyclic_parents_are_reported() {
+    let mut scene = TestScene::load("broken");
+
+    let parent_error = |field: &str| {
+        scene
+            .diagnostics()
+            .iter()
+            .find(|entry| entry.field.as_deref() == Some(field))
+            .unwrap_or_else(|| panic!("a diagnostic for {field}"))
+            .message
+            .clone()
+    };
+    assert!(parent_error("entities[2].parent").contains("'nowhere'"));
+    assert!(parent_error("entities[3].parent").contains("chicken -> egg -> chicken"));
+    assert!(parent_error("entities[4].parent").contains("egg -> chicken -> egg"));
+
+    // Each is still placed, just without a parent.
+    for name in ["orphan", "chicken", "egg"] {
+        assert_eq!(scene.parent(name), None);
+    }
+}
+
+#[test]
+fn terrain_follows_its_heightmap_with_a_matching_heightfield() {
+    let mut scene = TestScene::load("terrain");
+
+    let ColliderView::HeightField(heightfield) = scene.get::<Collider>("mesa").as_typed_shape()
+    else {
+        panic!("terrain collider is not a heightfield");
+    };
+    // Nine vertices a side, resampled from the five pixel heightmap.
+    assert_eq!((heightfield.nrows(), heightfield.ncols()), (8, 8));
+    assert_eq!(heightfield.scale(), Vec3::new(8.0, 2.0, 8.0));
+
+    let handle = scene.get::<Mesh3d>("mesa").0.clone();
+    let meshes = scene.resource::<Assets<Mesh>>();
+    let mesh = meshes.get(&handle).expect("terrain mesh is added");
+    assert_eq!(mesh.count_vertices(), 81);
+    assert!(mesh.attribute(Mesh::ATTRIBUTE_NORMAL).is_some());
+    assert!(mesh.attribute(Mesh::ATTRIBUTE_UV_0).is_some());
+
+    // The ball comes to rest on the flat top of the mesa, two units up.
+    scene.step(120);
+    let ball = scene.get::<Transform>("ball").translation;
+    assert!((ball.y - 2.5).abs() < 0.05, "{ball}");
+    assert!(Vec2::new(ball.x, ball.z).length() < 0.05, "{ball}");
+    assert!(scene.diagnostics().is_empty(), "{:?}", scene.diagnostics());
+}
+
+#[test]
+fn terrain_splat_layers_paint_by_height_and_slope() {
+    let mut scene = TestScene::load("terrain");
+    // The baked texture reaches the image assets on the next update.
+    scene.step(1);
+
+    let material = scene
+        .get::<MeshMaterial3d<StandardMaterial>>("mesa")
+        .0
+        .clone();
+    let materials = scene.resource::<Assets<StandardMaterial>>();
+    let material = materials.get(&material).expect("material is loaded");
+    assert_eq!(material.base_color, Color::WHITE);
+    let texture = material
+        .base_color_texture
+        .clone()
+        .expect("splat layers bake a base color texture");
+    let texture_id = texture.id();
+    let images = scene.resource::<Assets<Image>>();
+    let texture = images.get(&texture).expect("splat texture is added");
+    assert_eq!((texture.width(), texture.height()), (16, 16));
+
+    let assert_texel = |x, y, expected: Srgba| {
+        let texel = texture.get_color_at(x, y).unwrap().to_srgba();
+        let difference = (texel.to_vec4() - expected.to_vec4()).abs().max_element();
+        assert!(difference < 0.01, "texel ({x}, {y}) is {texel:?}");
+    };
+    // Low and level at the corner, high and level on top, steep halfway up the side.
+    assert_texel(0, 0, Srgba::hex("c2b280").unwrap());
+    assert_texel(8, 8, Srgba::rgb(0.0, 1.0, 0.0));
+    assert_texel(8, 2, Srgba::rgb_u8(128, 128, 128));
+
+    // Both placements of the template share one bake.
+    let far_mesa = scene
+        .get::<MeshMaterial3d<StandardMaterial>>("far_mesa")
+        .0
+        .clone();
+    let materials = scene.resource::<Assets<StandardMaterial>>();
+    let far_texture = materials
+        .get(&far_mesa)
+        .and_then(|material| material.base_color_texture.clone());
+    assert_eq!(far_texture.map(|texture| texture.id()), Some(texture_id));
+}
+
+#[test]
+fn billboards_turn_toward_the_scene_camera() {
+    let mut scene = TestScene::load("sprites");
+    let camera = Transform::from_xyz(0.0, 6.0, 10.0).looking_at(Vec3::ZERO, Vec3::Y);
+    scene.world_mut().spawn((SceneCamera, camera));
+    // The camera's global transform is filled in at the end of the first update.
+    scene.step(2);
+
+    let extents = half_extents(scene.get::<Collider>("marker"));
+    assert!((extents.x - 0.5).abs() < 1e-4 && (extents.y - 1.0).abs() < 1e-4);
+
+    // Turned about Y only, toward the camera across the ground.
+    let facing = scene.get::<Transform>("marker").rotation * Vec3::Z;
+    let toward = Vec3::new(-4.0, 0.0, 10.0).normalize();
+    assert!(facing.abs_diff_eq(toward, 1e-4), "marker faces {facing}");
+
+    // Screen-aligned: the flag takes the camera's own rotation.
+    let flag = scene.get::<Transform>("flag").rotation;
+    assert!(
+        flag.abs_diff_eq(camera.rotation, 1e-4),
+        "flag is turned {flag}"
+    );
+}
+
+#[test]
+fn sprite_sheets_step_through_their_frames() {
+    let mut scene = TestScene::load("sprites");
+    let frame_time = Duration::from_millis(100);
+    scene
+        .world_mut()
+        .insert_resource(TimeUpdateStrategy::ManualDuration(frame_time));
+    let uv_transform = |scene: &mut TestScene| {
+        let handle = scene
+            .get::<MeshMaterial3d<StandardMaterial>>("flame")
+            .0
+            .clone();
+        let materials = scene.resource::<Assets<StandardMaterial>>();
+        materials
+            .get(&handle)
+            .expect("material is loaded")
+            .uv_transform
+    };
+
+    // Four cells in a row, starting on the first.
+    let first = uv_transform(&mut scene);
+    let cell = Mat2::from_diagonal(Vec2::new(0.25, 1.0));
+    assert!(first.matrix2.abs_diff_eq(cell, 1e-6));
+    assert_eq!(first.translation, Vec2::ZERO);
+
+    // At 5 fps, 0.3s is halfway through the second frame and 0.5s through the third.
+    scene.step(3);
+    let second = uv_transform(&mut scene);
+    assert!(
+        (second.translation.x - 0.25).abs() < 1e-6,
+        "uv is {second:?}"
+    );
+    assert_eq!(second.translation.y, 0.0);
+    scene.step(2);
+    let third = uv_transform(&mut scene);
+    assert!((third.translation.x - 0.5).abs() < 1e-6, "uv is {third:?}");
+
+    // Only three frames play, so the fourth cell is skipped on the way back to the first.
+    scene.step(2);
+    assert_eq!(uv_transform(&mut scene).translation, Vec2::ZERO);
+}
+
+#[test]
+fn version_1_rotations_are_migrated_to_named_axes() {
+    let text = r#"format_version = 1
+
+[[entities]]
+template = "entities/crate.3D.toml"
+[entities.transform]
+rotation = { roll = -90.0, yaw = 30.0 }
+"#;
+    let migrated = migrate_text(SceneFormat::Toml, text).expect("file migrates");
+    assert_eq!(migrated.from, 1);
+    assert!(
+        migrated
+            .text
+            .contains(r#"rotation = { x = -90.0, z = 30.0, order = "xyz" }"#),
+        "{}",
+        migrated.text
+    );
+    assert!(migrated.text.starts_with("format_version = 2\n"));
+}
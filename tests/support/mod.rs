@@ -0,0 +1,122 @@
+//! Loads the fixture scenes under `tests/fixtures/assets/scenes` in a headless app and
+//! looks up what they spawned by name.
+
+use std::{
+    env,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use atmos::scenes::{
+    headless_app, load_headless_scene, step_headless_scene, ConfigDiagnostic, ConfigDiagnostics,
+};
+use bevy::{asset::AssetPlugin, prelude::*};
+
+const FIXTURE_ROOT: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+
+/// Scene files are read relative to the working directory, which is process-wide, so only
+/// one fixture scene is loaded at a time.
+static SCENE_LOCK: Mutex<()> = Mutex::new(());
+
+pub struct TestScene {
+    app: App,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl TestScene {
+    /// Loads the fixture scene `scene` and waits for every placement to spawn. Physics is
+    /// paused until `step` is called.
+    pub fn load(scene: &str) -> Self {
+        let lock = SCENE_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+        env::set_current_dir(FIXTURE_ROOT).expect("fixture directory is readable");
+        let mut app = headless_app(
+            scene,
+            AssetPlugin {
+                file_path: format!("{FIXTURE_ROOT}/assets"),
+                ..default()
+            },
+        );
+        if let Err(err) = load_headless_scene(&mut app) {
+            panic!("{err}");
+        }
+        Self { app, _lock: lock }
+    }
+
+    /// Advances physics by `steps` fixed steps.
+    pub fn step(&mut self, steps: u32) {
+        step_headless_scene(&mut self.app, steps);
+    }
+
+    /// Names of every named entity in the scene, sorted. Entities without a transform,
+    /// such as the physics context, are not part of it.
+    pub fn names(&mut self) -> Vec<String> {
+        let world = self.app.world_mut();
+        let mut names: Vec<String> = world
+            .query_filtered::<&Name, With<Transform>>()
+            .iter(world)
+            .map(|name| name.to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// The entity called `name`; panics when there is none, listing the names there are.
+    pub fn entity(&mut self, name: &str) -> Entity {
+        let world = self.app.world_mut();
+        let found = world
+            .query::<(Entity, &Name)>()
+            .iter(world)
+            .find(|(_, entity_name)| entity_name.as_str() == name)
+            .map(|(entity, _)| entity);
+        found.unwrap_or_else(|| panic!("no entity named '{name}' in {:?}", self.names()))
+    }
+
+    pub fn has<C: Component>(&mut self, name: &str) -> bool {
+        let entity = self.entity(name);
+        self.app.world().get::<C>(entity).is_some()
+    }
+
+    pub fn get<C: Component>(&mut self, name: &str) -> &C {
+        let entity = self.entity(name);
+        self.app
+            .world()
+            .get::<C>(entity)
+            .unwrap_or_else(|| panic!("'{name}' has no {}", std::any::type_name::<C>()))
+    }
+
+    /// Name of the entity `name` is a child of, if any.
+    pub fn parent(&mut self, name: &str) -> Option<String> {
+        let entity = self.entity(name);
+        let world = self.app.world();
+        let parent = world.get::<ChildOf>(entity)?.parent();
+        world.get::<Name>(parent).map(|name| name.to_string())
+    }
+
+    /// Components of type `C` on the unnamed children of `name`, such as the collider of
+    /// a circle.
+    pub fn child_components<C: Component>(&mut self, name: &str) -> Vec<&C> {
+        let entity = self.entity(name);
+        let world = self.app.world();
+        world
+            .get::<Children>(entity)
+            .into_iter()
+            .flatten()
+            .filter_map(|child| world.get::<C>(*child))
+            .collect()
+    }
+
+    pub fn resource<R: Resource>(&self) -> &R {
+        self.app.world().resource::<R>()
+    }
+
+    pub fn get_resource<R: Resource>(&self) -> Option<&R> {
+        self.app.world().get_resource::<R>()
+    }
+
+    pub fn diagnostics(&self) -> &[ConfigDiagnostic] {
+        &self.resource::<ConfigDiagnostics>().entries
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        self.app.world_mut()
+    }
+}
@@ -0,0 +1,39 @@
+//! Validates the scenes shipped in `assets/scenes`, from the repository root the way
+//! `atmos-validate` runs.
+
+use std::process::Command;
+
+use atmos::scenes::{format_report, validate_scene};
+
+#[test]
+fn the_main_scene_validates_without_errors() {
+    let diagnostics = validate_scene("main");
+    assert!(
+        !diagnostics.iter().any(|diagnostic| diagnostic.is_error()),
+        "{}",
+        format_report(&diagnostics)
+    );
+}
+
+#[test]
+fn scene_paths_outside_the_scene_root_are_rejected() {
+    let validate = |arg: &str| {
+        Command::new(env!("CARGO_BIN_EXE_atmos-validate"))
+            .arg(arg)
+            .output()
+            .expect("atmos-validate runs")
+    };
+
+    let output = validate("assets/scenes/main/");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+
+    // Only scenes under the scene root can be loaded, so a directory elsewhere is refused
+    // rather than read as the scene of the same name.
+    let output = validate("tests/fixtures/assets/scenes/shapes");
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.starts_with("'tests/fixtures/assets/scenes/shapes' is not a scene directory"),
+        "{stderr}"
+    );
+}